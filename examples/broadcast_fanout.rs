@@ -0,0 +1,215 @@
+// ルームのブロードキャストで1つのイベントを多数の購読者に配る際のコストを計測する
+// cargo run --release --example broadcast_fanout -- [購読者数] [メッセージ数]
+//
+// before : broadcast::Sender<String> (購読者ごとにrecvでStringをコピーしてからフレームにする)
+// after  : サーバーと同じくルームのタスクでpublishし、共有したUtf8Bytesをそのままフレームにする
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use axum::extract::ws::Message;
+use chat_app_api::{
+    domain::{
+        entity::{
+            chat::Chat, create_room::CreateRoom, event_log::EventLog, pub_user_info::PubUserInfo,
+            room::Resume, room_event::RoomEvent,
+        },
+        repository::room_repository::RoomRepository,
+    },
+    infrastructure::repository::room_repository_impl::RoomRepositoryImpl,
+    RoomDb,
+};
+use tokio::sync::{broadcast, mpsc};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+struct Report {
+    elapsed: Duration,
+    allocations: usize,
+    allocated_bytes: usize,
+}
+
+fn reset_counters() -> Instant {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    ALLOCATED_BYTES.store(0, Ordering::Relaxed);
+    Instant::now()
+}
+
+fn report(start: Instant) -> Report {
+    Report {
+        elapsed: start.elapsed(),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+// 購読者はそれぞれのタスクでmessages件のイベントを受け取り、ソケットに書き込むフレームにする
+fn spawn_subscriber<T, F>(
+    mut receiver: broadcast::Receiver<T>,
+    messages: usize,
+    done_sender: mpsc::Sender<usize>,
+    to_frame: F,
+) where
+    T: Clone + Send + 'static,
+    F: Fn(T) -> Message + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut written = 0;
+        for _ in 0..messages {
+            let Ok(event) = receiver.recv().await else {
+                break;
+            };
+            if let Message::Text(frame) = black_box(to_frame(event)) {
+                written += frame.len();
+            }
+        }
+        let _ = done_sender.send(written).await;
+    });
+}
+
+// 変更前: 購読者ごとにStringをコピーする
+async fn fan_out_strings(events: Vec<String>, subscribers: usize) -> Report {
+    let messages = events.len();
+    let (sender, _) = broadcast::channel::<String>(messages);
+    let (done_sender, mut done_receiver) = mpsc::channel(subscribers);
+    for _ in 0..subscribers {
+        spawn_subscriber(
+            sender.subscribe(),
+            messages,
+            done_sender.clone(),
+            |event: String| Message::Text(event.into()),
+        );
+    }
+    drop(done_sender);
+
+    let start = reset_counters();
+    for event in events {
+        let _ = sender.send(event);
+    }
+    while done_receiver.recv().await.is_some() {}
+    report(start)
+}
+
+// 変更後: サーバーと同じ経路でルームに参加し、ルームのタスクでシリアライズしたイベントを共有する
+async fn fan_out_room(chats: Vec<Chat>, subscribers: usize) -> Report {
+    let messages = chats.len();
+    let repo = RoomRepositoryImpl::new(RoomDb::new());
+    let user_info = PubUserInfo {
+        user_id: "user_id".to_string(),
+        user_name: "user_name".to_string(),
+    };
+    let payload = CreateRoom {
+        room_name: "room".to_string(),
+        ..Default::default()
+    };
+    let room_info = repo
+        .open_new_room(&payload, &user_info, None)
+        .await
+        .unwrap();
+
+    let (done_sender, mut done_receiver) = mpsc::channel(subscribers);
+    let mut disconnects = Vec::with_capacity(subscribers);
+    for i in 0..subscribers {
+        let (disconnect_sender, disconnect_receiver) = mpsc::unbounded_channel();
+        disconnects.push(disconnect_receiver);
+        let resume = repo
+            .join(
+                &room_info.room_id,
+                &format!("connection{}", i),
+                &user_info,
+                None,
+                disconnect_sender,
+            )
+            .await
+            .unwrap();
+        let Resume::Replay(_, receiver, _) = resume else {
+            panic!("failed to join the room");
+        };
+        spawn_subscriber(receiver, messages, done_sender.clone(), Message::Text);
+    }
+    drop(done_sender);
+
+    let start = reset_counters();
+    for chat in chats {
+        repo.publish(&room_info.room_id, RoomEvent::Chat(chat))
+            .await
+            .unwrap();
+    }
+    while done_receiver.recv().await.is_some() {}
+    report(start)
+}
+
+fn print_report(name: &str, report: &Report, deliveries: usize) {
+    println!(
+        "{:<7} {:>10.2?} {:>12} {:>14} {:>10.2}",
+        name,
+        report.elapsed,
+        report.allocations,
+        report.allocated_bytes,
+        report.allocated_bytes as f64 / deliveries as f64,
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let subscribers: usize = args.next().and_then(|s| s.parse().ok()).unwrap_or(1000);
+    let messages: usize = args.next().and_then(|s| s.parse().ok()).unwrap_or(100);
+
+    let chats: Vec<Chat> = (0..messages)
+        .map(|i| {
+            let text = format!("message {} {}", i, "lorem ipsum ".repeat(20));
+            Chat::from_str("user_id", "user_name", &text)
+        })
+        .collect();
+    // 変更前の経路でも実際にルームに流れるイベントと同じ形式でシリアライズする
+    let mut log = EventLog::new(messages);
+    let strings: Vec<String> = chats
+        .iter()
+        .map(|chat| {
+            log.append(&RoomEvent::Chat(chat.clone()))
+                .unwrap()
+                .1
+                .to_string()
+        })
+        .collect();
+    let deliveries = subscribers * messages;
+
+    println!(
+        "subscribers: {}, messages: {}, payload: {} bytes",
+        subscribers,
+        messages,
+        strings[0].len()
+    );
+    println!(
+        "{:<7} {:>10} {:>12} {:>14} {:>10}",
+        "", "elapsed", "allocations", "bytes", "bytes/msg"
+    );
+
+    let before = fan_out_strings(strings, subscribers).await;
+    print_report("before", &before, deliveries);
+
+    let after = fan_out_room(chats, subscribers).await;
+    print_report("after", &after, deliveries);
+}
@@ -0,0 +1,2 @@
+ALTER TABLE user_data
+    ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
@@ -1,4 +1,20 @@
-use chat_app_api::{route::app, AppState, RoomDb, UserDb};
+use std::{path::PathBuf, time::Duration};
+
+use chat_app_api::{
+    domain::entity::{
+        hook_target_policy::HookTargetPolicy,
+        idle_expiry::IdleExpiry,
+        import_config::ImportConfig,
+        orphan_policy::OrphanPolicy,
+        retention_policy::{RetentionLimits, RetentionPolicy},
+    },
+    jobs::{
+        message_sweeper::spawn_message_sweeper, retention_purger::spawn_retention_purger,
+        room_reaper::spawn_room_reaper, sanction_sweeper::spawn_sanction_sweeper,
+    },
+    route::app,
+    AppState, FavoriteDb, IncomingHookDb, MembershipDb, MessageDb, OutgoingHookDb, RoomDb, UserDb,
+};
 use tracing::info;
 
 #[tokio::main]
@@ -13,8 +29,90 @@ async fn main() {
     let database_url = dotenvy::var("DATABASE_URL").unwrap();
     let user_db = UserDb::connect(&database_url).await.unwrap();
     let room_db = RoomDb::new();
+    let message_db = MessageDb::new();
+    let incoming_hook_db = IncomingHookDb::new();
+    // 開発用。trueの場合はhttp://やローカルネットワーク内の送信Webhookを許可する
+    let outgoing_hook_db = OutgoingHookDb::new().with_target_policy(HookTargetPolicy {
+        allow_insecure: dotenvy::var("OUTGOING_HOOK_ALLOW_INSECURE")
+            .map(|allow| allow.parse().unwrap())
+            .unwrap_or_default(),
+    });
+    let membership_db = MembershipDb::new();
+    let favorite_db = FavoriteDb::new();
+    // transfer, archive, deleteのいずれか。未設定の場合はtransfer
+    let orphan_policy = dotenvy::var("ORPHAN_ROOM_POLICY")
+        .map(|policy| policy.parse::<OrphanPolicy>().unwrap())
+        .unwrap_or_default();
+
+    spawn_message_sweeper(room_db.clone(), message_db.clone(), Duration::from_secs(1));
+
+    spawn_sanction_sweeper(
+        room_db.clone(),
+        membership_db.clone(),
+        Duration::from_secs(60),
+    );
+
+    // 未設定の場合はアイドル期限を設定したルームのみ削除する
+    let idle_expiry = IdleExpiry {
+        default_ttl: dotenvy::var("ROOM_IDLE_TTL_SECS")
+            .ok()
+            .map(|secs| chrono::Duration::seconds(secs.parse().unwrap())),
+        warning: chrono::Duration::seconds(
+            dotenvy::var("ROOM_EXPIRY_WARNING_SECS")
+                .map(|secs| secs.parse().unwrap())
+                .unwrap_or(86_400),
+        ),
+    };
+    // 削除したルームを復元できる期間
+    let delete_grace = chrono::Duration::seconds(
+        dotenvy::var("ROOM_DELETE_GRACE_SECS")
+            .map(|secs| secs.parse().unwrap())
+            .unwrap_or(604_800),
+    );
+    spawn_room_reaper(
+        room_db.clone(),
+        membership_db.clone(),
+        message_db.clone(),
+        incoming_hook_db.clone(),
+        outgoing_hook_db.clone(),
+        favorite_db.clone(),
+        idle_expiry,
+        delete_grace,
+        Duration::from_secs(60),
+    );
+
+    // forever, days:<日数>, messages:<件数>のいずれか。未設定の場合はforever
+    let retention_limits = RetentionLimits::new(
+        dotenvy::var("RETENTION_DEFAULT")
+            .map(|policy| policy.parse::<RetentionPolicy>().unwrap())
+            .unwrap_or_default(),
+        dotenvy::var("RETENTION_MAX")
+            .map(|policy| policy.parse::<RetentionPolicy>().unwrap())
+            .unwrap_or_default(),
+    )
+    .unwrap();
+    spawn_retention_purger(
+        room_db.clone(),
+        message_db.clone(),
+        retention_limits,
+        Duration::from_secs(60),
+    );
 
-    let app_state = AppState::new(room_db, user_db);
+    let app_state = AppState::new(
+        room_db,
+        user_db,
+        message_db,
+        incoming_hook_db,
+        outgoing_hook_db,
+        membership_db,
+        favorite_db,
+    )
+    .with_orphan_policy(orphan_policy)
+    .with_retention_limits(retention_limits)
+    // 未設定の場合はリクエストボディのZIPのみ取り込める
+    .with_import_config(ImportConfig {
+        import_dir: dotenvy::var("SLACK_IMPORT_DIR").ok().map(PathBuf::from),
+    });
     let app = app(app_state, origins);
 
     axum::serve(listener, app).await.unwrap();
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// 監査ログに記録するルームの管理操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditAction {
+    RoomRenamed,
+    // 名前以外の設定の変更。detailに変更した項目を入れる
+    RoomUpdated,
+    RoomArchived,
+    RoomUnarchived,
+    RoomDeleted,
+    RoomRestored,
+    RoleChanged,
+    MemberKicked,
+    MemberBanned,
+    MemberMuted,
+    BanLifted,
+    MuteLifted,
+    MessageDeleted,
+    OwnershipTransferred,
+}
+
+// 追記のみで、変更や削除はしない
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    // ルームごとに1から増加する番号
+    pub seq: u64,
+    pub room_id: String,
+    pub action: AuditAction,
+    // 操作したユーザー
+    pub actor_id: String,
+    pub actor_name: String,
+    // 操作の対象のユーザーID。メッセージの削除ではメッセージIDで、detailに投稿者のIDを入れる
+    pub target_id: Option<String>,
+    pub detail: Option<String>,
+    pub time: DateTime<Utc>,
+}
@@ -0,0 +1,7 @@
+use super::audit_entry::AuditEntry;
+
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    // 続きがない場合はNone
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQuery {
+    pub limit: Option<usize>,
+    // 前のページの最後のエントリーを指す文字列
+    pub cursor: Option<String>,
+}
@@ -1,22 +1,52 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::Serialize;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Chat {
-    user_id: String,
-    user_name: String,
-    text: String,
-    time: DateTime<Utc>,
+    pub message_id: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub text: String,
+    pub time: DateTime<Utc>,
+    pub is_bot: bool,
+    // スレッドの返信の場合は親メッセージのID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_at: Option<DateTime<Utc>>,
 }
 
 impl Chat {
     pub fn from_str(user_id: &str, user_name: &str, text: &str) -> Self {
         Self {
+            message_id: Uuid::new_v4().to_string(),
             user_id: user_id.to_string(),
             user_name: user_name.to_string(),
             text: text.to_string(),
             time: Utc::now(),
+            is_bot: false,
+            reply_to: None,
+            expire_at: None,
         }
     }
+
+    // Webhookなどユーザー以外から投稿されたメッセージ
+    pub fn from_bot(bot_id: &str, display_name: &str, text: &str) -> Self {
+        Self {
+            is_bot: true,
+            ..Self::from_str(bot_id, display_name, text)
+        }
+    }
+
+    // 送信時刻からttl経過後に自動削除されるメッセージにする
+    pub fn expire_in(mut self, ttl: Duration) -> Self {
+        self.expire_at = Some(self.time + ttl);
+        self
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expire_at.is_some_and(|expire_at| expire_at <= now)
+    }
 }
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use validator::Validate;
+
+// クライアントから送られてくるチャットフレーム
+// JSONでない場合はテキストのみのメッセージとして扱う
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatPayload {
+    pub text: String,
+    #[validate(range(min = 1, max = 604_800))]
+    pub ttl_secs: Option<u64>,
+    // 再送時の重複排除とack/nackの対応付けに使う
+    #[serde(alias = "client_msg_id")]
+    #[validate(length(min = 1, max = 64))]
+    pub client_msg_id: Option<String>,
+}
+
+impl ChatPayload {
+    pub fn parse(frame: &str) -> Self {
+        serde_json::from_str(frame).unwrap_or_else(|_| Self {
+            text: frame.to_string(),
+            ttl_secs: None,
+            client_msg_id: None,
+        })
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatQuery {
+    // 最後に受け取ったイベントのseq
+    // 指定した場合はそれより後のイベントを再送してからライブのイベントを流す
+    pub since: Option<u64>,
+}
@@ -1,4 +1,4 @@
-use axum::{async_trait, extract::FromRequestParts};
+use axum::extract::FromRequestParts;
 use axum_extra::extract::CookieJar;
 use http::request::Parts;
 use serde::{Deserialize, Serialize};
@@ -17,7 +17,6 @@ pub struct Claims {
     pub exp: usize,
 }
 
-#[async_trait]
 impl<S> FromRequestParts<S> for Claims
 where
     S: Send + Sync,
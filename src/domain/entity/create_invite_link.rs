@@ -0,0 +1,23 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use super::room_role::RoomRole;
+
+// 招待リンクの有効期限のデフォルト(秒)
+const DEFAULT_INVITE_EXPIRES_IN_SECS: u64 = 86_400;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInviteLink {
+    #[serde(default = "default_expires_in_secs")]
+    #[validate(range(min = 60, max = 2_592_000))]
+    pub expires_in_secs: u64,
+    #[validate(range(min = 1, max = 10_000))]
+    pub max_uses: Option<u32>,
+    // 省略するとメンバーとして参加させる。オーナーは指定できない
+    pub role: Option<RoomRole>,
+}
+
+fn default_expires_in_secs() -> u64 {
+    DEFAULT_INVITE_EXPIRES_IN_SECS
+}
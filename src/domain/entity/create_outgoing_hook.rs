@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use super::hook_event::HookEventKind;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOutgoingHook {
+    #[validate(url, length(max = 2048))]
+    pub callback_url: String,
+    #[validate(length(min = 1, max = 4))]
+    pub events: Vec<HookEventKind>,
+}
@@ -1,9 +1,50 @@
 use serde::Deserialize;
 use validator::Validate;
 
-#[derive(Debug, Clone, Deserialize, Validate)]
+use super::{
+    overflow_policy::OverflowPolicy,
+    retention_policy::RetentionPolicy,
+    room_tag::{validate_category, validate_tags},
+    visibility::Visibility,
+};
+
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateRoom {
     #[validate(length(min = 1, max = 30))]
     pub room_name: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    #[validate(length(max = 100))]
+    pub topic: Option<String>,
+    // ルーム内の全メッセージに適用されるデフォルトのTTL(秒)
+    #[validate(range(min = 1, max = 604_800))]
+    pub message_ttl_secs: Option<u64>,
+    // 省略するとサーバーのデフォルトを使う
+    pub retention: Option<RetentionPolicy>,
+    #[serde(default)]
+    pub visibility: Visibility,
+    // 初めて参加するユーザーに一度だけ求めるパスワード
+    #[validate(length(min = 4, max = 128))]
+    pub password: Option<String>,
+    // 同時に参加できるユーザー数。省略すると無制限
+    #[validate(range(min = 1, max = 10_000))]
+    pub capacity: Option<u32>,
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+    // 接続も投稿もない状態がこの秒数続くと削除される。省略するとサーバーの設定に従う
+    #[validate(range(min = 1, max = 31_536_000))]
+    pub idle_ttl_secs: Option<u64>,
+    // trueの場合は接続や投稿がなくても削除しない
+    #[serde(default)]
+    pub keep_forever: bool,
+    // trueの場合はオーナーとモデレーターのみ投稿できる
+    #[serde(default)]
+    pub announcement_only: bool,
+    // 一覧の絞り込みに使う自由なタグ。大文字・小文字は区別しない
+    #[serde(default)]
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Vec<String>,
+    #[validate(custom(function = "validate_category"))]
+    pub category: Option<String>,
 }
@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+
+use axum::extract::ws::Utf8Bytes;
+use serde::Serialize;
+
+use super::room_event::RoomEvent;
+
+// 再接続時に再送するためにルームごとに保持するイベント数
+pub const EVENT_LOG_CAPACITY: usize = 1024;
+
+// クライアントに送られるイベント
+// seqはルームごとに1から単調増加する
+#[derive(Debug, Serialize)]
+pub struct SequencedEvent<'a> {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: &'a RoomEvent,
+}
+
+// ブロードキャストしたイベントの直近の履歴
+#[derive(Debug)]
+pub struct EventLog {
+    latest_seq: u64,
+    events: VecDeque<(u64, Utf8Bytes)>,
+    capacity: usize,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            latest_seq: 0,
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn latest_seq(&self) -> u64 {
+        self.latest_seq
+    }
+
+    // 次のseqを割り当ててシリアライズし、履歴に追加する
+    // シリアライズは1度だけ行い、履歴と全ての購読者のソケットで同じバッファを共有する
+    pub fn append(&mut self, event: &RoomEvent) -> Result<(u64, Utf8Bytes), serde_json::Error> {
+        let seq = self.latest_seq + 1;
+        let serialized: Utf8Bytes = serde_json::to_string(&SequencedEvent { seq, event })?.into();
+        self.latest_seq = seq;
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back((seq, serialized.clone()));
+        Ok((seq, serialized))
+    }
+
+    // sinceより後のイベントを返す
+    // 履歴から既に消えている場合やsinceが未来の場合はNone
+    pub fn since(&self, since: u64) -> Option<Vec<Utf8Bytes>> {
+        let oldest_seq = self.latest_seq - self.events.len() as u64 + 1;
+        if since > self.latest_seq || since + 1 < oldest_seq {
+            return None;
+        }
+        let skip = (since + 1 - oldest_seq) as usize;
+        Some(
+            self.events
+                .iter()
+                .skip(skip)
+                .map(|(_, event)| event.clone())
+                .collect(),
+        )
+    }
+}
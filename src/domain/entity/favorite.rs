@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+
+// ユーザーごとのお気に入りのルーム。並び順はユーザーのリスト内の位置で表す
+#[derive(Debug, Clone, PartialEq)]
+pub struct Favorite {
+    pub room_id: String,
+    // ユーザー個人の通知の抑制。ミュートしたルームは未読にならない。ルームの他のメンバーには影響しない
+    pub muted: bool,
+    pub added_time: DateTime<Utc>,
+    // 最後に既読にした時刻。これより後に投稿があったルームは未読になる
+    pub last_read_time: DateTime<Utc>,
+}
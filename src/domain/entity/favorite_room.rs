@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::room_info::RoomInfo;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FavoriteRoom {
+    pub position: usize,
+    pub muted: bool,
+    // 既読にした後に投稿があった。ミュートしたルームは常にfalse
+    pub unread: bool,
+    pub added_time: DateTime<Utc>,
+    pub room: RoomInfo,
+}
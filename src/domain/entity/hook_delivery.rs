@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::hook_event::HookEventKind;
+
+// 送信Webhookの配送ログ
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookDelivery {
+    pub delivery_id: String,
+    pub hook_id: String,
+    pub event: HookEventKind,
+    pub attempts: u32,
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_time: DateTime<Utc>,
+    pub updated_time: DateTime<Utc>,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{chat::Chat, pub_room_info::PubRoomInfo, pub_user_info::PubUserInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookEventKind {
+    MessagePosted,
+    MemberJoined,
+    RoomDeleted,
+    RoomExpiring,
+}
+
+impl HookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEventKind::MessagePosted => "messagePosted",
+            HookEventKind::MemberJoined => "memberJoined",
+            HookEventKind::RoomDeleted => "roomDeleted",
+            HookEventKind::RoomExpiring => "roomExpiring",
+        }
+    }
+}
+
+// 送信Webhookで外部サービスに通知するルームのイベント
+// 送信先はルームの外にあるため、ルーム情報には参加コードなどを含めない
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum HookEvent {
+    MessagePosted(Chat),
+    MemberJoined(PubUserInfo),
+    RoomDeleted(PubRoomInfo),
+    // 接続も投稿もないため、expiresTimeに削除される
+    #[serde(rename_all = "camelCase")]
+    RoomExpiring {
+        #[serde(flatten)]
+        room_info: PubRoomInfo,
+        expires_time: DateTime<Utc>,
+    },
+}
+
+impl HookEvent {
+    pub fn kind(&self) -> HookEventKind {
+        match self {
+            HookEvent::MessagePosted(_) => HookEventKind::MessagePosted,
+            HookEvent::MemberJoined(_) => HookEventKind::MemberJoined,
+            HookEvent::RoomDeleted(_) => HookEventKind::RoomDeleted,
+            HookEvent::RoomExpiring { .. } => HookEventKind::RoomExpiring,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct HookPayload {
+    #[validate(length(min = 1, max = 4000))]
+    pub text: String,
+    #[validate(length(min = 1, max = 30))]
+    pub display_name: String,
+}
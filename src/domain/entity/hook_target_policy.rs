@@ -0,0 +1,127 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use http::Uri;
+
+// 送信Webhookの送信先の制限
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HookTargetPolicy {
+    // 開発用。http://のURLとループバックやプライベートアドレスへの送信を許可する
+    pub allow_insecure: bool,
+}
+
+impl HookTargetPolicy {
+    // ホスト名の名前解決後のアドレスは送信時にallows_addrで確認する
+    pub fn allows_url(&self, url: &str) -> bool {
+        let Ok(uri) = url.parse::<Uri>() else {
+            return false;
+        };
+        match uri.scheme_str() {
+            Some("https") => {}
+            Some("http") if self.allow_insecure => {}
+            _ => return false,
+        }
+        let Some(host) = uri.host() else {
+            return false;
+        };
+        if self.allow_insecure {
+            return true;
+        }
+
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match host.parse::<IpAddr>() {
+            Ok(addr) => is_public(addr),
+            Err(_) => {
+                let host = host.trim_end_matches('.').to_ascii_lowercase();
+                host != "localhost" && !host.ends_with(".localhost")
+            }
+        }
+    }
+
+    pub fn allows_addr(&self, addr: IpAddr) -> bool {
+        self.allow_insecure || is_public(addr)
+    }
+}
+
+// ループバック、リンクローカル、プライベートなど外部に公開されていないアドレスを除く
+fn is_public(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => is_public_v4(addr),
+        IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+            Some(addr) => is_public_v4(addr),
+            None => is_public_v6(addr),
+        },
+    }
+}
+
+fn is_public_v4(addr: Ipv4Addr) -> bool {
+    let [a, b, ..] = addr.octets();
+    !(addr.is_loopback()
+        || addr.is_private()
+        || addr.is_link_local()
+        || addr.is_unspecified()
+        || addr.is_broadcast()
+        || addr.is_multicast()
+        || addr.is_documentation()
+        // 0.0.0.0/8と100.64.0.0/10(キャリアグレードNAT)
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64))
+}
+
+fn is_public_v6(addr: Ipv6Addr) -> bool {
+    let first = addr.segments()[0];
+    !(addr.is_loopback()
+        || addr.is_unspecified()
+        || addr.is_multicast()
+        // fc00::/7(ユニークローカル)とfe80::/10(リンクローカル)
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allows_url() {
+        let policy = HookTargetPolicy::default();
+        assert!(policy.allows_url("https://example.com/callback"));
+        assert!(policy.allows_url("https://93.184.215.14/callback"));
+        assert!(!policy.allows_url("http://example.com/callback"));
+        assert!(!policy.allows_url("ftp://example.com/callback"));
+        assert!(!policy.allows_url("https://localhost/callback"));
+        assert!(!policy.allows_url("https://api.localhost./callback"));
+        assert!(!policy.allows_url("https://127.0.0.1/callback"));
+        assert!(!policy.allows_url("https://10.0.0.1/callback"));
+        assert!(!policy.allows_url("https://172.16.0.1/callback"));
+        assert!(!policy.allows_url("https://192.168.0.1/callback"));
+        assert!(!policy.allows_url("https://169.254.169.254/latest/meta-data"));
+        assert!(!policy.allows_url("https://100.64.0.1/callback"));
+        assert!(!policy.allows_url("https://0.0.0.0/callback"));
+        assert!(!policy.allows_url("https://[::1]/callback"));
+        assert!(!policy.allows_url("https://[fe80::1]/callback"));
+        assert!(!policy.allows_url("https://[fd00::1]/callback"));
+        assert!(!policy.allows_url("https://[::ffff:127.0.0.1]/callback"));
+        assert!(!policy.allows_url("not a url"));
+
+        let policy = HookTargetPolicy {
+            allow_insecure: true,
+        };
+        assert!(policy.allows_url("http://127.0.0.1:8000/callback"));
+        assert!(policy.allows_url("https://localhost/callback"));
+        assert!(!policy.allows_url("ftp://example.com/callback"));
+    }
+
+    #[test]
+    fn test_allows_addr() {
+        let policy = HookTargetPolicy::default();
+        assert!(policy.allows_addr("8.8.8.8".parse().unwrap()));
+        assert!(policy.allows_addr("2001:4860:4860::8888".parse().unwrap()));
+        assert!(!policy.allows_addr("127.0.0.1".parse().unwrap()));
+        assert!(!policy.allows_addr("::ffff:10.0.0.1".parse().unwrap()));
+
+        let policy = HookTargetPolicy {
+            allow_insecure: true,
+        };
+        assert!(policy.allows_addr("127.0.0.1".parse().unwrap()));
+    }
+}
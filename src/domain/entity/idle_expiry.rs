@@ -0,0 +1,10 @@
+use chrono::Duration;
+
+// 接続も投稿もないルームを削除するまでの期間の設定
+#[derive(Debug, Clone, Copy)]
+pub struct IdleExpiry {
+    // ルームごとの期限がない場合に使う期限。Noneの場合は期限を設定したルームのみ削除する
+    pub default_ttl: Option<Duration>,
+    // 削除のどれだけ前にオーナーに通知するか
+    pub warning: Duration,
+}
@@ -0,0 +1,8 @@
+use std::path::PathBuf;
+
+// Slackエクスポートの取り込みの設定
+#[derive(Debug, Clone, Default)]
+pub struct ImportConfig {
+    // pathで指定できるサーバー上のディレクトリ。Noneの場合はpathを指定できない
+    pub import_dir: Option<PathBuf>,
+}
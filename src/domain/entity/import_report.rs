@@ -0,0 +1,27 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedItem {
+    pub location: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedRoom {
+    pub channel_name: String,
+    // dry-runの場合はNone
+    pub room_id: Option<String>,
+    pub message_count: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub rooms: Vec<ImportedRoom>,
+    pub users_created: usize,
+    pub messages_imported: usize,
+    pub skipped: Vec<SkippedItem>,
+}
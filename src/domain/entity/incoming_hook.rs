@@ -0,0 +1,37 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+// 1フックあたりのレート制限(RATE_LIMIT_WINDOW_SECS秒間にRATE_LIMIT_COUNT回まで)
+const RATE_LIMIT_COUNT: u32 = 30;
+const RATE_LIMIT_WINDOW_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingHook {
+    pub hook_id: String,
+    pub hook_name: String,
+    pub token: String,
+    pub room_id: String,
+    pub created_by_id: String,
+    pub created_time: DateTime<Utc>,
+    #[serde(skip)]
+    pub window_start: DateTime<Utc>,
+    #[serde(skip)]
+    pub window_count: u32,
+}
+
+impl IncomingHook {
+    // 現在のウィンドウに空きがあれば1回分消費する
+    pub fn try_acquire(&mut self, now: DateTime<Utc>) -> bool {
+        if now - self.window_start >= Duration::seconds(RATE_LIMIT_WINDOW_SECS) {
+            self.window_start = now;
+            self.window_count = 0;
+        }
+
+        if self.window_count >= RATE_LIMIT_COUNT {
+            return false;
+        }
+        self.window_count += 1;
+        true
+    }
+}
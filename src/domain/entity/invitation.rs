@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// 招待されたユーザーが承諾するか辞退するまで残る
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invitation {
+    pub invitation_id: String,
+    pub room_id: String,
+    pub room_name: String,
+    pub invitee_id: String,
+    pub invited_by_id: String,
+    pub invited_by_name: String,
+    pub created_time: DateTime<Utc>,
+}
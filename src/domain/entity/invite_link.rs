@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::room_role::RoomRole;
+
+// トークンを知っていれば誰でも期限と回数の上限まで使える招待リンク
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteLink {
+    pub invite_id: String,
+    pub token: String,
+    pub room_id: String,
+    pub room_name: String,
+    // 参加したユーザーに付与するロール
+    pub role: RoomRole,
+    // Noneは回数無制限
+    pub max_uses: Option<u32>,
+    pub uses: u32,
+    pub expires_time: DateTime<Utc>,
+    pub created_by_id: String,
+    pub created_time: DateTime<Utc>,
+}
+
+impl InviteLink {
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_time && self.max_uses.is_none_or(|max_uses| self.uses < max_uses)
+    }
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use validator::Validate;
+
+// パスワードのないルームではボディごと省略できる
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinRoom {
+    #[serde(default)]
+    #[validate(length(min = 1, max = 128))]
+    pub password: Option<String>,
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::room_role::RoomRole;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Membership {
+    pub room_id: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub role: RoomRole,
+    pub joined_time: DateTime<Utc>,
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+
+use super::chat::Chat;
+
+// メッセージを(time, message_id)順に読み進めるためのカーソル
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MessageCursor {
+    pub time: DateTime<Utc>,
+    pub message_id: String,
+}
+
+impl MessageCursor {
+    pub fn from_chat(chat: &Chat) -> Self {
+        Self {
+            time: chat.time,
+            message_id: chat.message_id.to_owned(),
+        }
+    }
+}
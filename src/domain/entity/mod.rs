@@ -1,10 +1,62 @@
 pub mod access_token;
+pub mod audit_entry;
+pub mod audit_page;
+pub mod audit_query;
 pub mod auth_payload;
 pub mod chat;
+pub mod chat_payload;
+pub mod chat_query;
 pub mod claims;
+pub mod create_hook;
+pub mod create_invitation;
+pub mod create_invite_link;
+pub mod create_outgoing_hook;
 pub mod create_room;
 pub mod create_user_payload;
+pub mod event_log;
+pub mod export_query;
+pub mod favorite;
+pub mod favorite_room;
+pub mod hook_delivery;
+pub mod hook_event;
+pub mod hook_payload;
+pub mod hook_target_policy;
+pub mod idle_expiry;
+pub mod import_config;
+pub mod import_report;
+pub mod incoming_hook;
+pub mod invitation;
+pub mod invite_link;
+pub mod join_room;
+pub mod membership;
+pub mod message_cursor;
+pub mod moderation_payload;
+pub mod orphan_policy;
+pub mod outgoing_hook;
+pub mod overflow_policy;
+pub mod ownership_transfer;
+pub mod post_policy;
+pub mod post_rejection;
+pub mod pub_room_info;
 pub mod pub_user_info;
+pub mod retention_policy;
+pub mod retention_purge;
 pub mod room;
+pub mod room_cursor;
+pub mod room_event;
 pub mod room_info;
+pub mod room_list_item;
+pub mod room_page;
+pub mod room_query;
+pub mod room_role;
+pub mod room_tag;
+pub mod sanction;
+pub mod slack_archive;
+pub mod slack_import_query;
+pub mod submission;
+pub mod transfer_ownership;
+pub mod update_favorite;
+pub mod update_role;
+pub mod update_room;
 pub mod user;
+pub mod visibility;
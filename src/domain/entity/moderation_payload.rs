@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerationPayload {
+    #[validate(length(min = 1, max = 64))]
+    pub user_id: String,
+    // kickでは使われない。省略した場合は解除されるまで続く
+    #[validate(range(min = 1, max = 31_536_000))]
+    pub duration_secs: Option<u64>,
+    #[validate(length(max = 200))]
+    pub reason: Option<String>,
+}
@@ -0,0 +1,24 @@
+use std::str::FromStr;
+
+// オーナーのアカウントが削除されたルームの扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrphanPolicy {
+    // 最も古くからいるモデレーターに譲渡する。いなければアーカイブする
+    #[default]
+    Transfer,
+    Archive,
+    Delete,
+}
+
+impl FromStr for OrphanPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transfer" => Ok(Self::Transfer),
+            "archive" => Ok(Self::Archive),
+            "delete" => Ok(Self::Delete),
+            _ => Err(format!("unknown orphan room policy: {}", s)),
+        }
+    }
+}
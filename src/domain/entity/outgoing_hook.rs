@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::hook_event::HookEventKind;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingHook {
+    pub hook_id: String,
+    pub room_id: String,
+    pub callback_url: String,
+    // ペイロードのHMAC署名に使う共有シークレット
+    pub secret: String,
+    pub events: Vec<HookEventKind>,
+    pub created_by_id: String,
+    pub created_time: DateTime<Utc>,
+}
+
+impl OutgoingHook {
+    pub fn is_subscribed(&self, kind: HookEventKind) -> bool {
+        self.events.contains(&kind)
+    }
+}
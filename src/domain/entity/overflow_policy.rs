@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// 定員に達したルームに接続しようとした場合の扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowPolicy {
+    // 409で接続を拒否する
+    #[default]
+    Reject,
+    // 投稿できない観覧者として接続させる
+    Spectate,
+}
@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// 譲渡先のユーザーが承諾するまでオーナーは変わらない
+// ルームごとに未処理の譲渡は1つまで
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnershipTransfer {
+    pub room_id: String,
+    pub room_name: String,
+    pub from_id: String,
+    pub from_name: String,
+    pub to_id: String,
+    pub to_name: String,
+    pub created_time: DateTime<Utc>,
+}
@@ -0,0 +1,10 @@
+// 投稿者によって適用するルームの制限。アーカイブ済みのルームには誰も投稿できない
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostPolicy {
+    // モデレーター未満のメンバー。アナウンス専用とスローモードの対象
+    Member,
+    // モデレーター以上。アナウンス専用とスローモードの対象外
+    Moderator,
+    // 受信Webhookの投稿。メンバーと同じく、アナウンス専用とフックごとのスローモードの対象
+    Bot,
+}
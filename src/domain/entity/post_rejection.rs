@@ -0,0 +1,10 @@
+use chrono::Duration;
+
+// ルームの状態によって投稿を流さなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostRejection {
+    Archived,
+    AnnouncementOnly,
+    // 次に投稿できるまでの待ち時間
+    SlowMode(Duration),
+}
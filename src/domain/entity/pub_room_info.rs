@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::{
+    overflow_policy::OverflowPolicy, retention_policy::RetentionPolicy, room_info::RoomInfo,
+    visibility::Visibility,
+};
+
+// 接続中の全てのクライアントに流すルーム情報
+// 観覧者や参加コードを知らないメンバーにも届くため、参加コードやオーナー向けの設定は含めない
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PubRoomInfo {
+    pub room_id: String,
+    pub room_name: String,
+    pub description: Option<String>,
+    pub topic: Option<String>,
+    pub created_by_id: String,
+    pub created_by_name: String,
+    pub updated_time: DateTime<Utc>,
+    pub message_ttl_secs: Option<u64>,
+    pub retention: RetentionPolicy,
+    pub slow_mode_secs: Option<u32>,
+    pub announcement_only: bool,
+    pub visibility: Visibility,
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub password_protected: bool,
+    pub capacity: Option<u32>,
+    pub overflow: OverflowPolicy,
+    pub archived: bool,
+}
+
+impl From<&RoomInfo> for PubRoomInfo {
+    fn from(room_info: &RoomInfo) -> Self {
+        Self {
+            room_id: room_info.room_id.clone(),
+            room_name: room_info.room_name.clone(),
+            description: room_info.description.clone(),
+            topic: room_info.topic.clone(),
+            created_by_id: room_info.created_by_id.clone(),
+            created_by_name: room_info.created_by_name.clone(),
+            updated_time: room_info.updated_time,
+            message_ttl_secs: room_info.message_ttl_secs,
+            retention: room_info.retention,
+            slow_mode_secs: room_info.slow_mode_secs,
+            announcement_only: room_info.announcement_only,
+            visibility: room_info.visibility,
+            tags: room_info.tags.clone(),
+            category: room_info.category.clone(),
+            password_protected: room_info.join_password_hash.is_some(),
+            capacity: room_info.capacity,
+            overflow: room_info.overflow,
+            archived: room_info.is_archived(),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// 日数の上限は約100年、件数の上限は100万件
+const MAX_RETENTION_DAYS: u32 = 36_500;
+const MAX_RETENTION_MESSAGES: u32 = 1_000_000;
+
+// ルームのメッセージを保持する範囲。範囲外になったメッセージはバックグラウンドで削除される
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RetentionPolicy {
+    #[default]
+    Forever,
+    // 投稿からこの日数が過ぎたメッセージを削除する
+    Days {
+        days: u32,
+    },
+    // 新しい順にこの件数を超えたメッセージを削除する
+    Messages {
+        count: u32,
+    },
+}
+
+impl RetentionPolicy {
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::Forever => true,
+            Self::Days { days } => (1..=MAX_RETENTION_DAYS).contains(&days),
+            Self::Messages { count } => (1..=MAX_RETENTION_MESSAGES).contains(&count),
+        }
+    }
+
+    // 同じ種類の上限より長く保持しない場合にtrue。種類が異なる上限は削除時に合わせて適用する
+    fn is_within(&self, max: &RetentionPolicy) -> bool {
+        match (*self, *max) {
+            (_, Self::Forever) => true,
+            (Self::Forever, _) => false,
+            (Self::Days { days }, Self::Days { days: max }) => days <= max,
+            (Self::Messages { count }, Self::Messages { count: max }) => count <= max,
+            _ => true,
+        }
+    }
+}
+
+// "forever", "days:30", "messages:10000"の形式
+impl FromStr for RetentionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let policy = match s.split_once(':') {
+            None if s == "forever" => Self::Forever,
+            Some(("days", days)) => Self::Days {
+                days: days
+                    .parse()
+                    .map_err(|_| format!("invalid days: {}", days))?,
+            },
+            Some(("messages", count)) => Self::Messages {
+                count: count
+                    .parse()
+                    .map_err(|_| format!("invalid count: {}", count))?,
+            },
+            _ => return Err(format!("unknown retention policy: {}", s)),
+        };
+        if !policy.is_valid() {
+            return Err(format!("retention policy out of range: {}", s));
+        }
+        Ok(policy)
+    }
+}
+
+// サーバー全体の保持期間の設定
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionLimits {
+    // 保持期間を指定せずに作成したルームに適用する
+    pub default: RetentionPolicy,
+    // ルームの設定に関わらず、これを超えるメッセージは削除する
+    pub max: RetentionPolicy,
+}
+
+impl RetentionLimits {
+    pub fn new(default: RetentionPolicy, max: RetentionPolicy) -> Result<Self, String> {
+        if !default.is_within(&max) {
+            return Err(format!(
+                "default retention {:?} exceeds maximum {:?}",
+                default, max
+            ));
+        }
+        Ok(Self { default, max })
+    }
+
+    // ルームに設定できる保持期間の場合にtrue
+    pub fn allows(&self, policy: &RetentionPolicy) -> bool {
+        policy.is_valid() && policy.is_within(&self.max)
+    }
+
+    // 削除の基準とする(保持する日数, 保持する件数)。ルームの設定と上限のうち短い方を使う
+    pub fn effective(&self, policy: &RetentionPolicy) -> (Option<u32>, Option<u32>) {
+        let mut days = None;
+        let mut count = None;
+        for policy in [policy, &self.max] {
+            match *policy {
+                RetentionPolicy::Forever => {}
+                RetentionPolicy::Days { days: d } => {
+                    days = Some(days.map_or(d, |days: u32| days.min(d)))
+                }
+                RetentionPolicy::Messages { count: c } => {
+                    count = Some(count.map_or(c, |count: u32| count.min(c)))
+                }
+            }
+        }
+        (days, count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DAYS_30: RetentionPolicy = RetentionPolicy::Days { days: 30 };
+    const DAYS_365: RetentionPolicy = RetentionPolicy::Days { days: 365 };
+    const MESSAGES_100: RetentionPolicy = RetentionPolicy::Messages { count: 100 };
+    const MESSAGES_1000: RetentionPolicy = RetentionPolicy::Messages { count: 1000 };
+
+    #[test]
+    fn test_from_str() {
+        // テスト対象
+        assert_eq!("forever".parse(), Ok(RetentionPolicy::Forever));
+        assert_eq!("days:30".parse(), Ok(DAYS_30));
+        assert_eq!("messages:100".parse(), Ok(MESSAGES_100));
+        // 範囲外や不正な形式は受け付けない
+        for s in [
+            "",
+            "days",
+            "days:0",
+            "days:36501",
+            "days:-1",
+            "messages:0",
+            "messages:1000001",
+            "hours:1",
+            "forever:1",
+        ] {
+            assert!(s.parse::<RetentionPolicy>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_is_within() {
+        // テスト対象
+        // 上限が無期限なら何でも、無期限は上限がある場合は超える
+        assert!(RetentionPolicy::Forever.is_within(&RetentionPolicy::Forever));
+        assert!(DAYS_365.is_within(&RetentionPolicy::Forever));
+        assert!(!RetentionPolicy::Forever.is_within(&DAYS_30));
+        // 同じ種類は値で比べる
+        assert!(DAYS_30.is_within(&DAYS_365));
+        assert!(DAYS_30.is_within(&DAYS_30));
+        assert!(!DAYS_365.is_within(&DAYS_30));
+        assert!(!MESSAGES_1000.is_within(&MESSAGES_100));
+        // 種類が異なる場合は削除時に両方を適用する
+        assert!(DAYS_365.is_within(&MESSAGES_100));
+        assert!(MESSAGES_1000.is_within(&DAYS_30));
+    }
+
+    #[test]
+    fn test_allows() {
+        let limits = RetentionLimits::new(DAYS_30, DAYS_365).unwrap();
+
+        // テスト対象
+        assert!(limits.allows(&DAYS_365));
+        assert!(limits.allows(&MESSAGES_1000));
+        assert!(!limits.allows(&RetentionPolicy::Days { days: 366 }));
+        assert!(!limits.allows(&RetentionPolicy::Forever));
+        // 範囲外の値は上限に関わらず受け付けない
+        let unlimited = RetentionLimits::default();
+        assert!(unlimited.allows(&RetentionPolicy::Forever));
+        assert!(!unlimited.allows(&RetentionPolicy::Days { days: 0 }));
+        // デフォルトは上限を超えられない
+        assert!(RetentionLimits::new(DAYS_365, DAYS_30).is_err());
+    }
+
+    #[test]
+    fn test_effective() {
+        let limits =
+            RetentionLimits::new(RetentionPolicy::Forever, RetentionPolicy::Forever).unwrap();
+
+        // テスト対象
+        assert_eq!(limits.effective(&RetentionPolicy::Forever), (None, None));
+        assert_eq!(limits.effective(&DAYS_30), (Some(30), None));
+
+        // ルームの設定と上限のうち短い方を使う
+        let limits = RetentionLimits::new(DAYS_30, DAYS_365).unwrap();
+        assert_eq!(
+            limits.effective(&RetentionPolicy::Forever),
+            (Some(365), None)
+        );
+        assert_eq!(limits.effective(&DAYS_30), (Some(30), None));
+        // 種類が異なる場合は両方を適用する
+        assert_eq!(limits.effective(&MESSAGES_100), (Some(365), Some(100)));
+        let limits = RetentionLimits::new(MESSAGES_100, MESSAGES_100).unwrap();
+        assert_eq!(limits.effective(&MESSAGES_1000), (None, Some(100)));
+    }
+}
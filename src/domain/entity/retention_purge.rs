@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::retention_policy::RetentionPolicy;
+
+// 保持期間によって削除したメッセージの記録
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPurge {
+    pub room_id: String,
+    // 削除した時点のルームの保持期間
+    pub policy: RetentionPolicy,
+    // 削除したメッセージの数
+    pub count: usize,
+    // 削除したメッセージの投稿日時の範囲
+    pub oldest_time: DateTime<Utc>,
+    pub newest_time: DateTime<Utc>,
+    pub purged_time: DateTime<Utc>,
+}
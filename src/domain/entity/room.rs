@@ -1,9 +1,276 @@
-use tokio::sync::broadcast::Sender;
+use std::collections::{HashMap, HashSet};
 
-use super::room_info::RoomInfo;
+use axum::extract::ws::Utf8Bytes;
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::{
+    broadcast::{self, Receiver, Sender},
+    mpsc::UnboundedSender,
+};
 
-#[derive(Debug, Clone)]
+use super::{
+    chat::Chat,
+    event_log::{EventLog, EVENT_LOG_CAPACITY},
+    overflow_policy::OverflowPolicy,
+    post_policy::PostPolicy,
+    post_rejection::PostRejection,
+    pub_user_info::PubUserInfo,
+    room_event::RoomEvent,
+    room_info::RoomInfo,
+};
+
+// パスワードでの参加は1ユーザーあたりウィンドウの間に上限の回数まで試せる
+const JOIN_ATTEMPT_WINDOW_SECS: i64 = 15 * 60;
+const JOIN_ATTEMPT_LIMIT: u32 = 5;
+
+// ルームごとのタスクが所有する状態
+// 1つのタスクからのみ操作されるためロックを持たない
+#[derive(Debug)]
 pub struct Room {
     pub room_info: RoomInfo,
-    pub sender: Sender<String>,
+    // 購読者ごとにコピーしないよう、シリアライズ済みのイベントを参照カウントで共有する
+    // Utf8Bytesのままソケットに書き込めるため、送信時にもコピーしない
+    pub sender: Sender<Utf8Bytes>,
+    pub event_log: EventLog,
+    // 接続IDごとの接続中のユーザー
+    pub members: HashMap<String, Connection>,
+    // スローモードの判定に使う、ユーザーごとの最後に投稿した時刻
+    pub last_posted: HashMap<String, DateTime<Utc>>,
+    // パスワードの総当たりを防ぐための、ユーザーごとのウィンドウの開始時刻と試行回数
+    pub join_attempts: HashMap<String, (DateTime<Utc>, u32)>,
+}
+
+#[derive(Debug)]
+pub struct Connection {
+    pub user_info: PubUserInfo,
+    // その接続にのみ送るイベント。Participating以外を送るとその接続は切断される
+    pub signal: UnboundedSender<RoomEvent>,
+    pub admission: Admission,
+    // 観覧者を参加者にする際に、古くから待っている順に選ぶ
+    pub connected_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Participant,
+    // 定員を超えたため投稿できない観覧者として接続する
+    Spectator,
+}
+
+pub enum Resume {
+    // 再送するイベントと、その後のイベントを受け取るReceiver
+    Replay(Vec<Utf8Bytes>, Receiver<Utf8Bytes>, Admission),
+    GapTooLarge { latest_seq: u64 },
+    // 定員に達しており、観覧も許可されていない
+    Full,
+}
+
+impl Room {
+    pub fn new(room_info: RoomInfo) -> Self {
+        let (sender, _) = broadcast::channel(128);
+        Self {
+            room_info,
+            sender,
+            event_log: EventLog::new(EVENT_LOG_CAPACITY),
+            members: HashMap::new(),
+            last_posted: HashMap::new(),
+            join_attempts: HashMap::new(),
+        }
+    }
+
+    // seqを割り当ててルームにブロードキャストする
+    // 接続中のクライアントがいなくても履歴には残る
+    pub fn publish(&mut self, event: &RoomEvent) -> Result<u64, serde_json::Error> {
+        let (seq, serialized) = self.event_log.append(event)?;
+        if let RoomEvent::Chat(chat) = event {
+            self.room_info.last_activity_time = chat.time;
+            self.room_info.expires_time = None;
+        }
+        let _ = self.sender.send(serialized);
+        Ok(seq)
+    }
+
+    // 投稿をルームの状態と照らし合わせてから流す
+    // 判定と配信を同じタスク内で行うため、判定の後にアーカイブなどの変更が割り込むことはない
+    pub fn publish_chat(
+        &mut self,
+        chat: Chat,
+        policy: PostPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<Option<PostRejection>, serde_json::Error> {
+        if self.room_info.is_archived() {
+            return Ok(Some(PostRejection::Archived));
+        }
+        match policy {
+            PostPolicy::Moderator => {}
+            // Botのスローモードはフックごとに数える
+            PostPolicy::Member | PostPolicy::Bot => {
+                if self.room_info.announcement_only {
+                    return Ok(Some(PostRejection::AnnouncementOnly));
+                }
+                if let Some(wait) = self.acquire_slow_mode(&chat.user_id, now) {
+                    return Ok(Some(PostRejection::SlowMode(wait)));
+                }
+            }
+        }
+        self.publish(&RoomEvent::Chat(chat))?;
+        Ok(None)
+    }
+
+    // sinceより後のイベントを再送してからライブのイベントに切り替える
+    // 履歴の取得と購読を同じタスク内で行うため、取りこぼしも重複も起きない
+    pub fn resume(&self, since: Option<u64>, admission: Admission) -> Resume {
+        let replay = match since {
+            Some(since) => match self.event_log.since(since) {
+                Some(replay) => replay,
+                None => {
+                    return Resume::GapTooLarge {
+                        latest_seq: self.event_log.latest_seq(),
+                    }
+                }
+            },
+            None => Vec::new(),
+        };
+        Resume::Replay(replay, self.sender.subscribe(), admission)
+    }
+
+    // 定員に達している場合はルームの設定に従って観覧者として受け入れるか拒否する
+    // 既に参加者として接続しているユーザーの別の接続は定員に数えない
+    pub fn admit(&self, user_id: &str) -> Option<Admission> {
+        let participating = self.members.values().any(|connection| {
+            connection.admission == Admission::Participant
+                && connection.user_info.user_id == user_id
+        });
+        let full = self
+            .room_info
+            .capacity
+            .is_some_and(|capacity| self.room_info.occupancy >= capacity as usize);
+        if participating || !full {
+            return Some(Admission::Participant);
+        }
+        match self.room_info.overflow {
+            OverflowPolicy::Spectate => Some(Admission::Spectator),
+            OverflowPolicy::Reject => None,
+        }
+    }
+
+    // スローモード中は前回の投稿から設定の秒数が経つまで投稿を受け付けず、残りの待ち時間を返す
+    // 受け付ける場合は投稿した時刻として記録する
+    pub fn acquire_slow_mode(&mut self, user_id: &str, now: DateTime<Utc>) -> Option<Duration> {
+        let slow_mode_secs = self.room_info.slow_mode_secs?;
+        if let Some(last_posted) = self.last_posted.get(user_id) {
+            let next = *last_posted + Duration::seconds(slow_mode_secs as i64);
+            if now < next {
+                return Some(next - now);
+            }
+        }
+        self.last_posted.insert(user_id.to_owned(), now);
+        None
+    }
+
+    // パスワードでの参加を試せるかを判定して記録する。上限に達している場合はウィンドウが終わるまでの時間を返す
+    pub fn acquire_join_attempt(&mut self, user_id: &str, now: DateTime<Utc>) -> Option<Duration> {
+        let window = Duration::seconds(JOIN_ATTEMPT_WINDOW_SECS);
+        // 終わったウィンドウは捨てて、試行したユーザーの数だけ記録が増え続けないようにする
+        self.join_attempts
+            .retain(|_, (window_start, _)| now - *window_start < window);
+        let (window_start, count) = self
+            .join_attempts
+            .entry(user_id.to_owned())
+            .or_insert((now, 0));
+        if *count >= JOIN_ATTEMPT_LIMIT {
+            return Some(*window_start + window - now);
+        }
+        *count += 1;
+        None
+    }
+
+    pub fn add_connection(&mut self, connection_id: String, connection: Connection) {
+        self.members.insert(connection_id, connection);
+        self.refresh_occupancy();
+    }
+
+    // 参加者が抜けて定員に空きができた場合は観覧者を参加者にする
+    pub fn remove_connection(&mut self, connection_id: &str) -> Option<Connection> {
+        let connection = self.members.remove(connection_id);
+        self.refresh_occupancy();
+        self.promote_spectators();
+        connection
+    }
+
+    // 定員に空きがある間、最も古くから接続している観覧者から順に参加者にして通知する
+    // 定員を増やした場合や無制限にした場合にも呼ぶ
+    pub fn promote_spectators(&mut self) {
+        loop {
+            let full = self
+                .room_info
+                .capacity
+                .is_some_and(|capacity| self.room_info.occupancy >= capacity as usize);
+            if full {
+                return;
+            }
+            let oldest = self
+                .members
+                .iter()
+                .filter(|(_, connection)| connection.admission == Admission::Spectator)
+                .min_by_key(|(connection_id, connection)| {
+                    (connection.connected_time, connection_id.as_str())
+                })
+                .map(|(connection_id, _)| connection_id.to_owned());
+            let Some(connection) = oldest.and_then(|id| self.members.get_mut(&id)) else {
+                return;
+            };
+            connection.admission = Admission::Participant;
+            let _ = connection.signal.send(RoomEvent::Participating);
+            self.refresh_occupancy();
+        }
+    }
+
+    // ユーザーの全ての接続を切断し、切断した接続の数を返す
+    pub fn disconnect_user(&mut self, user_id: &str, event: &RoomEvent) -> usize {
+        self.disconnect_where(|connection| connection.user_info.user_id == user_id, event)
+    }
+
+    pub fn disconnect_all(&mut self, event: &RoomEvent) -> usize {
+        self.disconnect_where(|_| true, event)
+    }
+
+    fn disconnect_where(
+        &mut self,
+        predicate: impl Fn(&Connection) -> bool,
+        event: &RoomEvent,
+    ) -> usize {
+        let connection_ids: Vec<String> = self
+            .members
+            .iter()
+            .filter(|(_, connection)| predicate(connection))
+            .map(|(connection_id, _)| connection_id.to_owned())
+            .collect();
+        for connection_id in &connection_ids {
+            if let Some(connection) = self.members.remove(connection_id) {
+                let _ = connection.signal.send(event.clone());
+            }
+        }
+        // 切断し終えてから空いた席を観覧者に回す
+        self.refresh_occupancy();
+        self.promote_spectators();
+        connection_ids.len()
+    }
+
+    fn refresh_occupancy(&mut self) {
+        let mut participants = HashSet::new();
+        let mut spectators = 0;
+        for connection in self.members.values() {
+            match connection.admission {
+                Admission::Participant => {
+                    participants.insert(connection.user_info.user_id.as_str());
+                }
+                Admission::Spectator => spectators += 1,
+            }
+        }
+        self.room_info.occupancy = participants.len();
+        self.room_info.spectators = spectators;
+        // 接続の出入りがあったルームは削除を取り消す
+        self.room_info.last_seen_time = Utc::now();
+        self.room_info.expires_time = None;
+    }
 }
@@ -0,0 +1,119 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use super::{
+    room_info::RoomInfo,
+    room_query::{RoomSort, SortOrder},
+};
+
+// ルームを(ソートキー, room_id)順に読み進めるためのカーソル
+// 同じキーのルームもroom_idで順序が決まるため、ページの境界で重複も欠落も起きない
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RoomCursor {
+    pub key: String,
+    pub room_id: String,
+}
+
+impl RoomCursor {
+    // 時刻は桁数を固定したRFC3339にして文字列のまま比較できるようにする
+    pub fn from_room(room_info: &RoomInfo, sort: RoomSort) -> Self {
+        match sort {
+            RoomSort::Created => Self::from_time(room_info, room_info.created_time),
+            RoomSort::Name => Self {
+                key: room_info.room_name.to_lowercase(),
+                room_id: room_info.room_id.to_owned(),
+            },
+            RoomSort::Activity => Self::from_time(room_info, room_info.last_activity_time),
+        }
+    }
+
+    // 最後の投稿の時刻を指定して、ある時点でのアクティビティ順の位置を表す
+    pub fn from_activity(room_info: &RoomInfo, last_activity_time: DateTime<Utc>) -> Self {
+        Self::from_time(room_info, last_activity_time)
+    }
+
+    fn from_time(room_info: &RoomInfo, time: DateTime<Utc>) -> Self {
+        Self {
+            key: time.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            room_id: room_info.room_id.to_owned(),
+        }
+    }
+
+    // as_ofは1ページ目を返した時刻。続きのページもその時点の並び順で返す
+    pub fn encode(&self, sort: RoomSort, order: SortOrder, as_of: DateTime<Utc>) -> String {
+        let value = serde_json::json!([sort, order, self.key, self.room_id, as_of]);
+        URL_SAFE_NO_PAD.encode(value.to_string())
+    }
+
+    // 並び順が異なる検索で発行されたカーソルは使えない
+    pub fn decode(cursor: &str, sort: RoomSort, order: SortOrder) -> Option<(Self, DateTime<Utc>)> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let (cursor_sort, cursor_order, key, room_id, as_of): (
+            RoomSort,
+            SortOrder,
+            String,
+            String,
+            DateTime<Utc>,
+        ) = serde_json::from_slice(&bytes).ok()?;
+        if cursor_sort != sort || cursor_order != order {
+            return None;
+        }
+        Some((Self { key, room_id }, as_of))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::infrastructure::repository::room_repository_impl::test_room_info;
+
+    use super::*;
+
+    fn cursor() -> RoomCursor {
+        RoomCursor {
+            key: "2024-01-02T03:04:05.000000006Z".to_string(),
+            room_id: "room_id".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let as_of = Utc::now();
+        for sort in [RoomSort::Created, RoomSort::Name, RoomSort::Activity] {
+            for order in [SortOrder::Asc, SortOrder::Desc] {
+                let encoded = cursor().encode(sort, order, as_of);
+                // ヘッダーやクエリにそのまま使える
+                assert!(encoded
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+                // テスト対象
+                let decoded = RoomCursor::decode(&encoded, sort, order);
+                assert_eq!(decoded, Some((cursor(), as_of)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_mismatch() {
+        let encoded = cursor().encode(RoomSort::Created, SortOrder::Desc, Utc::now());
+
+        // テスト対象
+        assert!(RoomCursor::decode(&encoded, RoomSort::Name, SortOrder::Desc).is_none());
+        assert!(RoomCursor::decode(&encoded, RoomSort::Created, SortOrder::Asc).is_none());
+        assert!(RoomCursor::decode("not a cursor", RoomSort::Created, SortOrder::Desc).is_none());
+        let truncated = &encoded[..encoded.len() / 2];
+        assert!(RoomCursor::decode(truncated, RoomSort::Created, SortOrder::Desc).is_none());
+    }
+
+    #[test]
+    fn test_time_key_order() {
+        // 文字列の比較が時刻の比較と一致する
+        let earlier: DateTime<Utc> = "2024-01-02T03:04:05.9Z".parse().unwrap();
+        let later: DateTime<Utc> = "2024-01-02T03:04:10Z".parse().unwrap();
+        let room_info = test_room_info("room_id");
+        assert!(
+            RoomCursor::from_activity(&room_info, earlier)
+                < RoomCursor::from_activity(&room_info, later)
+        );
+    }
+}
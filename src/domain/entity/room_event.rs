@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::{chat::Chat, pub_room_info::PubRoomInfo};
+
+// MessagesExpiredの1つのイベントで通知するメッセージの数
+pub const EXPIRED_BATCH_SIZE: usize = 500;
+
+// ルームにブロードキャストされるイベント
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RoomEvent {
+    Chat(Chat),
+    // ルームの名前・説明・トピックなどの設定が変更された
+    RoomUpdated(Box<PubRoomInfo>),
+    // スローモードが有効・無効になった。無効の場合はsecondsがnull
+    #[serde(rename_all = "camelCase")]
+    SlowMode {
+        seconds: Option<u32>,
+    },
+    // TTLや保持期間を過ぎて削除されたメッセージ。一度に多数削除されるため、まとめて流す
+    #[serde(rename_all = "camelCase")]
+    MessagesExpired {
+        message_ids: Vec<String>,
+    },
+    // モデレーターがメッセージを削除した
+    #[serde(rename_all = "camelCase")]
+    MessageDeleted {
+        message_id: String,
+    },
+    // 再接続時に再送できる範囲を超えていた場合に、そのクライアントにのみ送られる
+    // クライアントは履歴を読み込み直してsinceなしで再接続する
+    #[serde(rename_all = "camelCase")]
+    GapTooLarge {
+        latest_seq: u64,
+    },
+    // clientMsgIdを付けて送信したクライアントにのみ送られる
+    #[serde(rename_all = "camelCase")]
+    Ack {
+        client_msg_id: String,
+        message_id: String,
+        time: DateTime<Utc>,
+    },
+    // スローモードで受け付けなかった場合は、次に投稿できるまでの秒数が付く
+    #[serde(rename_all = "camelCase")]
+    Nack {
+        client_msg_id: String,
+        reason: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        retry_after_secs: Option<u64>,
+    },
+    // clientMsgIdのない投稿を受け付けなかった場合に、送信したクライアントにのみ送られる
+    #[serde(rename_all = "camelCase")]
+    Error {
+        reason: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        retry_after_secs: Option<u64>,
+    },
+    // 定員を超えて観覧者として接続した場合に、その接続にのみ最初に送られる
+    Spectating,
+    // 定員に空きができて観覧者から参加者になった場合に、その接続にのみ送られる
+    Participating,
+    // 定員に達していて接続できなかった場合に送られ、その後切断される
+    RoomFull,
+    // ルームが削除された場合に全ての接続に送られ、その後切断される
+    RoomDeleted,
+    // キック・BANされた接続にのみ送られ、その後切断される
+    #[serde(rename_all = "camelCase")]
+    Kicked {
+        reason: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Banned {
+        reason: Option<String>,
+        expires_time: Option<DateTime<Utc>>,
+    },
+}
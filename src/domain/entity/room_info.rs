@@ -1,12 +1,85 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{ser::SerializeMap, Serialize, Serializer};
 
-#[derive(Debug, Clone, Serialize)]
+use super::{
+    overflow_policy::OverflowPolicy, retention_policy::RetentionPolicy, visibility::Visibility,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RoomInfo {
     pub room_id: String,
     pub room_name: String,
+    pub description: Option<String>,
+    pub topic: Option<String>,
     pub created_by_id: String,
     pub created_by_name: String,
     pub created_time: DateTime<Utc>,
+    // 名前・説明・トピックなどの設定を最後に変更した時刻
+    pub updated_time: DateTime<Utc>,
+    // 最後にメッセージが投稿された時刻。投稿がなければ作成した時刻
+    pub last_activity_time: DateTime<Utc>,
+    pub message_ttl_secs: Option<u64>,
+    // 作成時に指定がなければサーバーのデフォルト
+    pub retention: RetentionPolicy,
+    // 有効な場合、モデレーター未満のメンバーはこの秒数に1回しか投稿できない
+    pub slow_mode_secs: Option<u32>,
+    // 有効な場合、モデレーター未満のメンバーは閲覧のみできる
+    pub announcement_only: bool,
+    pub visibility: Visibility,
+    // 正規化済みのタグ
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    // 口頭でも伝えられる"ABC-123"形式のコード
+    pub join_code: String,
+    // 参加パスワードのハッシュ。レスポンスには有無のみを含める
+    #[serde(rename = "passwordProtected", serialize_with = "serialize_is_some")]
+    pub join_password_hash: Option<String>,
+    // 同時に参加できるユーザー数。Noneは無制限
+    pub capacity: Option<u32>,
+    pub overflow: OverflowPolicy,
+    // 接続中の参加者の数。同じユーザーの複数の接続は1人として数える
+    pub occupancy: usize,
+    // 定員を超えて観覧のみで接続している接続の数
+    pub spectators: usize,
+    // ルームごとのアイドル期限。Noneの場合はサーバーの設定に従う
+    pub idle_ttl_secs: Option<u64>,
+    pub keep_forever: bool,
+    // 最後に接続または切断があった時刻
+    pub last_seen_time: DateTime<Utc>,
+    // 削除が近づいたことをオーナーに通知した後に設定される、削除される予定の時刻
+    // 接続や投稿があると取り消される
+    pub expires_time: Option<DateTime<Utc>>,
+    // アーカイブされたルームは閲覧のみでき、投稿は受け付けない
+    // レスポンスにはアーカイブの有無(archived)と時刻(archivedTime)を含める
+    #[serde(flatten, serialize_with = "serialize_archived")]
+    pub archived_time: Option<DateTime<Utc>>,
+    // オーナーのアカウントが削除されてアーカイブされた時刻。管理者が引き継ぐまで設定される
+    pub orphaned_time: Option<DateTime<Utc>>,
+    // 削除されたルームは猶予期間が過ぎるまでオーナーが復元できる
+    // 削除されたルームはオーナーの削除済み一覧以外では存在しないものとして扱う
+    pub deleted_time: Option<DateTime<Utc>>,
+}
+
+impl RoomInfo {
+    pub fn is_archived(&self) -> bool {
+        self.archived_time.is_some()
+    }
+}
+
+fn serialize_is_some<S>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bool(value.is_some())
+}
+
+fn serialize_archived<S>(value: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut map = serializer.serialize_map(Some(2))?;
+    map.serialize_entry("archived", &value.is_some())?;
+    map.serialize_entry("archivedTime", value)?;
+    map.end()
 }
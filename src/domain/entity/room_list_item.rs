@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+use super::room_info::RoomInfo;
+
+// ルーム一覧の各ルームに、呼び出したユーザーのお気に入りの状態を加えたもの
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomListItem {
+    #[serde(flatten)]
+    pub room_info: RoomInfo,
+    pub favorite: bool,
+    pub muted: bool,
+    // お気に入りのルームで、既読にした後に投稿があった
+    pub unread: bool,
+}
@@ -0,0 +1,7 @@
+use super::room_info::RoomInfo;
+
+pub struct RoomPage {
+    pub rooms: Vec<RoomInfo>,
+    // 続きがない場合はNone
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoomSort {
+    #[default]
+    Created,
+    Name,
+    // 最後にメッセージが投稿された時刻
+    Activity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameMatch {
+    #[default]
+    Contains,
+    Prefix,
+}
+
+// ルーム一覧の検索条件。名前の検索は大文字・小文字を区別しない
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomQuery {
+    pub q: Option<String>,
+    #[serde(default, rename = "match")]
+    pub name_match: NameMatch,
+    pub owner_id: Option<String>,
+    // タグとカテゴリは大文字・小文字を区別せず完全一致で絞り込む
+    pub tag: Option<String>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub sort: RoomSort,
+    pub order: Option<SortOrder>,
+    pub limit: Option<usize>,
+    // 前のページの最後のルームを指す不透明な文字列
+    pub cursor: Option<String>,
+}
+
+impl RoomQuery {
+    // 名前順は昇順、それ以外は新しい順がデフォルト
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or(match self.sort {
+            RoomSort::Name => SortOrder::Asc,
+            RoomSort::Created | RoomSort::Activity => SortOrder::Desc,
+        })
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+// 宣言順に権限が強くなる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RoomRole {
+    Member,
+    Moderator,
+    Owner,
+}
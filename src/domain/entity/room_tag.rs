@@ -0,0 +1,60 @@
+use serde::Serialize;
+use validator::ValidationError;
+
+// 1つのルームに付けられるタグの数と、タグ・カテゴリの長さの上限
+pub const MAX_ROOM_TAGS: usize = 10;
+pub const MAX_TAG_LENGTH: usize = 30;
+
+// タグごとの、そのタグが付いたルームの数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomTagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+// 前後の空白を除いて小文字にする。大文字・小文字の違いは同じタグとして扱う
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+// 正規化して重複を除く。順序は最初に現れた順
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.iter().map(|tag| normalize_tag(tag)) {
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+// タグと同じく正規化する。空の場合はカテゴリなし
+pub fn normalize_category(category: &str) -> Option<String> {
+    Some(normalize_tag(category)).filter(|category| !category.is_empty())
+}
+
+pub fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > MAX_ROOM_TAGS {
+        return Err(ValidationError::new("too_many_tags"));
+    }
+    if !tags.iter().all(|tag| is_valid_label(tag)) {
+        return Err(ValidationError::new("invalid_tag"));
+    }
+    Ok(())
+}
+
+// カテゴリは空文字で削除できるため、空文字は許可する
+pub fn validate_category(category: &str) -> Result<(), ValidationError> {
+    if !category.is_empty() && !is_valid_label(category) {
+        return Err(ValidationError::new("invalid_category"));
+    }
+    Ok(())
+}
+
+fn is_valid_label(label: &str) -> bool {
+    let label = label.trim();
+    !label.is_empty()
+        && label.chars().count() <= MAX_TAG_LENGTH
+        && !label.chars().any(char::is_control)
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SanctionKind {
+    // 再参加できない
+    Ban,
+    // 参加はできるが投稿できない
+    Mute,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sanction {
+    pub room_id: String,
+    pub user_id: String,
+    pub kind: SanctionKind,
+    pub reason: Option<String>,
+    pub issued_by_id: String,
+    pub created_time: DateTime<Utc>,
+    // Noneの場合は解除されるまで続く
+    pub expires_time: Option<DateTime<Utc>>,
+}
+
+impl Sanction {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_time
+            .is_none_or(|expires_time| now < expires_time)
+    }
+}
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+use super::import_report::SkippedItem;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SlackProfile {
+    pub display_name: Option<String>,
+    pub real_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackUser {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub real_name: Option<String>,
+    #[serde(default)]
+    pub profile: SlackProfile,
+}
+
+impl SlackUser {
+    // 表示名 > 本名 > ユーザー名の順で空でないものを使う
+    pub fn display_name(&self) -> &str {
+        [
+            self.profile.display_name.as_deref(),
+            self.profile.real_name.as_deref(),
+            self.real_name.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|name| !name.is_empty())
+        .unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackMessage {
+    pub user: Option<String>,
+    #[serde(default)]
+    pub text: String,
+    pub ts: String,
+    pub thread_ts: Option<String>,
+    pub subtype: Option<String>,
+    pub bot_id: Option<String>,
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlackChannel {
+    pub name: String,
+    pub messages: Vec<SlackMessage>,
+}
+
+// 読み込み済みのSlackエクスポート
+// 読み込めなかったファイルはskippedに記録される
+#[derive(Debug, Clone, Default)]
+pub struct SlackArchive {
+    pub users: Vec<SlackUser>,
+    pub channels: Vec<SlackChannel>,
+    pub skipped: Vec<SkippedItem>,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Deserializer};
+
+use super::retention_policy::RetentionPolicy;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlackImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+    // 取り込み用ディレクトリ内のエクスポートのディレクトリかZIPファイルへの相対パス
+    // 省略した場合はリクエストボディをZIPとして読む
+    pub path: Option<String>,
+    // 作成するルームの保持期間。"forever", "days:30", "messages:10000"の形式
+    // 省略するとサーバーの上限(未設定の場合は無期限)まで保持する
+    #[serde(default, deserialize_with = "deserialize_retention")]
+    pub retention: Option<RetentionPolicy>,
+}
+
+fn deserialize_retention<'de, D>(deserializer: D) -> Result<Option<RetentionPolicy>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(retention) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    retention
+        .parse()
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
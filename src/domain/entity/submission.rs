@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+
+// クライアントが付けたIDでメッセージの重複送信を判定するためのキー
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubmissionKey {
+    pub room_id: String,
+    pub user_id: String,
+    pub client_msg_id: String,
+}
+
+// 受け付けたメッセージ
+// dedupe_untilまでは同じキーで再送されても保存しない
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub message_id: String,
+    pub time: DateTime<Utc>,
+    pub dedupe_until: DateTime<Utc>,
+}
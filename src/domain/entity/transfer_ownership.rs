@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOwnership {
+    #[validate(length(min = 1, max = 64))]
+    pub user_id: String,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use validator::Validate;
+
+// 1人のユーザーが登録できるお気に入りの上限
+pub const MAX_FAVORITES: usize = 100;
+
+// 省略した項目は変更しない。新しく追加する場合、positionを省略すると末尾に、mutedを省略するとfalseになる
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateFavorite {
+    // 0始まりの並び順。リストの長さを超える場合は末尾に置く
+    #[validate(range(max = 99))]
+    pub position: Option<usize>,
+    pub muted: Option<bool>,
+    // trueの場合は既読にする
+    #[serde(default)]
+    pub read: bool,
+}
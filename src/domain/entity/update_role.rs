@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use super::room_role::RoomRole;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRole {
+    pub role: RoomRole,
+}
@@ -0,0 +1,73 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use super::{
+    overflow_policy::OverflowPolicy,
+    retention_policy::RetentionPolicy,
+    room_tag::{validate_category, validate_tags},
+};
+
+// 省略した項目は変更しない。説明とトピックとカテゴリは空文字、定員とアイドル期限とスローモードは0で削除する
+// タグは指定したタグで置き換える
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRoom {
+    #[validate(length(min = 1, max = 30))]
+    pub room_name: Option<String>,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    #[validate(length(max = 100))]
+    pub topic: Option<String>,
+    #[validate(range(max = 10_000))]
+    pub capacity: Option<u32>,
+    pub overflow: Option<OverflowPolicy>,
+    #[validate(range(max = 31_536_000))]
+    pub idle_ttl_secs: Option<u64>,
+    pub keep_forever: Option<bool>,
+    #[validate(range(max = 21_600))]
+    pub slow_mode_secs: Option<u32>,
+    pub announcement_only: Option<bool>,
+    pub retention: Option<RetentionPolicy>,
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+    #[validate(custom(function = "validate_category"))]
+    pub category: Option<String>,
+}
+
+impl UpdateRoom {
+    pub fn is_empty(&self) -> bool {
+        self.room_name.is_none()
+            && self.description.is_none()
+            && self.topic.is_none()
+            && self.capacity.is_none()
+            && self.overflow.is_none()
+            && self.idle_ttl_secs.is_none()
+            && self.keep_forever.is_none()
+            && self.slow_mode_secs.is_none()
+            && self.announcement_only.is_none()
+            && self.retention.is_none()
+            && self.tags.is_none()
+            && self.category.is_none()
+    }
+
+    // 指定された名前以外の項目。監査ログに記録する
+    pub fn setting_names(&self) -> Vec<&'static str> {
+        [
+            ("description", self.description.is_some()),
+            ("topic", self.topic.is_some()),
+            ("capacity", self.capacity.is_some()),
+            ("overflow", self.overflow.is_some()),
+            ("idleTtlSecs", self.idle_ttl_secs.is_some()),
+            ("keepForever", self.keep_forever.is_some()),
+            ("slowModeSecs", self.slow_mode_secs.is_some()),
+            ("announcementOnly", self.announcement_only.is_some()),
+            ("retention", self.retention.is_some()),
+            ("tags", self.tags.is_some()),
+            ("category", self.category.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, is_set)| *is_set)
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+// 非公開のルームはメンバー以外には一覧にも表示されず、参加もできない
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::entity::favorite::Favorite;
+
+use super::error::RepositoryError;
+
+pub trait FavoriteRepository {
+    // 登録済みの場合は指定した項目のみ変更し、positionを指定した場合はその位置に移動する
+    // 新しく登録した場合とreadがtrueの場合は、nowまでを既読にする
+    fn put_favorite(
+        &self,
+        user_id: &str,
+        room_id: &str,
+        position: Option<usize>,
+        muted: Option<bool>,
+        read: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Favorite, RepositoryError>;
+    // ユーザーが指定した順に返す
+    fn get_favorites(&self, user_id: &str) -> Result<Vec<Favorite>, RepositoryError>;
+    fn delete_favorite(&self, user_id: &str, room_id: &str) -> Result<(), RepositoryError>;
+    // 完全に削除されたルームを全てのユーザーのお気に入りから外す
+    fn delete_room(&self, room_id: &str) -> Result<(), RepositoryError>;
+    fn delete_user(&self, user_id: &str) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::entity::incoming_hook::IncomingHook;
+
+use super::error::RepositoryError;
+
+pub trait IncomingHookRepository {
+    fn create_hook(
+        &self,
+        room_id: &str,
+        hook_name: &str,
+        token: &str,
+        created_by_id: &str,
+    ) -> Result<IncomingHook, RepositoryError>;
+    fn get_room_hooks(&self, room_id: &str) -> Result<Vec<IncomingHook>, RepositoryError>;
+    // レート制限の枠を消費できた場合のみSomeを返す
+    fn acquire_hook(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<IncomingHook>, RepositoryError>;
+    fn delete_hook(&self, room_id: &str, hook_id: &str) -> Result<(), RepositoryError>;
+    fn delete_room_hooks(&self, room_id: &str) -> Result<(), RepositoryError>;
+}
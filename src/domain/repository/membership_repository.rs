@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::entity::{
+    audit_entry::AuditEntry,
+    invitation::Invitation,
+    invite_link::InviteLink,
+    membership::Membership,
+    ownership_transfer::OwnershipTransfer,
+    pub_user_info::PubUserInfo,
+    room_info::RoomInfo,
+    room_role::RoomRole,
+    sanction::{Sanction, SanctionKind},
+};
+
+use super::error::RepositoryError;
+
+pub trait MembershipRepository {
+    // 既にメンバーの場合はロールを変更せずにそのメンバーを返す
+    fn add_member(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+        role: RoomRole,
+    ) -> Result<Membership, RepositoryError>;
+    // メンバーでなければ追加し、既にメンバーの場合はロールを変更する
+    fn put_member(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+        role: RoomRole,
+    ) -> Result<Membership, RepositoryError>;
+    fn is_member(&self, room_id: &str, user_id: &str) -> Result<bool, RepositoryError>;
+    fn get_member(&self, room_id: &str, user_id: &str) -> Result<Membership, RepositoryError>;
+    fn set_role(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        role: RoomRole,
+    ) -> Result<Membership, RepositoryError>;
+    fn remove_member(&self, room_id: &str, user_id: &str) -> Result<(), RepositoryError>;
+    fn get_room_members(&self, room_id: &str) -> Result<Vec<Membership>, RepositoryError>;
+    // 同じユーザーへの未処理の招待がある場合はそれを返す
+    fn create_invitation(
+        &self,
+        room_info: &RoomInfo,
+        invitee_id: &str,
+        invited_by: &PubUserInfo,
+    ) -> Result<Invitation, RepositoryError>;
+    fn get_room_invitations(&self, room_id: &str) -> Result<Vec<Invitation>, RepositoryError>;
+    fn get_user_invitations(&self, user_id: &str) -> Result<Vec<Invitation>, RepositoryError>;
+    // 招待されたユーザー本人の場合のみ取り出して削除する
+    fn take_invitation(
+        &self,
+        invitation_id: &str,
+        invitee_id: &str,
+    ) -> Result<Invitation, RepositoryError>;
+    fn delete_invitation(&self, room_id: &str, invitation_id: &str) -> Result<(), RepositoryError>;
+    fn create_invite_link(&self, invite_link: &InviteLink) -> Result<(), RepositoryError>;
+    fn get_invite_link(&self, token: &str) -> Result<InviteLink, RepositoryError>;
+    fn get_room_invite_links(&self, room_id: &str) -> Result<Vec<InviteLink>, RepositoryError>;
+    // 招待リンクのロールでメンバーに追加し、使用回数を1増やす。メンバーの確認から追加までを1つのロックで行う
+    // 既にメンバーの場合は使用回数を消費せずにそのメンバーシップを返す
+    // それ以外は期限内で回数の上限に達していない場合のみ追加する
+    fn redeem_invite_link(
+        &self,
+        token: &str,
+        user_info: &PubUserInfo,
+        now: DateTime<Utc>,
+    ) -> Result<Membership, RepositoryError>;
+    fn delete_invite_link(&self, room_id: &str, invite_id: &str) -> Result<(), RepositoryError>;
+    // 同じユーザーへの同じ種類の制裁は置き換える
+    fn add_sanction(&self, sanction: &Sanction) -> Result<(), RepositoryError>;
+    fn remove_sanction(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        kind: SanctionKind,
+    ) -> Result<(), RepositoryError>;
+    // 期限切れの制裁は含めない
+    fn get_active_sanction(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        kind: SanctionKind,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Sanction>, RepositoryError>;
+    fn get_room_sanctions(
+        &self,
+        room_id: &str,
+        kind: SanctionKind,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Sanction>, RepositoryError>;
+    // 期限切れの制裁を削除し、削除した件数を返す
+    fn remove_expired_sanctions(&self, now: DateTime<Utc>) -> Result<usize, RepositoryError>;
+    // 同じルームの未処理の譲渡は置き換える
+    fn create_transfer(&self, transfer: &OwnershipTransfer) -> Result<(), RepositoryError>;
+    fn get_transfer(&self, room_id: &str) -> Result<OwnershipTransfer, RepositoryError>;
+    fn get_user_transfers(&self, user_id: &str) -> Result<Vec<OwnershipTransfer>, RepositoryError>;
+    // 譲渡先のユーザー本人の場合のみ取り出して削除する
+    fn take_transfer(
+        &self,
+        room_id: &str,
+        to_id: &str,
+    ) -> Result<OwnershipTransfer, RepositoryError>;
+    fn delete_transfer(&self, room_id: &str) -> Result<(), RepositoryError>;
+    // ルームごとの番号を付けて追記し、追記したエントリーを返す
+    fn append_audit(&self, entry: AuditEntry) -> Result<AuditEntry, RepositoryError>;
+    // beforeより前のエントリーを新しい順に最大limit件返す
+    fn get_audit_log(
+        &self,
+        room_id: &str,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, RepositoryError>;
+    // ルームのメンバー・招待・招待リンク・制裁・譲渡をまとめて削除する。監査ログは残す
+    fn delete_room(&self, room_id: &str) -> Result<(), RepositoryError>;
+    // 削除されたユーザーのメンバーシップと、そのユーザーへの招待・譲渡を削除する
+    fn delete_user(&self, user_id: &str) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::entity::{
+    chat::Chat,
+    message_cursor::MessageCursor,
+    retention_purge::RetentionPurge,
+    submission::{Submission, SubmissionKey},
+};
+
+use super::error::RepositoryError;
+
+pub trait MessageRepository {
+    fn save_message(&self, room_id: &str, chat: &Chat) -> Result<(), RepositoryError>;
+    // 同じキーのメッセージがwindow内に受付済みの場合は保存せずにそれを返す
+    fn save_message_once(
+        &self,
+        key: &SubmissionKey,
+        chat: &Chat,
+        window: Duration,
+    ) -> Result<Option<Submission>, RepositoryError>;
+    // save_message_onceで保存したメッセージを取り消し、同じキーで再送できるようにする
+    // その後に同じキーで受け付けたメッセージの記録は消さない
+    fn discard_submission(
+        &self,
+        key: &SubmissionKey,
+        message_id: &str,
+    ) -> Result<(), RepositoryError>;
+    fn get_messages(&self, room_id: &str) -> Result<Vec<Chat>, RepositoryError>;
+    // [from, to]の範囲でafterより後のメッセージを(time, message_id)順に最大limit件返す
+    fn get_messages_page(
+        &self,
+        room_id: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after: Option<&MessageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError>;
+    // at以前に投稿された最後のメッセージの時刻
+    fn last_message_time(
+        &self,
+        room_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, RepositoryError>;
+    // 期限切れのメッセージを削除し、ルームIDごとに削除したメッセージIDを返す
+    fn remove_expired(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<HashMap<String, Vec<String>>, RepositoryError>;
+    // older_thanより前のメッセージと、新しい順にkeep_last件を超えたメッセージを削除して返す
+    fn remove_outside_retention(
+        &self,
+        room_id: &str,
+        older_than: Option<DateTime<Utc>>,
+        keep_last: Option<usize>,
+    ) -> Result<Vec<Chat>, RepositoryError>;
+    fn save_purge(&self, purge: &RetentionPurge) -> Result<(), RepositoryError>;
+    // 古い順に返す
+    fn get_purges(&self, room_id: &str) -> Result<Vec<RetentionPurge>, RepositoryError>;
+    // 重複判定の期間が過ぎた受付済みのメッセージを削除する
+    fn remove_expired_submissions(&self, now: DateTime<Utc>) -> Result<usize, RepositoryError>;
+    fn delete_message(&self, room_id: &str, message_id: &str) -> Result<Chat, RepositoryError>;
+    fn delete_room_messages(&self, room_id: &str) -> Result<(), RepositoryError>;
+}
@@ -1,3 +1,8 @@
 pub mod error;
+pub mod favorite_repository;
+pub mod incoming_hook_repository;
+pub mod membership_repository;
+pub mod message_repository;
+pub mod outgoing_hook_repository;
 pub mod room_repository;
 pub mod user_repository;
@@ -0,0 +1,27 @@
+use crate::domain::entity::{
+    hook_delivery::HookDelivery, hook_event::HookEventKind, outgoing_hook::OutgoingHook,
+};
+
+use super::error::RepositoryError;
+
+pub trait OutgoingHookRepository {
+    fn create_hook(
+        &self,
+        room_id: &str,
+        callback_url: &str,
+        secret: &str,
+        events: &[HookEventKind],
+        created_by_id: &str,
+    ) -> Result<OutgoingHook, RepositoryError>;
+    fn get_room_hooks(&self, room_id: &str) -> Result<Vec<OutgoingHook>, RepositoryError>;
+    fn get_subscribed_hooks(
+        &self,
+        room_id: &str,
+        kind: HookEventKind,
+    ) -> Result<Vec<OutgoingHook>, RepositoryError>;
+    fn delete_hook(&self, room_id: &str, hook_id: &str) -> Result<(), RepositoryError>;
+    fn delete_room_hooks(&self, room_id: &str) -> Result<(), RepositoryError>;
+    // 同じdelivery_idのログがあれば上書きする
+    fn save_delivery(&self, delivery: &HookDelivery) -> Result<(), RepositoryError>;
+    fn get_deliveries(&self, hook_id: &str) -> Result<Vec<HookDelivery>, RepositoryError>;
+}
@@ -1,15 +1,167 @@
-use crate::domain::entity::{pub_user_info::PubUserInfo, room::Room, room_info::RoomInfo};
+use std::{future::Future, pin::Pin};
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::domain::entity::{
+    chat::Chat,
+    create_room::CreateRoom,
+    post_policy::PostPolicy,
+    post_rejection::PostRejection,
+    pub_user_info::PubUserInfo,
+    room::{Admission, Resume},
+    room_event::RoomEvent,
+    room_info::RoomInfo,
+    update_room::UpdateRoom,
+};
 
 use super::error::RepositoryError;
 
+// 削除済みのルームは、削除済みのルームを扱う関数と呼び出し以外ではNotFoundとして扱う
 pub trait RoomRepository {
-    fn open_new_room(
-        &self,
-        room_name: &str,
-        user_info: &PubUserInfo,
-    ) -> Result<RoomInfo, RepositoryError>;
-    fn listen_room(&self, room_id: &str) -> Result<Room, RepositoryError>;
-    fn get_owner_rooms(&self, owner_id: &str) -> Result<Vec<RoomInfo>, RepositoryError>;
-    fn get_all_room(&self) -> Result<Vec<RoomInfo>, RepositoryError>;
-    fn delete_room(&self, room_id: &str) -> Result<(), RepositoryError>;
+    // 参加コードは重複しないように割り当てられる
+    fn open_new_room<'a>(
+        &'a self,
+        payload: &'a CreateRoom,
+        user_info: &'a PubUserInfo,
+        join_password_hash: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    fn get_room_info<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    fn get_room_by_join_code<'a>(
+        &'a self,
+        join_code: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    fn get_owner_rooms<'a>(
+        &'a self,
+        owner_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<RoomInfo>, RepositoryError>> + Send + 'a>>;
+
+    fn get_all_room<'a>(
+        &'a self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<RoomInfo>, RepositoryError>> + Send + 'a>>;
+
+    // 変更後のルーム情報を接続中のクライアントにもブロードキャストする
+    fn update_room<'a>(
+        &'a self,
+        room_id: &'a str,
+        payload: UpdateRoom,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    // オーナーを変更し、変更後のルーム情報をブロードキャストする
+    // オーナーのいないルームは引き継がれたものとして扱う
+    fn transfer_room<'a>(
+        &'a self,
+        room_id: &'a str,
+        new_owner: &'a PubUserInfo,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    // オーナーのアカウントが削除されたルームをアーカイブし、オーナーのいないルームとして記録する
+    fn orphan_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    // アーカイブ済みの場合はそのまま返す
+    fn archive_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    // アーカイブされていない場合はそのまま返す
+    fn unarchive_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    // 削除が近づいたことを通知したルームに削除予定の時刻を記録する
+    fn set_expires_time<'a>(
+        &'a self,
+        room_id: &'a str,
+        expires_time: DateTime<Utc>,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    // 削除済みにして接続中のクライアントを切断する。履歴はrestore_roomで復元できるよう残す
+    fn soft_delete_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    fn restore_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>>;
+
+    fn get_deleted_rooms<'a>(
+        &'a self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<RoomInfo>, RepositoryError>> + Send + 'a>>;
+
+    // 履歴も含めて完全に削除する
+    fn delete_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RepositoryError>> + Send + 'a>>;
+
+    // seqを割り当ててルームにブロードキャストする
+    fn publish<'a>(
+        &'a self,
+        room_id: &'a str,
+        event: RoomEvent,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, RepositoryError>> + Send + 'a>>;
+
+    // 接続をメンバーとして登録し、sinceより後のイベントとその後のイベントの購読を返す
+    // signalにはキックなどで切断される際のイベントや、観覧者から参加者になった際のイベントが送られる
+    fn join<'a>(
+        &'a self,
+        room_id: &'a str,
+        connection_id: &'a str,
+        user_info: &'a PubUserInfo,
+        since: Option<u64>,
+        signal: UnboundedSender<RoomEvent>,
+    ) -> Pin<Box<dyn Future<Output = Result<Resume, RepositoryError>> + Send + 'a>>;
+
+    // アーカイブ済みやアナウンス専用、スローモードの待ち時間中でなければチャットを流す
+    // 流さなかった場合はその理由を返す
+    // アナウンス専用とスローモードを適用するかは投稿者のpolicyで決まる
+    fn publish_chat<'a>(
+        &'a self,
+        room_id: &'a str,
+        chat: Chat,
+        policy: PostPolicy,
+        now: DateTime<Utc>,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<PostRejection>, RepositoryError>> + Send + 'a>>;
+
+    // パスワードでの参加を試せるかを判定して記録する。試せない場合は次に試せるまでの待ち時間を返す
+    fn acquire_join_attempt<'a>(
+        &'a self,
+        room_id: &'a str,
+        user_id: &'a str,
+        now: DateTime<Utc>,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<Duration>, RepositoryError>> + Send + 'a>>;
+
+    // 接続した場合に参加者と観覧者のどちらになるか。満員で接続できない場合はNone
+    fn admission<'a>(
+        &'a self,
+        room_id: &'a str,
+        user_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<Admission>, RepositoryError>> + Send + 'a>>;
+
+    fn leave<'a>(
+        &'a self,
+        room_id: &'a str,
+        connection_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RepositoryError>> + Send + 'a>>;
+
+    // ユーザーの全ての接続にeventを送って切断し、切断した接続の数を返す
+    fn disconnect_user<'a>(
+        &'a self,
+        room_id: &'a str,
+        user_id: &'a str,
+        event: RoomEvent,
+    ) -> Pin<Box<dyn Future<Output = Result<usize, RepositoryError>> + Send + 'a>>;
 }
@@ -0,0 +1,231 @@
+use chrono::Utc;
+
+use crate::domain::{
+    entity::{
+        audit_entry::{AuditAction, AuditEntry},
+        audit_page::AuditPage,
+        audit_query::AuditQuery,
+        pub_user_info::PubUserInfo,
+        room_role::RoomRole,
+    },
+    repository::{membership_repository::MembershipRepository, room_repository::RoomRepository},
+};
+
+use super::{
+    error::ServiceError,
+    membership_service::{can_access, role_of},
+};
+
+// 監査ログの1ページの件数
+const DEFAULT_AUDIT_PAGE_SIZE: usize = 50;
+const MAX_AUDIT_PAGE_SIZE: usize = 100;
+
+pub struct AuditServices<B, R>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+{
+    membership_repo: B,
+    room_repo: R,
+}
+
+impl<B, R> AuditServices<B, R>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+{
+    pub fn new(membership_repo: B, room_repo: R) -> Self {
+        Self {
+            membership_repo,
+            room_repo,
+        }
+    }
+
+    // オーナーとモデレーターのみ閲覧できる。新しい順に返す
+    pub async fn get_audit_log(
+        &self,
+        room_id: &str,
+        query: AuditQuery,
+        user_info: PubUserInfo,
+    ) -> Result<AuditPage, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        match role_of(&self.membership_repo, &room_info, &user_info.user_id)? {
+            Some(role) if role >= RoomRole::Moderator => {}
+            _ if can_access(&self.membership_repo, &room_info, &user_info.user_id)? => {
+                return Err(ServiceError::Forbidden)
+            }
+            _ => return Err(ServiceError::NotFound),
+        }
+
+        let limit = query.limit.unwrap_or(DEFAULT_AUDIT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_AUDIT_PAGE_SIZE {
+            return Err(ServiceError::Validation);
+        }
+        // カーソルは前のページの最後のエントリーのseq
+        let before = match &query.cursor {
+            Some(cursor) => Some(cursor.parse().map_err(|_| ServiceError::Validation)?),
+            None => None,
+        };
+
+        let mut entries = self
+            .membership_repo
+            .get_audit_log(room_id, before, limit + 1)?;
+        let next_cursor = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|entry| entry.seq.to_string())
+        } else {
+            None
+        };
+        Ok(AuditPage {
+            entries,
+            next_cursor,
+        })
+    }
+}
+
+// 管理操作を監査ログに追記する
+pub fn record_audit<B>(
+    membership_repo: &B,
+    room_id: &str,
+    action: AuditAction,
+    actor: &PubUserInfo,
+    target_id: Option<&str>,
+    detail: Option<String>,
+) -> Result<(), ServiceError>
+where
+    B: MembershipRepository,
+{
+    membership_repo.append_audit(AuditEntry {
+        seq: 0,
+        room_id: room_id.to_owned(),
+        action,
+        actor_id: actor.user_id.to_owned(),
+        actor_name: actor.user_name.to_owned(),
+        target_id: target_id.map(str::to_owned),
+        detail,
+        time: Utc::now(),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        domain::entity::visibility::Visibility,
+        infrastructure::repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            room_repository_impl::{set_up_test_room, test_user, RoomRepositoryImpl},
+        },
+    };
+
+    use super::*;
+
+    // ownerのルームにmoderatorとmemberが参加し、監査ログが3件ある
+    async fn set_up(
+        visibility: Visibility,
+    ) -> (
+        AuditServices<MembershipRepositoryImpl, RoomRepositoryImpl>,
+        String,
+    ) {
+        let members = [
+            ("moderator", RoomRole::Moderator),
+            ("member", RoomRole::Member),
+        ];
+        let (room_db, membership_db, room_info) = set_up_test_room(visibility, &members).await;
+        let room_id = room_info.room_id;
+        let membership_repo = MembershipRepositoryImpl::new(membership_db.clone());
+        for _ in 0..3 {
+            record_audit(
+                &membership_repo,
+                &room_id,
+                AuditAction::MemberKicked,
+                &test_user("owner"),
+                Some("member_id"),
+                None,
+            )
+            .unwrap();
+        }
+
+        let services = AuditServices::new(
+            MembershipRepositoryImpl::new(membership_db),
+            RoomRepositoryImpl::new(room_db),
+        );
+        (services, room_id)
+    }
+
+    #[tokio::test]
+    async fn test_get_audit_log() {
+        let (services, room_id) = set_up(Visibility::Public).await;
+        let query = AuditQuery {
+            limit: Some(2),
+            cursor: None,
+        };
+
+        // テスト対象
+        // オーナーとモデレーターは閲覧できる
+        let page = services
+            .get_audit_log(&room_id, query.clone(), test_user("owner"))
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.next_cursor.as_deref(), Some("2"));
+        let page = services
+            .get_audit_log(
+                &room_id,
+                AuditQuery {
+                    cursor: page.next_cursor,
+                    ..query.clone()
+                },
+                test_user("moderator"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.next_cursor, None);
+
+        // 範囲外のlimitや不正なカーソルは受け付けない
+        for query in [
+            AuditQuery {
+                limit: Some(0),
+                cursor: None,
+            },
+            AuditQuery {
+                limit: Some(MAX_AUDIT_PAGE_SIZE + 1),
+                cursor: None,
+            },
+            AuditQuery {
+                limit: None,
+                cursor: Some("abc".to_string()),
+            },
+        ] {
+            let result = services
+                .get_audit_log(&room_id, query, test_user("owner"))
+                .await;
+            assert!(matches!(result, Err(ServiceError::Validation)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_audit_log_requires_moderator() {
+        // テスト対象
+        // 公開ルームではモデレーター未満はForbidden
+        let (services, room_id) = set_up(Visibility::Public).await;
+        for name in ["member", "outsider"] {
+            let result = services
+                .get_audit_log(&room_id, AuditQuery::default(), test_user(name))
+                .await;
+            assert!(matches!(result, Err(ServiceError::Forbidden)));
+        }
+
+        // 非公開ルームではメンバーはForbidden、メンバー以外にはルームの存在を明かさない
+        let (services, room_id) = set_up(Visibility::Private).await;
+        let result = services
+            .get_audit_log(&room_id, AuditQuery::default(), test_user("member"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        let result = services
+            .get_audit_log(&room_id, AuditQuery::default(), test_user("outsider"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+    }
+}
@@ -39,7 +39,7 @@ where
     }
 
     pub async fn authorize(&self, auth_payload: AuthPayload) -> Result<AccessToken, ServiceError> {
-        if let Err(_) = auth_payload.validate() {
+        if auth_payload.validate().is_err() {
             return Err(ServiceError::Validation);
         }
 
@@ -1,61 +1,246 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
 use axum::extract::ws::{Message, WebSocket};
+use chrono::{Duration, Utc};
 use futures::{SinkExt, StreamExt};
-use tokio::sync::broadcast::Sender;
+use tokio::sync::mpsc;
 use tracing::warn;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::{
+    entity::{
+        chat::Chat,
+        chat_payload::ChatPayload,
+        hook_event::HookEvent,
+        post_policy::PostPolicy,
+        post_rejection::PostRejection,
+        pub_user_info::PubUserInfo,
+        room::{Admission, Resume},
+        room_event::RoomEvent,
+        room_info::RoomInfo,
+        room_role::RoomRole,
+        sanction::SanctionKind,
+        submission::{Submission, SubmissionKey},
+    },
+    repository::{
+        membership_repository::MembershipRepository, message_repository::MessageRepository,
+        room_repository::RoomRepository,
+    },
+};
+
+use super::{membership_service::role_of, util::event_notifier::EventNotifier};
 
-use crate::domain::entity::{chat::Chat, pub_user_info::PubUserInfo};
+// 同じclientMsgIdの再送を重複とみなす期間
+const CLIENT_MSG_ID_WINDOW_SECS: i64 = 600;
+// 送信待ちのack/nackの上限
+const REPLY_BUFFER: usize = 32;
 
-pub struct ChatServices {
+pub struct ChatServices<M, N, R, B>
+where
+    M: MessageRepository,
+    N: EventNotifier,
+    R: RoomRepository,
+    B: MembershipRepository,
+{
     socket: WebSocket,
-    room_sender: Sender<String>,
+    room_info: RoomInfo,
     user_info: PubUserInfo,
+    since: Option<u64>,
+    message_repo: M,
+    notifier: N,
+    room_repo: R,
+    membership_repo: B,
 }
 
-impl ChatServices {
-    pub fn new(socket: WebSocket, room_sender: Sender<String>, user_info: PubUserInfo) -> Self {
+impl<M, N, R, B> ChatServices<M, N, R, B>
+where
+    M: MessageRepository + Send + Sync + 'static,
+    N: EventNotifier + Send + Sync + 'static,
+    R: RoomRepository + Send + Sync + 'static,
+    B: MembershipRepository + Send + Sync + 'static,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        socket: WebSocket,
+        room_info: RoomInfo,
+        user_info: PubUserInfo,
+        since: Option<u64>,
+        message_repo: M,
+        notifier: N,
+        room_repo: R,
+        membership_repo: B,
+    ) -> Self {
         Self {
             socket,
-            room_sender,
+            room_info,
             user_info,
+            since,
+            message_repo,
+            notifier,
+            room_repo,
+            membership_repo,
         }
     }
 
     pub async fn ws_task(self) {
-        let (mut ws_sender, mut ws_receiver) = self.socket.split();
+        let Self {
+            socket,
+            room_info,
+            user_info,
+            since,
+            message_repo,
+            notifier,
+            room_repo,
+            membership_repo,
+        } = self;
+        let (mut ws_sender, mut ws_receiver) = socket.split();
+        let room_id = room_info.room_id.clone();
+        let connection_id = Uuid::new_v4().to_string();
+
+        // キック・BANされた場合や、観覧者から参加者になった場合に通知される
+        let (signal_sender, mut signal_receiver) = mpsc::unbounded_channel::<RoomEvent>();
+        let resume = room_repo
+            .join(&room_id, &connection_id, &user_info, since, signal_sender)
+            .await;
+        let (replay, mut room_receiver, admission) = match resume {
+            Ok(Resume::Replay(replay, room_receiver, admission)) => {
+                (replay, room_receiver, admission)
+            }
+            // 接続の間にルームが削除された
+            Err(e) => {
+                warn!("websocket join error: {:?}", e);
+                let _ = ws_sender.close().await;
+                return;
+            }
+            Ok(Resume::GapTooLarge { latest_seq }) => {
+                if let Ok(signal) = serde_json::to_string(&RoomEvent::GapTooLarge { latest_seq }) {
+                    let _ = ws_sender.send(Message::Text(signal.into())).await;
+                }
+                let _ = ws_sender.close().await;
+                return;
+            }
+            // 接続を受け付けた後に他のユーザーが参加して満員になった
+            Ok(Resume::Full) => {
+                if let Ok(signal) = serde_json::to_string(&RoomEvent::RoomFull) {
+                    let _ = ws_sender.send(Message::Text(signal.into())).await;
+                }
+                let _ = ws_sender.close().await;
+                return;
+            }
+        };
+        // 定員に空きができると送信タスクが参加者に切り替える
+        let spectator = Arc::new(AtomicBool::new(admission == Admission::Spectator));
+        let task_spectator = spectator.clone();
 
-        let room_sender = self.room_sender.clone();
+        // ack/nackは送信したクライアントにのみ返す
+        let (reply_sender, mut reply_receiver) = mpsc::channel::<String>(REPLY_BUFFER);
+        let room_repo = Arc::new(room_repo);
+        let task_room_repo = room_repo.clone();
         let mut receive_task = tokio::task::spawn(async move {
             while let Some(Ok(Message::Text(sended_text))) = ws_receiver.next().await {
-                if sended_text.len() == 0 {
+                if sended_text.is_empty() {
                     continue;
                 }
 
-                let chat_msg = Chat::from_str(
-                    &self.user_info.user_id,
-                    &self.user_info.user_name,
-                    &sended_text,
-                );
-
-                let Ok(serialized_chat_msg) = serde_json::to_string(&chat_msg) else {
-                    if let Err(e) = room_sender.send("failed to serialize chat message".to_string())
-                    {
-                        warn!("websocket receive task error: {:?}", e);
-                        break;
-                    }
-                    break;
+                let payload = ChatPayload::parse(&sended_text);
+                let client_msg_id = payload.client_msg_id.clone();
+                let result = if task_spectator.load(Ordering::Acquire) {
+                    Err(Rejection::Spectator)
+                } else {
+                    submit(
+                        &room_info.room_id,
+                        &user_info,
+                        &message_repo,
+                        &notifier,
+                        task_room_repo.as_ref(),
+                        &membership_repo,
+                        payload,
+                    )
+                    .await
                 };
+                if let Err(Rejection::Server(e)) = &result {
+                    warn!("websocket receive task error: {}", e);
+                }
 
-                if let Err(e) = room_sender.send(serialized_chat_msg) {
-                    warn!("websocket receive task error: {:?}", e);
+                // clientMsgIdがない場合は、アーカイブ済みやスローモード、アナウンス専用で受け付けなかった場合のみ応答する
+                let reply = match (client_msg_id, &result) {
+                    (Some(client_msg_id), Ok(submission)) => Some(RoomEvent::Ack {
+                        client_msg_id,
+                        message_id: submission.message_id.clone(),
+                        time: submission.time,
+                    }),
+                    (Some(client_msg_id), Err(rejection)) => Some(RoomEvent::Nack {
+                        client_msg_id,
+                        reason: rejection.reason().to_string(),
+                        retry_after_secs: rejection.retry_after_secs(),
+                    }),
+                    (
+                        None,
+                        Err(
+                            rejection @ (Rejection::Archived
+                            | Rejection::SlowMode { .. }
+                            | Rejection::AnnouncementOnly),
+                        ),
+                    ) => Some(RoomEvent::Error {
+                        reason: rejection.reason().to_string(),
+                        retry_after_secs: rejection.retry_after_secs(),
+                    }),
+                    (None, _) => None,
+                };
+                if let Some(Ok(reply)) = reply.map(|reply| serde_json::to_string(&reply)) {
+                    let _ = reply_sender.send(reply).await;
+                }
+                if let Err(Rejection::Server(_)) = result {
                     break;
                 }
             }
         });
 
-        let mut room_receiver = self.room_sender.subscribe();
         let mut send_task = tokio::task::spawn(async move {
-            while let Ok(chat_msg) = room_receiver.recv().await {
-                if let Err(e) = ws_sender.send(Message::Text(chat_msg)).await {
+            if spectator.load(Ordering::Acquire) {
+                if let Ok(signal) = serde_json::to_string(&RoomEvent::Spectating) {
+                    let _ = ws_sender.send(Message::Text(signal.into())).await;
+                }
+            }
+            // 切断中に流れたイベントを先に送る
+            for event in replay {
+                if let Err(e) = ws_sender.send(Message::Text(event)).await {
+                    warn!("websocket send task error: {:?}", e);
+                    return;
+                }
+            }
+            loop {
+                // ルームのイベントは共有しているバッファをそのまま送る
+                let frame = tokio::select! {
+                    event = room_receiver.recv() => match event {
+                        Ok(event) => event,
+                        Err(_) => break,
+                    },
+                    Some(reply) = reply_receiver.recv() => reply.into(),
+                    event = signal_receiver.recv() => match event {
+                        // 参加者になった場合は通知して投稿を受け付ける
+                        Some(RoomEvent::Participating) => {
+                            spectator.store(false, Ordering::Release);
+                            match serde_json::to_string(&RoomEvent::Participating) {
+                                Ok(signal) => signal.into(),
+                                Err(_) => continue,
+                            }
+                        }
+                        // 理由を送ってから切断する
+                        event => {
+                            if let Some(Ok(signal)) = event.map(|e| serde_json::to_string(&e)) {
+                                let _ = ws_sender.send(Message::Text(signal.into())).await;
+                            }
+                            let _ = ws_sender.close().await;
+                            break;
+                        }
+                    },
+                };
+                if let Err(e) = ws_sender.send(Message::Text(frame)).await {
                     warn!("websocket send task error: {:?}", e);
                     break;
                 }
@@ -66,5 +251,390 @@ impl ChatServices {
             _ = &mut send_task => receive_task.abort(),
             _ = &mut receive_task => send_task.abort(),
         };
+        let _ = room_repo.leave(&room_id, &connection_id).await;
+    }
+}
+
+// メッセージを保存してルームに流す
+// clientMsgIdが同じメッセージを再送された場合は保存済みのメッセージを返す
+// ミュートされている間やアーカイブされた後、スローモードの待ち時間中は接続したままでも受け付けない
+// アナウンス専用ルームではモデレーター未満のメンバーの投稿を受け付けない
+async fn submit<M, N, R, B>(
+    room_id: &str,
+    user_info: &PubUserInfo,
+    message_repo: &M,
+    notifier: &N,
+    room_repo: &R,
+    membership_repo: &B,
+    payload: ChatPayload,
+) -> Result<Submission, Rejection>
+where
+    M: MessageRepository,
+    N: EventNotifier,
+    R: RoomRepository,
+    B: MembershipRepository,
+{
+    if payload.text.is_empty() {
+        return Err(Rejection::EmptyMessage);
+    }
+    let mute = membership_repo
+        .get_active_sanction(room_id, &user_info.user_id, SanctionKind::Mute, Utc::now())
+        .map_err(|e| Rejection::Server(format!("{:?}", e)))?;
+    if mute.is_some() {
+        return Err(Rejection::Muted);
+    }
+    if payload.validate().is_err() {
+        return Err(Rejection::InvalidPayload);
+    }
+    // 接続後のオーナーの譲渡やロールの変更を反映するため、投稿ごとに最新のルーム情報でロールを判定する
+    let room_info = room_repo
+        .get_room_info(room_id)
+        .await
+        .map_err(|e| Rejection::Server(format!("{:?}", e)))?;
+    // モデレーター以上はアナウンス専用ルームでも投稿でき、スローモードの対象外
+    let role = role_of(membership_repo, &room_info, &user_info.user_id)
+        .map_err(|e| Rejection::Server(format!("{:?}", e)))?;
+    let policy = if role >= Some(RoomRole::Moderator) {
+        PostPolicy::Moderator
+    } else {
+        PostPolicy::Member
+    };
+
+    let window = Duration::seconds(CLIENT_MSG_ID_WINDOW_SECS);
+    let mut chat_msg = Chat::from_str(&user_info.user_id, &user_info.user_name, &payload.text);
+    // メッセージ個別のTTLがルームのデフォルトより優先される
+    if let Some(ttl_secs) = payload.ttl_secs.or(room_info.message_ttl_secs) {
+        chat_msg = chat_msg.expire_in(Duration::seconds(ttl_secs as i64));
+    }
+
+    let key = payload.client_msg_id.map(|client_msg_id| SubmissionKey {
+        room_id: room_id.to_owned(),
+        user_id: user_info.user_id.clone(),
+        client_msg_id,
+    });
+    match &key {
+        Some(key) => {
+            let duplicated = message_repo
+                .save_message_once(key, &chat_msg, window)
+                .map_err(|e| Rejection::Server(format!("{:?}", e)))?;
+            if let Some(submission) = duplicated {
+                return Ok(submission);
+            }
+        }
+        None => message_repo
+            .save_message(room_id, &chat_msg)
+            .map_err(|e| Rejection::Server(format!("{:?}", e)))?,
+    }
+
+    // アーカイブやアナウンス専用、スローモードはルームのタスクで流す直前に判定する
+    // 流せなかったメッセージは保存を取り消す
+    let rejection = match room_repo
+        .publish_chat(room_id, chat_msg.clone(), policy, Utc::now())
+        .await
+    {
+        Ok(rejection) => rejection.map(Rejection::from),
+        Err(e) => Some(Rejection::Server(format!("{:?}", e))),
+    };
+    if let Some(rejection) = rejection {
+        let discarded = match &key {
+            Some(key) => message_repo.discard_submission(key, &chat_msg.message_id),
+            None => message_repo
+                .delete_message(room_id, &chat_msg.message_id)
+                .map(|_| ()),
+        };
+        if let Err(e) = discarded {
+            warn!("failed to discard rejected message: {:?}", e);
+        }
+        return Err(rejection);
+    }
+
+    let submission = Submission {
+        message_id: chat_msg.message_id.clone(),
+        time: chat_msg.time,
+        dedupe_until: chat_msg.time + window,
+    };
+    notifier.notify(room_id, HookEvent::MessagePosted(chat_msg));
+    Ok(submission)
+}
+
+// メッセージを受け付けなかった理由
+enum Rejection {
+    EmptyMessage,
+    InvalidPayload,
+    Muted,
+    Archived,
+    Spectator,
+    SlowMode { retry_after_secs: u64 },
+    AnnouncementOnly,
+    Server(String),
+}
+
+impl From<PostRejection> for Rejection {
+    fn from(rejection: PostRejection) -> Self {
+        match rejection {
+            PostRejection::Archived => Rejection::Archived,
+            PostRejection::AnnouncementOnly => Rejection::AnnouncementOnly,
+            // 端数は切り上げて、待ってから送れば必ず受け付けられる秒数を返す
+            PostRejection::SlowMode(wait) => Rejection::SlowMode {
+                retry_after_secs: (wait.num_milliseconds() as u64).div_ceil(1000),
+            },
+        }
+    }
+}
+
+impl Rejection {
+    fn reason(&self) -> &'static str {
+        match self {
+            Rejection::EmptyMessage => "emptyMessage",
+            Rejection::InvalidPayload => "invalidPayload",
+            Rejection::Muted => "muted",
+            Rejection::Archived => "archived",
+            Rejection::Spectator => "spectator",
+            Rejection::SlowMode { .. } => "slowMode",
+            Rejection::AnnouncementOnly => "announcementOnly",
+            Rejection::Server(_) => "serverError",
+        }
+    }
+
+    fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            Rejection::SlowMode { retry_after_secs } => Some(*retry_after_secs),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        domain::entity::{update_room::UpdateRoom, visibility::Visibility},
+        infrastructure::repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::{set_up_test_room, test_user, RoomRepositoryImpl},
+        },
+        MessageDb,
+    };
+
+    use super::*;
+
+    // 送信Webhookに通知された回数を数える
+    #[derive(Default)]
+    struct CountingNotifier(AtomicUsize);
+
+    impl EventNotifier for CountingNotifier {
+        fn notify(&self, _room_id: &str, _event: HookEvent) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    struct TestRoom {
+        room_info: RoomInfo,
+        room_repo: RoomRepositoryImpl,
+        message_repo: MessageRepositoryImpl,
+        membership_repo: MembershipRepositoryImpl,
+        notifier: CountingNotifier,
+    }
+
+    impl TestRoom {
+        async fn submit(
+            &self,
+            user: &str,
+            client_msg_id: Option<&str>,
+        ) -> Result<Submission, Rejection> {
+            let payload = ChatPayload {
+                text: "hello".to_string(),
+                ttl_secs: None,
+                client_msg_id: client_msg_id.map(str::to_string),
+            };
+            submit(
+                &self.room_info.room_id,
+                &test_user(user),
+                &self.message_repo,
+                &self.notifier,
+                &self.room_repo,
+                &self.membership_repo,
+                payload,
+            )
+            .await
+        }
+
+        fn message_count(&self) -> usize {
+            self.message_repo
+                .get_messages(&self.room_info.room_id)
+                .unwrap()
+                .len()
+        }
+    }
+
+    fn update(slow_mode_secs: Option<u32>, announcement_only: Option<bool>) -> UpdateRoom {
+        UpdateRoom {
+            slow_mode_secs,
+            announcement_only,
+            ..Default::default()
+        }
+    }
+
+    // ownerのルームにmoderatorとmemberが参加している
+    async fn set_up() -> TestRoom {
+        let members = [
+            ("moderator", RoomRole::Moderator),
+            ("member", RoomRole::Member),
+        ];
+        let (room_db, membership_db, room_info) =
+            set_up_test_room(Visibility::Public, &members).await;
+        TestRoom {
+            room_info,
+            room_repo: RoomRepositoryImpl::new(room_db),
+            message_repo: MessageRepositoryImpl::new(MessageDb::new()),
+            membership_repo: MembershipRepositoryImpl::new(membership_db),
+            notifier: CountingNotifier::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_archived() {
+        let room = set_up().await;
+        let room_id = room.room_info.room_id.clone();
+        room.room_repo.archive_room(&room_id).await.unwrap();
+
+        // テスト対象
+        let result = room.submit("moderator", Some("c1")).await;
+        assert!(matches!(result, Err(Rejection::Archived)));
+        // 流せなかったメッセージは残らず、通知もされない
+        assert_eq!(room.message_count(), 0);
+        assert_eq!(room.notifier.0.load(Ordering::Relaxed), 0);
+
+        // 取り消したclientMsgIdは重複として扱われない
+        room.room_repo.unarchive_room(&room_id).await.unwrap();
+        room.submit("moderator", Some("c1")).await.ok().unwrap();
+        assert_eq!(room.message_count(), 1);
+        assert_eq!(room.notifier.0.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_submit_slow_mode() {
+        let room = set_up().await;
+        room.room_repo
+            .update_room(&room.room_info.room_id, update(Some(10), None))
+            .await
+            .unwrap();
+
+        // テスト対象
+        room.submit("member", None).await.ok().unwrap();
+        let result = room.submit("member", None).await;
+        assert!(matches!(
+            result,
+            Err(Rejection::SlowMode {
+                retry_after_secs: 10
+            })
+        ));
+        // モデレーター以上は対象外
+        room.submit("moderator", None).await.ok().unwrap();
+        room.submit("moderator", None).await.ok().unwrap();
+        assert_eq!(room.message_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_submit_announcement_only() {
+        let room = set_up().await;
+        room.room_repo
+            .update_room(&room.room_info.room_id, update(None, Some(true)))
+            .await
+            .unwrap();
+
+        // テスト対象
+        let result = room.submit("member", Some("c1")).await;
+        assert!(matches!(result, Err(Rejection::AnnouncementOnly)));
+        // 拒否したメッセージは残らず、通知もされない
+        assert_eq!(room.message_count(), 0);
+        assert_eq!(room.notifier.0.load(Ordering::Relaxed), 0);
+
+        // モデレーター以上は投稿できる
+        room.submit("moderator", None).await.ok().unwrap();
+        room.submit("owner", None).await.ok().unwrap();
+        assert_eq!(room.message_count(), 2);
+        assert_eq!(room.notifier.0.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_submit_after_transfer() {
+        let room = set_up().await;
+        let room_id = room.room_info.room_id.clone();
+        room.room_repo
+            .update_room(&room_id, update(None, Some(true)))
+            .await
+            .unwrap();
+        room.submit("owner", None).await.ok().unwrap();
+        // 接続したままのownerからmoderatorにオーナーを譲渡し、ownerはメンバーになった
+        room.room_repo
+            .transfer_room(&room_id, &test_user("moderator"))
+            .await
+            .unwrap();
+        room.membership_repo
+            .set_role(&room_id, "moderator_id", RoomRole::Owner)
+            .unwrap();
+        room.membership_repo
+            .add_member(&room_id, &test_user("owner"), RoomRole::Member)
+            .unwrap();
+
+        // テスト対象
+        // 接続時のルーム情報ではなく、最新のロールで判定する
+        let result = room.submit("owner", None).await;
+        assert!(matches!(result, Err(Rejection::AnnouncementOnly)));
+        room.submit("moderator", None).await.ok().unwrap();
+        assert_eq!(room.message_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_submit_resend_in_slow_mode() {
+        let room = set_up().await;
+        room.room_repo
+            .update_room(&room.room_info.room_id, update(Some(10), None))
+            .await
+            .unwrap();
+        let first = room.submit("member", Some("c1")).await.ok().unwrap();
+
+        // テスト対象
+        // 待ち時間中の再送もスローモードで拒否せず、最初の応答を返す
+        let resent = room.submit("member", Some("c1")).await.ok().unwrap();
+        assert_eq!(resent.message_id, first.message_id);
+        assert_eq!(resent.time, first.time);
+        assert_eq!(room.message_count(), 1);
+        assert_eq!(room.notifier.0.load(Ordering::Relaxed), 1);
+
+        let result = room.submit("member", Some("c2")).await;
+        assert!(matches!(result, Err(Rejection::SlowMode { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_submit_failed_save_in_slow_mode() {
+        let message_db = MessageDb::new();
+        let room = TestRoom {
+            message_repo: MessageRepositoryImpl::new(message_db.clone()),
+            ..set_up().await
+        };
+        room.room_repo
+            .update_room(&room.room_info.room_id, update(Some(10), None))
+            .await
+            .unwrap();
+        // 保存に失敗させるため、メッセージのロックを壊す
+        let pool = message_db.pool.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = pool.write().unwrap();
+            panic!("poison the message lock");
+        })
+        .join();
+
+        // テスト対象
+        let result = room.submit("member", Some("c1")).await;
+        assert!(matches!(result, Err(Rejection::Server(_))));
+
+        // 保存できなかった投稿はスローモードの待ち時間を消費しない
+        message_db.pool.clear_poison();
+        room.submit("member", Some("c1")).await.ok().unwrap();
+        assert_eq!(room.message_count(), 1);
     }
 }
@@ -1,5 +1,6 @@
-use axum::response::IntoResponse;
+use axum::{response::IntoResponse, Json};
 use http::StatusCode;
+use serde_json::json;
 
 use crate::domain::repository::error::RepositoryError;
 
@@ -15,6 +16,10 @@ pub enum ServiceError {
     TokenVerify,
     MissingCredentials,
     InvalidToken,
+    RateLimited,
+    Forbidden,
+    Banned,
+    RoomFull,
 }
 
 impl From<sqlx::Error> for ServiceError {
@@ -38,6 +43,17 @@ impl From<RepositoryError> for ServiceError {
 impl IntoResponse for ServiceError {
     fn into_response(self) -> axum::response::Response {
         match self {
+            ServiceError::NotFound => StatusCode::NOT_FOUND.into_response(),
+            ServiceError::Validation => StatusCode::BAD_REQUEST.into_response(),
+            ServiceError::RateLimited => StatusCode::TOO_MANY_REQUESTS.into_response(),
+            ServiceError::Forbidden | ServiceError::Banned => StatusCode::FORBIDDEN.into_response(),
+            // どのエンドポイントでもチャット参加の拒否と同じ本文を返す
+            ServiceError::RoomFull => {
+                let body = Json(json!({
+                    "error": "Room is full",
+                }));
+                (StatusCode::CONFLICT, body).into_response()
+            }
             _ => (StatusCode::INTERNAL_SERVER_ERROR).into_response(),
         }
     }
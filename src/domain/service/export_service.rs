@@ -0,0 +1,123 @@
+use futures::{stream, Stream};
+
+use crate::domain::{
+    entity::{
+        export_query::ExportQuery, message_cursor::MessageCursor, pub_user_info::PubUserInfo,
+        room_info::RoomInfo,
+    },
+    repository::{message_repository::MessageRepository, room_repository::RoomRepository},
+};
+
+use super::{error::ServiceError, util::transcript_encoder::TranscriptEncoder};
+
+// 一度にメモリに載せるメッセージの件数
+const EXPORT_CHUNK_SIZE: usize = 500;
+
+enum ExportState {
+    Header,
+    Rows {
+        after: Option<MessageCursor>,
+        written: usize,
+    },
+    Footer,
+    Done,
+}
+
+pub struct ExportServices<M, R, E>
+where
+    M: MessageRepository,
+    R: RoomRepository,
+    E: TranscriptEncoder,
+{
+    message_repo: M,
+    room_repo: R,
+    encoder: E,
+}
+
+impl<M, R, E> ExportServices<M, R, E>
+where
+    M: MessageRepository + Send + 'static,
+    R: RoomRepository + Send + 'static,
+    E: TranscriptEncoder + Send + 'static,
+{
+    pub fn new(message_repo: M, room_repo: R, encoder: E) -> Self {
+        Self {
+            message_repo,
+            room_repo,
+            encoder,
+        }
+    }
+
+    // ルーム全体をバッファせず、チャンクごとにエンコードして返すストリーム
+    pub async fn export_room(
+        self,
+        room_id: &str,
+        user_info: PubUserInfo,
+        query: ExportQuery,
+    ) -> Result<impl Stream<Item = Result<String, ServiceError>> + Send + 'static, ServiceError>
+    {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+
+        let stream = stream::unfold(
+            (self, room_info, ExportState::Header),
+            move |(services, room_info, state)| async move {
+                let (item, next) = services.next_chunk(&room_info, &query, state)?;
+                Some((item, (services, room_info, next)))
+            },
+        );
+        Ok(stream)
+    }
+
+    fn next_chunk(
+        &self,
+        room_info: &RoomInfo,
+        query: &ExportQuery,
+        state: ExportState,
+    ) -> Option<(Result<String, ServiceError>, ExportState)> {
+        match state {
+            ExportState::Header => {
+                let header = self.encoder.header(room_info);
+                let next = ExportState::Rows {
+                    after: None,
+                    written: 0,
+                };
+                Some((Ok(header), next))
+            }
+            ExportState::Rows { after, written } => {
+                let page = match self.message_repo.get_messages_page(
+                    &room_info.room_id,
+                    query.from,
+                    query.to,
+                    after.as_ref(),
+                    EXPORT_CHUNK_SIZE,
+                ) {
+                    Ok(page) => page,
+                    Err(e) => return Some((Err(e.into()), ExportState::Done)),
+                };
+                let Some(last) = page.last() else {
+                    return Some((Ok(self.encoder.footer()), ExportState::Done));
+                };
+
+                let next = if page.len() < EXPORT_CHUNK_SIZE {
+                    ExportState::Footer
+                } else {
+                    ExportState::Rows {
+                        after: Some(MessageCursor::from_chat(last)),
+                        written: written + page.len(),
+                    }
+                };
+                let chunk = page
+                    .iter()
+                    .enumerate()
+                    .map(|(i, chat)| self.encoder.row(chat, written + i))
+                    .collect();
+                Some((Ok(chunk), next))
+            }
+            ExportState::Footer => Some((Ok(self.encoder.footer()), ExportState::Done)),
+            ExportState::Done => None,
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use chrono::Utc;
+
+use crate::domain::{
+    entity::{
+        favorite::Favorite,
+        favorite_room::FavoriteRoom,
+        room_info::RoomInfo,
+        room_list_item::RoomListItem,
+        update_favorite::{UpdateFavorite, MAX_FAVORITES},
+    },
+    repository::{
+        error::RepositoryError, favorite_repository::FavoriteRepository,
+        membership_repository::MembershipRepository, room_repository::RoomRepository,
+    },
+};
+
+use super::{error::ServiceError, membership_service::can_access};
+
+pub struct FavoriteServices<F, R, B>
+where
+    F: FavoriteRepository,
+    R: RoomRepository,
+    B: MembershipRepository,
+{
+    favorite_repo: F,
+    room_repo: R,
+    membership_repo: B,
+}
+
+impl<F, R, B> FavoriteServices<F, R, B>
+where
+    F: FavoriteRepository,
+    R: RoomRepository,
+    B: MembershipRepository,
+{
+    pub fn new(favorite_repo: F, room_repo: R, membership_repo: B) -> Self {
+        Self {
+            favorite_repo,
+            room_repo,
+            membership_repo,
+        }
+    }
+
+    // アクセスできないルームは存在しないものとして扱う
+    // positionは一覧で返す位置で、一覧に含まれないお気に入りは数えない
+    pub async fn put_favorite(
+        &self,
+        user_id: &str,
+        room_id: &str,
+        payload: UpdateFavorite,
+    ) -> Result<Favorite, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if !can_access(&self.membership_repo, &room_info, user_id)? {
+            return Err(ServiceError::NotFound);
+        }
+        let favorites = self.favorite_repo.get_favorites(user_id)?;
+        let current = favorites
+            .iter()
+            .position(|favorite| favorite.room_id == room_id);
+        if current.is_none() && favorites.len() >= MAX_FAVORITES {
+            return Err(ServiceError::Validation);
+        }
+        let position = match payload.position {
+            Some(position) => Some(
+                self.stored_index(user_id, room_id, current, position)
+                    .await?,
+            ),
+            None => None,
+        };
+        let favorite = self.favorite_repo.put_favorite(
+            user_id,
+            room_id,
+            position,
+            payload.muted,
+            payload.read,
+            Utc::now(),
+        )?;
+        Ok(favorite)
+    }
+
+    // 削除されたルームやアクセスできなくなったルームは返さないが、お気に入りには残す
+    // positionは返すルームの中で0から連続する
+    pub async fn get_favorite_rooms(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<FavoriteRoom>, ServiceError> {
+        let favorite_rooms = self
+            .visible_favorites(user_id)
+            .await?
+            .into_iter()
+            .enumerate()
+            .map(|(position, (_, favorite, room_info))| FavoriteRoom {
+                position,
+                muted: favorite.muted,
+                unread: is_unread(&favorite, &room_info),
+                added_time: favorite.added_time,
+                room: room_info,
+            })
+            .collect();
+        Ok(favorite_rooms)
+    }
+
+    pub fn delete_favorite(&self, user_id: &str, room_id: &str) -> Result<(), ServiceError> {
+        self.favorite_repo.delete_favorite(user_id, room_id)?;
+        Ok(())
+    }
+
+    // ルーム一覧に呼び出したユーザーのお気に入りの状態を付ける
+    pub fn mark_rooms(
+        &self,
+        user_id: &str,
+        rooms: Vec<RoomInfo>,
+    ) -> Result<Vec<RoomListItem>, ServiceError> {
+        let favorites = self.favorite_repo.get_favorites(user_id)?;
+        let items = rooms
+            .into_iter()
+            .map(|room_info| {
+                let favorite = favorites
+                    .iter()
+                    .find(|favorite| favorite.room_id == room_info.room_id);
+                RoomListItem {
+                    favorite: favorite.is_some(),
+                    muted: favorite.is_some_and(|favorite| favorite.muted),
+                    unread: favorite.is_some_and(|favorite| is_unread(favorite, &room_info)),
+                    room_info,
+                }
+            })
+            .collect();
+        Ok(items)
+    }
+
+    pub fn delete_user(&self, user_id: &str) -> Result<(), ServiceError> {
+        self.favorite_repo.delete_user(user_id)?;
+        Ok(())
+    }
+
+    // 一覧で返すお気に入りと、その保存されている位置
+    async fn visible_favorites(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<(usize, Favorite, RoomInfo)>, ServiceError> {
+        let mut visible = Vec::new();
+        for (index, favorite) in self
+            .favorite_repo
+            .get_favorites(user_id)?
+            .into_iter()
+            .enumerate()
+        {
+            let room_info = match self.room_repo.get_room_info(&favorite.room_id).await {
+                Ok(room_info) => room_info,
+                Err(RepositoryError::NotFound) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            if !can_access(&self.membership_repo, &room_info, user_id)? {
+                continue;
+            }
+            visible.push((index, favorite, room_info));
+        }
+        Ok(visible)
+    }
+
+    // 一覧での位置を、移動するルームを取り除いた後の保存されている位置に変換する
+    // 一覧の長さを超える場合は末尾に置く
+    async fn stored_index(
+        &self,
+        user_id: &str,
+        room_id: &str,
+        current: Option<usize>,
+        position: usize,
+    ) -> Result<usize, ServiceError> {
+        let index = self
+            .visible_favorites(user_id)
+            .await?
+            .into_iter()
+            .filter(|(_, favorite, _)| favorite.room_id != room_id)
+            .nth(position)
+            .map_or(usize::MAX, |(index, _, _)| {
+                index - usize::from(current.is_some_and(|current| current < index))
+            });
+        Ok(index)
+    }
+}
+
+// 既読にした後に投稿があった場合にtrue。ミュートしたルームは未読にしない
+fn is_unread(favorite: &Favorite, room_info: &RoomInfo) -> bool {
+    !favorite.muted && room_info.last_activity_time > favorite.last_read_time
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        domain::entity::{chat::Chat, create_room::CreateRoom, post_policy::PostPolicy},
+        infrastructure::repository::{
+            favorite_repository_impl::FavoriteRepositoryImpl,
+            membership_repository_impl::MembershipRepositoryImpl,
+            room_repository_impl::{test_user, RoomRepositoryImpl},
+        },
+        FavoriteDb, MembershipDb, RoomDb,
+    };
+
+    use super::*;
+
+    type Services =
+        FavoriteServices<FavoriteRepositoryImpl, RoomRepositoryImpl, MembershipRepositoryImpl>;
+
+    // ownerが作成した公開ルームa, b, cを、その順でお気に入りに登録する
+    async fn set_up() -> (Services, Vec<String>) {
+        let services = FavoriteServices::new(
+            FavoriteRepositoryImpl::new(FavoriteDb::new()),
+            RoomRepositoryImpl::new(RoomDb::new()),
+            MembershipRepositoryImpl::new(MembershipDb::new()),
+        );
+        let owner = test_user("owner");
+        let mut room_ids = Vec::new();
+        for room_name in ["a", "b", "c"] {
+            let payload = CreateRoom {
+                room_name: room_name.to_string(),
+                ..Default::default()
+            };
+            let room_info = services
+                .room_repo
+                .open_new_room(&payload, &owner, None)
+                .await
+                .unwrap();
+            services
+                .put_favorite("user_id", &room_info.room_id, UpdateFavorite::default())
+                .await
+                .unwrap();
+            room_ids.push(room_info.room_id);
+        }
+        (services, room_ids)
+    }
+
+    async fn listed(services: &Services) -> Vec<(usize, String)> {
+        services
+            .get_favorite_rooms("user_id")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|favorite_room| (favorite_room.position, favorite_room.room.room_name))
+            .collect()
+    }
+
+    fn move_to(position: usize) -> UpdateFavorite {
+        UpdateFavorite {
+            position: Some(position),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_favorite_rooms() {
+        let (services, room_ids) = set_up().await;
+        services
+            .room_repo
+            .soft_delete_room(&room_ids[1])
+            .await
+            .unwrap();
+
+        // テスト対象
+        // 削除されたルームを除いて0から連続した位置を返す
+        let favorites = listed(&services).await;
+        assert_eq!(favorites, [(0, "a".to_string()), (1, "c".to_string())]);
+
+        // 復元するとお気に入りに残っていた位置に戻る
+        services.room_repo.restore_room(&room_ids[1]).await.unwrap();
+        let names: Vec<String> = listed(&services)
+            .await
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_put_favorite_position() {
+        let (services, room_ids) = set_up().await;
+        services
+            .room_repo
+            .soft_delete_room(&room_ids[1])
+            .await
+            .unwrap();
+
+        // テスト対象
+        // 位置は一覧で返す位置として扱う
+        services
+            .put_favorite("user_id", &room_ids[2], move_to(0))
+            .await
+            .unwrap();
+        assert_eq!(
+            listed(&services).await,
+            [(0, "c".to_string()), (1, "a".to_string())]
+        );
+        services
+            .put_favorite("user_id", &room_ids[2], move_to(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            listed(&services).await,
+            [(0, "a".to_string()), (1, "c".to_string())]
+        );
+
+        // 閲覧できないルームは登録できない
+        let result = services
+            .put_favorite("user_id", &room_ids[1], UpdateFavorite::default())
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_unread() {
+        let (services, room_ids) = set_up().await;
+        services
+            .put_favorite(
+                "user_id",
+                &room_ids[1],
+                UpdateFavorite {
+                    muted: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        for room_id in &room_ids[..2] {
+            let chat = Chat::from_str("owner_id", "owner", "text");
+            services
+                .room_repo
+                .publish_chat(room_id, chat, PostPolicy::Moderator, Utc::now())
+                .await
+                .unwrap();
+        }
+        let unread = |favorite_rooms: Vec<FavoriteRoom>| -> Vec<bool> {
+            favorite_rooms
+                .into_iter()
+                .map(|favorite_room| favorite_room.unread)
+                .collect()
+        };
+
+        // テスト対象
+        // 投稿があったルームは未読になるが、ミュートしたルームは未読にならない
+        let favorite_rooms = services.get_favorite_rooms("user_id").await.unwrap();
+        assert_eq!(unread(favorite_rooms), [true, false, false]);
+        let rooms = services.room_repo.get_all_room().await.unwrap();
+        let items = services.mark_rooms("user_id", rooms).unwrap();
+        assert_eq!(items.iter().filter(|item| item.unread).count(), 1);
+
+        // 既読にすると未読ではなくなる
+        services
+            .put_favorite(
+                "user_id",
+                &room_ids[0],
+                UpdateFavorite {
+                    read: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let favorite_rooms = services.get_favorite_rooms("user_id").await.unwrap();
+        assert_eq!(unread(favorite_rooms), [false, false, false]);
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::{Duration, Utc};
+
+use crate::domain::{
+    entity::{
+        chat::Chat, create_hook::CreateHook, hook_event::HookEvent, hook_payload::HookPayload,
+        incoming_hook::IncomingHook, post_policy::PostPolicy, post_rejection::PostRejection,
+        pub_user_info::PubUserInfo, room_info::RoomInfo,
+    },
+    repository::{
+        incoming_hook_repository::IncomingHookRepository, message_repository::MessageRepository,
+        room_repository::RoomRepository,
+    },
+};
+
+use super::{
+    error::ServiceError,
+    util::{event_notifier::EventNotifier, secret_gen::SecretGen},
+};
+
+pub struct IncomingHookServices<H, R, M, G, N>
+where
+    H: IncomingHookRepository,
+    R: RoomRepository,
+    M: MessageRepository,
+    G: SecretGen,
+    N: EventNotifier,
+{
+    hook_repo: H,
+    room_repo: R,
+    message_repo: M,
+    secret_gen: G,
+    notifier: N,
+}
+
+impl<H, R, M, G, N> IncomingHookServices<H, R, M, G, N>
+where
+    H: IncomingHookRepository,
+    R: RoomRepository,
+    M: MessageRepository,
+    G: SecretGen,
+    N: EventNotifier,
+{
+    pub fn new(hook_repo: H, room_repo: R, message_repo: M, secret_gen: G, notifier: N) -> Self {
+        Self {
+            hook_repo,
+            room_repo,
+            message_repo,
+            secret_gen,
+            notifier,
+        }
+    }
+
+    pub async fn create_hook(
+        &self,
+        room_id: &str,
+        payload: CreateHook,
+        user_info: PubUserInfo,
+    ) -> Result<IncomingHook, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+
+        let token = self.secret_gen.gen_secret();
+        let hook =
+            self.hook_repo
+                .create_hook(room_id, &payload.hook_name, &token, &user_info.user_id)?;
+        Ok(hook)
+    }
+
+    pub async fn get_room_hooks(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<IncomingHook>, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let hooks = self.hook_repo.get_room_hooks(room_id)?;
+        Ok(hooks)
+    }
+
+    pub async fn revoke_hook(
+        &self,
+        room_id: &str,
+        hook_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        self.hook_repo.delete_hook(room_id, hook_id)?;
+        Ok(())
+    }
+
+    // フックの投稿をBotのメッセージとしてルームに流す
+    // アーカイブやアナウンス専用、スローモードはルームのタスクで流す直前に判定し、流せなかったメッセージは保存を取り消す
+    pub async fn post_message(
+        &self,
+        token: &str,
+        payload: HookPayload,
+    ) -> Result<Chat, ServiceError> {
+        let hook = self
+            .hook_repo
+            .acquire_hook(token, Utc::now())?
+            .ok_or(ServiceError::RateLimited)?;
+        let room_info = self.room_repo.get_room_info(&hook.room_id).await?;
+
+        let mut chat_msg = Chat::from_bot(&hook.hook_id, &payload.display_name, &payload.text);
+        if let Some(ttl_secs) = room_info.message_ttl_secs {
+            chat_msg = chat_msg.expire_in(Duration::seconds(ttl_secs as i64));
+        }
+        self.message_repo
+            .save_message(&room_info.room_id, &chat_msg)?;
+
+        let rejection = match self
+            .room_repo
+            .publish_chat(
+                &room_info.room_id,
+                chat_msg.clone(),
+                PostPolicy::Bot,
+                Utc::now(),
+            )
+            .await
+        {
+            Ok(rejection) => rejection.map(|rejection| match rejection {
+                PostRejection::Archived | PostRejection::AnnouncementOnly => {
+                    ServiceError::Forbidden
+                }
+                PostRejection::SlowMode(_) => ServiceError::RateLimited,
+            }),
+            Err(e) => Some(e.into()),
+        };
+        if let Some(rejection) = rejection {
+            self.message_repo
+                .delete_message(&room_info.room_id, &chat_msg.message_id)?;
+            return Err(rejection);
+        }
+        self.notifier.notify(
+            &room_info.room_id,
+            HookEvent::MessagePosted(chat_msg.clone()),
+        );
+        Ok(chat_msg)
+    }
+
+    // オーナー以外にはルームの存在を明かさない
+    async fn get_owner_room(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
+}
@@ -0,0 +1,288 @@
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::domain::{
+    entity::{
+        create_invite_link::CreateInviteLink, invite_link::InviteLink, membership::Membership,
+        pub_user_info::PubUserInfo, room_info::RoomInfo, room_role::RoomRole,
+    },
+    repository::{membership_repository::MembershipRepository, room_repository::RoomRepository},
+};
+
+use super::{
+    error::ServiceError,
+    membership_service::{ensure_not_banned, notify_member_joined},
+    util::{event_notifier::EventNotifier, secret_gen::SecretGen},
+};
+
+pub struct InviteLinkServices<B, R, S>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+    S: SecretGen,
+{
+    membership_repo: B,
+    room_repo: R,
+    secret_gen: S,
+}
+
+impl<B, R, S> InviteLinkServices<B, R, S>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+    S: SecretGen,
+{
+    pub fn new(membership_repo: B, room_repo: R, secret_gen: S) -> Self {
+        Self {
+            membership_repo,
+            room_repo,
+            secret_gen,
+        }
+    }
+
+    pub async fn create_invite_link(
+        &self,
+        room_id: &str,
+        payload: CreateInviteLink,
+        user_info: PubUserInfo,
+    ) -> Result<InviteLink, ServiceError> {
+        let room_info = self.get_owner_room(room_id, &user_info).await?;
+        let role = payload.role.unwrap_or(RoomRole::Member);
+        if role == RoomRole::Owner {
+            return Err(ServiceError::Validation);
+        }
+
+        let now = Utc::now();
+        let invite_link = InviteLink {
+            invite_id: Uuid::new_v4().to_string(),
+            token: self.secret_gen.gen_secret(),
+            room_id: room_info.room_id,
+            room_name: room_info.room_name,
+            role,
+            max_uses: payload.max_uses,
+            uses: 0,
+            expires_time: now + Duration::seconds(payload.expires_in_secs as i64),
+            created_by_id: user_info.user_id,
+            created_time: now,
+        };
+        self.membership_repo.create_invite_link(&invite_link)?;
+        Ok(invite_link)
+    }
+
+    // 期限切れや使い切った招待リンクも含める
+    pub async fn get_room_invite_links(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<InviteLink>, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let invite_links = self.membership_repo.get_room_invite_links(room_id)?;
+        Ok(invite_links)
+    }
+
+    pub async fn revoke_invite_link(
+        &self,
+        room_id: &str,
+        invite_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        self.membership_repo
+            .delete_invite_link(room_id, invite_id)?;
+        Ok(())
+    }
+
+    // 無効なトークンと期限切れ・使い切ったトークンを区別しない
+    // 既にメンバーの場合は使用回数を消費せずにそのメンバーシップを返し、memberJoinedも送らない
+    pub async fn accept_invite_link<N>(
+        &self,
+        token: &str,
+        user_info: PubUserInfo,
+        notifier: &N,
+    ) -> Result<Membership, ServiceError>
+    where
+        N: EventNotifier,
+    {
+        let invite_link = self.membership_repo.get_invite_link(token)?;
+        let room_info = self.room_repo.get_room_info(&invite_link.room_id).await?;
+        ensure_not_banned(
+            &self.membership_repo,
+            &room_info.room_id,
+            &user_info.user_id,
+        )?;
+        let now = Utc::now();
+        let membership = self
+            .membership_repo
+            .redeem_invite_link(token, &user_info, now)?;
+        notify_member_joined(notifier, &membership, now);
+        Ok(membership)
+    }
+
+    // ルームが存在しない場合とオーナーでない場合を区別しない
+    async fn get_owner_room(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        domain::{
+            entity::{
+                hook_event::HookEventKind,
+                sanction::{Sanction, SanctionKind},
+                visibility::Visibility,
+            },
+            service::util::event_notifier::RecordingNotifier,
+        },
+        infrastructure::{
+            repository::{
+                membership_repository_impl::MembershipRepositoryImpl,
+                room_repository_impl::{set_up_test_room, test_user, RoomRepositoryImpl},
+            },
+            service::secret_gen_impl::SecretGenImpl,
+        },
+    };
+
+    use super::*;
+
+    type Services = InviteLinkServices<MembershipRepositoryImpl, RoomRepositoryImpl, SecretGenImpl>;
+
+    fn payload(max_uses: Option<u32>, role: Option<RoomRole>) -> CreateInviteLink {
+        CreateInviteLink {
+            expires_in_secs: 3600,
+            max_uses,
+            role,
+        }
+    }
+
+    async fn set_up() -> (Services, String) {
+        let (room_db, membership_db, room_info) = set_up_test_room(Visibility::Public, &[]).await;
+        let services = InviteLinkServices::new(
+            MembershipRepositoryImpl::new(membership_db),
+            RoomRepositoryImpl::new(room_db),
+            SecretGenImpl,
+        );
+        (services, room_info.room_id)
+    }
+
+    #[tokio::test]
+    async fn test_create_invite_link() {
+        let (services, room_id) = set_up().await;
+
+        // テスト対象
+        // オーナーのロールでは招待できない
+        let result = services
+            .create_invite_link(
+                &room_id,
+                payload(None, Some(RoomRole::Owner)),
+                test_user("owner"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Validation)));
+        // オーナー以外には存在しないものとして扱う
+        let result = services
+            .create_invite_link(&room_id, payload(None, None), test_user("other"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+
+        let invite_link = services
+            .create_invite_link(&room_id, payload(None, None), test_user("owner"))
+            .await
+            .unwrap();
+        assert_eq!(invite_link.role, RoomRole::Member);
+    }
+
+    #[tokio::test]
+    async fn test_accept_invite_link() {
+        let (services, room_id) = set_up().await;
+        let notifier = RecordingNotifier::default();
+        let invite_link = services
+            .create_invite_link(
+                &room_id,
+                payload(Some(1), Some(RoomRole::Moderator)),
+                test_user("owner"),
+            )
+            .await
+            .unwrap();
+        services
+            .membership_repo
+            .add_member(&room_id, &test_user("member"), RoomRole::Member)
+            .unwrap();
+
+        // テスト対象
+        // 既にメンバーの場合は使用回数を消費せず、ロールも変えない
+        let membership = services
+            .accept_invite_link(&invite_link.token, test_user("member"), &notifier)
+            .await
+            .unwrap();
+        assert_eq!(membership.role, RoomRole::Member);
+        let membership = services
+            .accept_invite_link(&invite_link.token, test_user("invitee"), &notifier)
+            .await
+            .unwrap();
+        assert_eq!(membership.role, RoomRole::Moderator);
+
+        // 使い切った招待リンクと無効なトークンを区別しない
+        for token in [invite_link.token.as_str(), "unknown"] {
+            let result = services
+                .accept_invite_link(token, test_user("other"), &notifier)
+                .await;
+            assert!(matches!(result, Err(ServiceError::NotFound)));
+        }
+        // 新しく参加したinviteeのみ通知される
+        assert_eq!(notifier.kinds(), vec![HookEventKind::MemberJoined]);
+    }
+
+    #[tokio::test]
+    async fn test_accept_invite_link_banned() {
+        let (services, room_id) = set_up().await;
+        let invite_link = services
+            .create_invite_link(&room_id, payload(Some(1), None), test_user("owner"))
+            .await
+            .unwrap();
+        services
+            .membership_repo
+            .add_sanction(&Sanction {
+                room_id: room_id.clone(),
+                user_id: "banned_id".to_string(),
+                kind: SanctionKind::Ban,
+                reason: None,
+                issued_by_id: "owner_id".to_string(),
+                created_time: Utc::now(),
+                expires_time: None,
+            })
+            .unwrap();
+
+        // テスト対象
+        let result = services
+            .accept_invite_link(
+                &invite_link.token,
+                test_user("banned"),
+                &RecordingNotifier::default(),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Banned)));
+        // BANされたユーザーは使用回数を消費しない
+        assert!(!services
+            .membership_repo
+            .is_member(&room_id, "banned_id")
+            .unwrap());
+        services
+            .accept_invite_link(
+                &invite_link.token,
+                test_user("invitee"),
+                &RecordingNotifier::default(),
+            )
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    entity::{
+        create_invitation::CreateInvitation, hook_event::HookEvent, invitation::Invitation,
+        membership::Membership, pub_user_info::PubUserInfo, room_info::RoomInfo,
+        room_role::RoomRole, sanction::SanctionKind, visibility::Visibility,
+    },
+    repository::{
+        error::RepositoryError, membership_repository::MembershipRepository,
+        room_repository::RoomRepository, user_repository::UserRepository,
+    },
+};
+
+use super::{error::ServiceError, util::event_notifier::EventNotifier};
+
+pub struct MembershipServices<B, R, U>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+    U: UserRepository,
+{
+    membership_repo: B,
+    room_repo: R,
+    user_repo: U,
+}
+
+impl<B, R, U> MembershipServices<B, R, U>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+    U: UserRepository,
+{
+    pub fn new(membership_repo: B, room_repo: R, user_repo: U) -> Self {
+        Self {
+            membership_repo,
+            room_repo,
+            user_repo,
+        }
+    }
+
+    pub async fn invite(
+        &self,
+        room_id: &str,
+        payload: CreateInvitation,
+        user_info: PubUserInfo,
+    ) -> Result<Invitation, ServiceError> {
+        let room_info = self.get_owner_room(room_id, &user_info).await?;
+        if payload.user_id == user_info.user_id
+            || self.membership_repo.is_member(room_id, &payload.user_id)?
+        {
+            return Err(ServiceError::Validation);
+        }
+        let invitee = self.user_repo.get_user_info_id(&payload.user_id).await?;
+
+        let invitation =
+            self.membership_repo
+                .create_invitation(&room_info, &invitee.user_id, &user_info)?;
+        Ok(invitation)
+    }
+
+    pub async fn get_room_invitations(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<Invitation>, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let invitations = self.membership_repo.get_room_invitations(room_id)?;
+        Ok(invitations)
+    }
+
+    pub async fn revoke_invitation(
+        &self,
+        room_id: &str,
+        invitation_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        self.membership_repo
+            .delete_invitation(room_id, invitation_id)?;
+        Ok(())
+    }
+
+    pub fn get_user_invitations(
+        &self,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<Invitation>, ServiceError> {
+        let invitations = self
+            .membership_repo
+            .get_user_invitations(&user_info.user_id)?;
+        Ok(invitations)
+    }
+
+    pub async fn accept_invitation<N>(
+        &self,
+        invitation_id: &str,
+        user_info: PubUserInfo,
+        notifier: &N,
+    ) -> Result<Membership, ServiceError>
+    where
+        N: EventNotifier,
+    {
+        let invitation = self
+            .membership_repo
+            .take_invitation(invitation_id, &user_info.user_id)?;
+        // 招待後に削除されたルームには参加できない
+        self.room_repo.get_room_info(&invitation.room_id).await?;
+        ensure_not_banned(
+            &self.membership_repo,
+            &invitation.room_id,
+            &user_info.user_id,
+        )?;
+        let since = Utc::now();
+        let membership =
+            self.membership_repo
+                .add_member(&invitation.room_id, &user_info, RoomRole::Member)?;
+        notify_member_joined(notifier, &membership, since);
+        Ok(membership)
+    }
+
+    pub fn decline_invitation(
+        &self,
+        invitation_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.membership_repo
+            .take_invitation(invitation_id, &user_info.user_id)?;
+        Ok(())
+    }
+
+    pub async fn get_room_members(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<Membership>, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if !can_access(&self.membership_repo, &room_info, &user_info.user_id)? {
+            return Err(ServiceError::NotFound);
+        }
+        let members = self.membership_repo.get_room_members(room_id)?;
+        Ok(members)
+    }
+
+    // ルームが存在しない場合とオーナーでない場合を区別しない
+    async fn get_owner_room(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
+}
+
+// 公開ルームは誰でも、非公開ルームはオーナーとメンバーのみアクセスできる
+pub fn can_access<B>(
+    membership_repo: &B,
+    room_info: &RoomInfo,
+    user_id: &str,
+) -> Result<bool, ServiceError>
+where
+    B: MembershipRepository,
+{
+    if room_info.visibility == Visibility::Public || room_info.created_by_id == user_id {
+        return Ok(true);
+    }
+    let is_member = membership_repo.is_member(&room_info.room_id, user_id)?;
+    Ok(is_member)
+}
+
+// BANされている間はルームに参加できない
+pub fn ensure_not_banned<B>(
+    membership_repo: &B,
+    room_id: &str,
+    user_id: &str,
+) -> Result<(), ServiceError>
+where
+    B: MembershipRepository,
+{
+    let ban =
+        membership_repo.get_active_sanction(room_id, user_id, SanctionKind::Ban, Utc::now())?;
+    match ban {
+        Some(_) => Err(ServiceError::Banned),
+        None => Ok(()),
+    }
+}
+
+// オーナーはメンバーとして登録されていなくてもオーナーとして扱う
+pub fn role_of<B>(
+    membership_repo: &B,
+    room_info: &RoomInfo,
+    user_id: &str,
+) -> Result<Option<RoomRole>, ServiceError>
+where
+    B: MembershipRepository,
+{
+    if room_info.created_by_id == user_id {
+        return Ok(Some(RoomRole::Owner));
+    }
+    match membership_repo.get_member(&room_info.room_id, user_id) {
+        Ok(membership) => Ok(Some(membership.role)),
+        Err(RepositoryError::NotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// 新しくメンバーになった場合のみ送信WebhookにmemberJoinedを送る
+// sinceはメンバーに追加する直前の時刻で、それより前から参加していたメンバーには送らない
+pub fn notify_member_joined<N>(notifier: &N, membership: &Membership, since: DateTime<Utc>)
+where
+    N: EventNotifier,
+{
+    if membership.joined_time >= since {
+        let user_info = PubUserInfo {
+            user_id: membership.user_id.clone(),
+            user_name: membership.user_name.clone(),
+        };
+        notifier.notify(&membership.room_id, HookEvent::MemberJoined(user_info));
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::Utc;
+
+use crate::domain::{
+    entity::room_event::{RoomEvent, EXPIRED_BATCH_SIZE},
+    repository::{
+        error::RepositoryError, message_repository::MessageRepository,
+        room_repository::RoomRepository,
+    },
+};
+
+use super::error::ServiceError;
+
+pub struct MessageServices<M, R>
+where
+    M: MessageRepository,
+    R: RoomRepository,
+{
+    message_repo: M,
+    room_repo: R,
+}
+
+impl<M, R> MessageServices<M, R>
+where
+    M: MessageRepository,
+    R: RoomRepository,
+{
+    pub fn new(message_repo: M, room_repo: R) -> Self {
+        Self {
+            message_repo,
+            room_repo,
+        }
+    }
+
+    // 期限切れのメッセージを削除し、クライアントが非表示にできるようにイベントを流す
+    pub async fn sweep_expired_messages(&self) -> Result<usize, ServiceError> {
+        let now = Utc::now();
+        self.message_repo.remove_expired_submissions(now)?;
+        let removed = self.message_repo.remove_expired(now)?;
+
+        let mut removed_count = 0;
+        for (room_id, message_ids) in removed {
+            removed_count += message_ids.len();
+
+            for chunk in message_ids.chunks(EXPIRED_BATCH_SIZE) {
+                let event = RoomEvent::MessagesExpired {
+                    message_ids: chunk.to_vec(),
+                };
+                match self.room_repo.publish(&room_id, event).await {
+                    Ok(_) => {}
+                    // ルームが既に削除されている場合は通知先がない
+                    Err(RepositoryError::NotFound) => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        Ok(removed_count)
+    }
+}
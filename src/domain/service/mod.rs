@@ -1,6 +1,20 @@
+pub mod audit_service;
 pub mod auth_service;
 pub mod chat_service;
 pub mod error;
+pub mod export_service;
+pub mod favorite_service;
+pub mod incoming_hook_service;
+pub mod invite_link_service;
+pub mod membership_service;
+pub mod message_service;
+pub mod moderation_service;
+pub mod outgoing_hook_service;
+pub mod ownership_service;
+pub mod retention_service;
+pub mod room_cleanup_service;
+pub mod room_expiry_service;
 pub mod room_service;
+pub mod slack_import_service;
 pub mod user_service;
 pub mod util;
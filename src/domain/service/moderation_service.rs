@@ -0,0 +1,553 @@
+use chrono::{Duration, Utc};
+
+use crate::domain::{
+    entity::{
+        audit_entry::AuditAction,
+        membership::Membership,
+        moderation_payload::ModerationPayload,
+        pub_user_info::PubUserInfo,
+        room_event::RoomEvent,
+        room_info::RoomInfo,
+        room_role::RoomRole,
+        sanction::{Sanction, SanctionKind},
+        update_role::UpdateRole,
+    },
+    repository::{
+        membership_repository::MembershipRepository, message_repository::MessageRepository,
+        room_repository::RoomRepository,
+    },
+};
+
+use super::{
+    audit_service::record_audit,
+    error::ServiceError,
+    membership_service::{can_access, role_of},
+};
+
+pub struct ModerationServices<B, R>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+{
+    membership_repo: B,
+    room_repo: R,
+}
+
+impl<B, R> ModerationServices<B, R>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+{
+    pub fn new(membership_repo: B, room_repo: R) -> Self {
+        Self {
+            membership_repo,
+            room_repo,
+        }
+    }
+
+    // オーナーのみメンバーをモデレーターに昇格・降格できる
+    pub async fn set_role(
+        &self,
+        room_id: &str,
+        target_id: &str,
+        payload: UpdateRole,
+        user_info: PubUserInfo,
+    ) -> Result<Membership, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        self.ensure_role(&room_info, &user_info.user_id, RoomRole::Owner)?;
+        if payload.role == RoomRole::Owner || target_id == room_info.created_by_id {
+            return Err(ServiceError::Validation);
+        }
+        let membership = self
+            .membership_repo
+            .set_role(room_id, target_id, payload.role)?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::RoleChanged,
+            &user_info,
+            Some(target_id),
+            Some(format!("{:?}", payload.role).to_lowercase()),
+        )?;
+        Ok(membership)
+    }
+
+    // 接続中の全ての接続を切断する。再接続はできる
+    // 接続していないユーザーには何もせず、監査ログにも記録しない
+    pub async fn kick(
+        &self,
+        room_id: &str,
+        payload: ModerationPayload,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        self.ensure_outranks(&room_info, &user_info.user_id, &payload.user_id)?;
+        let event = RoomEvent::Kicked {
+            reason: payload.reason.clone(),
+        };
+        let disconnected = self
+            .room_repo
+            .disconnect_user(room_id, &payload.user_id, event)
+            .await?;
+        // 切断できた場合のみ記録する
+        if disconnected == 0 {
+            return Ok(());
+        }
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::MemberKicked,
+            &user_info,
+            Some(&payload.user_id),
+            payload.reason,
+        )?;
+        Ok(())
+    }
+
+    // 接続中の接続を切断し、期限まで再参加できないようにする
+    // メンバーからも外れるため、非公開ルームには招待し直す必要がある
+    pub async fn ban(
+        &self,
+        room_id: &str,
+        payload: ModerationPayload,
+        user_info: PubUserInfo,
+    ) -> Result<Sanction, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        self.ensure_outranks(&room_info, &user_info.user_id, &payload.user_id)?;
+        let sanction = self.add_sanction(&room_info, SanctionKind::Ban, payload, &user_info)?;
+        self.membership_repo
+            .remove_member(room_id, &sanction.user_id)?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::MemberBanned,
+            &user_info,
+            Some(&sanction.user_id),
+            sanction.reason.clone(),
+        )?;
+
+        let event = RoomEvent::Banned {
+            reason: sanction.reason.clone(),
+            expires_time: sanction.expires_time,
+        };
+        self.room_repo
+            .disconnect_user(room_id, &sanction.user_id, event)
+            .await?;
+        Ok(sanction)
+    }
+
+    // 期限まで投稿できないようにする。接続中の場合も次の投稿から拒否される
+    pub async fn mute(
+        &self,
+        room_id: &str,
+        payload: ModerationPayload,
+        user_info: PubUserInfo,
+    ) -> Result<Sanction, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        self.ensure_outranks(&room_info, &user_info.user_id, &payload.user_id)?;
+        let sanction = self.add_sanction(&room_info, SanctionKind::Mute, payload, &user_info)?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::MemberMuted,
+            &user_info,
+            Some(&sanction.user_id),
+            sanction.reason.clone(),
+        )?;
+        Ok(sanction)
+    }
+
+    pub async fn lift_sanction(
+        &self,
+        room_id: &str,
+        target_id: &str,
+        kind: SanctionKind,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        self.ensure_role(&room_info, &user_info.user_id, RoomRole::Moderator)?;
+        self.membership_repo
+            .remove_sanction(room_id, target_id, kind)?;
+        let action = match kind {
+            SanctionKind::Ban => AuditAction::BanLifted,
+            SanctionKind::Mute => AuditAction::MuteLifted,
+        };
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            action,
+            &user_info,
+            Some(target_id),
+            None,
+        )?;
+        Ok(())
+    }
+
+    // モデレーター以上が削除できる。オーナーやモデレーターのメッセージは上位のロールのみ削除できる
+    // 監査ログには削除したメッセージのIDと投稿者のIDのみ残し、本文は残さない
+    pub async fn delete_message<M>(
+        &self,
+        message_repo: &M,
+        room_id: &str,
+        message_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError>
+    where
+        M: MessageRepository,
+    {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        let role = self.ensure_role(&room_info, &user_info.user_id, RoomRole::Moderator)?;
+        let chat = message_repo
+            .get_messages(room_id)?
+            .into_iter()
+            .find(|chat| chat.message_id == message_id)
+            .ok_or(ServiceError::NotFound)?;
+        if chat.user_id != user_info.user_id
+            && role_of(&self.membership_repo, &room_info, &chat.user_id)?
+                .is_some_and(|author_role| author_role >= role)
+        {
+            return Err(ServiceError::Forbidden);
+        }
+
+        message_repo.delete_message(room_id, message_id)?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::MessageDeleted,
+            &user_info,
+            Some(message_id),
+            Some(chat.user_id),
+        )?;
+        let event = RoomEvent::MessageDeleted {
+            message_id: message_id.to_owned(),
+        };
+        self.room_repo.publish(room_id, event).await?;
+        Ok(())
+    }
+
+    pub async fn get_sanctions(
+        &self,
+        room_id: &str,
+        kind: SanctionKind,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<Sanction>, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        self.ensure_role(&room_info, &user_info.user_id, RoomRole::Moderator)?;
+        let sanctions = self
+            .membership_repo
+            .get_room_sanctions(room_id, kind, Utc::now())?;
+        Ok(sanctions)
+    }
+
+    fn add_sanction(
+        &self,
+        room_info: &RoomInfo,
+        kind: SanctionKind,
+        payload: ModerationPayload,
+        user_info: &PubUserInfo,
+    ) -> Result<Sanction, ServiceError> {
+        let now = Utc::now();
+        let sanction = Sanction {
+            room_id: room_info.room_id.clone(),
+            user_id: payload.user_id,
+            kind,
+            reason: payload.reason,
+            issued_by_id: user_info.user_id.clone(),
+            created_time: now,
+            expires_time: payload
+                .duration_secs
+                .map(|secs| now + Duration::seconds(secs as i64)),
+        };
+        self.membership_repo.add_sanction(&sanction)?;
+        Ok(sanction)
+    }
+
+    // ルームを見られないユーザーにはルームの存在を明かさない
+    fn ensure_role(
+        &self,
+        room_info: &RoomInfo,
+        user_id: &str,
+        required: RoomRole,
+    ) -> Result<RoomRole, ServiceError> {
+        match role_of(&self.membership_repo, room_info, user_id)? {
+            Some(role) if role >= required => Ok(role),
+            _ if can_access(&self.membership_repo, room_info, user_id)? => {
+                Err(ServiceError::Forbidden)
+            }
+            _ => Err(ServiceError::NotFound),
+        }
+    }
+
+    // 期限切れの制裁を削除する。判定は期限を見て行うため、削除されるまでの間も効力はない
+    pub fn sweep_expired_sanctions(&self) -> Result<usize, ServiceError> {
+        Ok(self.membership_repo.remove_expired_sanctions(Utc::now())?)
+    }
+
+    // モデレーターはメンバーのみ、オーナーはモデレーターとメンバーを対象にできる
+    fn ensure_outranks(
+        &self,
+        room_info: &RoomInfo,
+        user_id: &str,
+        target_id: &str,
+    ) -> Result<(), ServiceError> {
+        let role = self.ensure_role(room_info, user_id, RoomRole::Moderator)?;
+        if user_id == target_id {
+            return Err(ServiceError::Validation);
+        }
+        match role_of(&self.membership_repo, room_info, target_id)? {
+            Some(target_role) if target_role >= role => Err(ServiceError::Forbidden),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::sync::mpsc;
+
+    use crate::{
+        domain::entity::{chat::Chat, visibility::Visibility},
+        infrastructure::repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::{set_up_test_room, test_user, RoomRepositoryImpl},
+        },
+        MembershipDb, MessageDb,
+    };
+
+    use super::*;
+
+    // ownerのルームにmoderatorとmemberが参加し、memberとmoderatorが1件ずつ投稿している
+    async fn set_up() -> (
+        ModerationServices<MembershipRepositoryImpl, RoomRepositoryImpl>,
+        MessageRepositoryImpl,
+        MembershipDb,
+        String,
+        Chat,
+        Chat,
+    ) {
+        let members = [
+            ("moderator", RoomRole::Moderator),
+            ("member", RoomRole::Member),
+        ];
+        let (room_db, membership_db, room_info) =
+            set_up_test_room(Visibility::Public, &members).await;
+        let room_id = room_info.room_id;
+
+        let message_repo = MessageRepositoryImpl::new(MessageDb::new());
+        let member_chat = Chat::from_str("member_id", "member", "spam");
+        let moderator_chat = Chat::from_str("moderator_id", "moderator", "notice");
+        message_repo.save_message(&room_id, &member_chat).unwrap();
+        message_repo
+            .save_message(&room_id, &moderator_chat)
+            .unwrap();
+
+        let services = ModerationServices::new(
+            MembershipRepositoryImpl::new(membership_db.clone()),
+            RoomRepositoryImpl::new(room_db),
+        );
+        (
+            services,
+            message_repo,
+            membership_db,
+            room_id,
+            member_chat,
+            moderator_chat,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_kick() {
+        let (services, _, membership_db, room_id, _, _) = set_up().await;
+        let payload = || ModerationPayload {
+            user_id: "member_id".to_string(),
+            duration_secs: None,
+            reason: Some("spam".to_string()),
+        };
+
+        // テスト対象
+        // 接続していなければ記録しない
+        services
+            .kick(&room_id, payload(), test_user("moderator"))
+            .await
+            .unwrap();
+        let membership_repo = MembershipRepositoryImpl::new(membership_db);
+        assert!(membership_repo
+            .get_audit_log(&room_id, None, 10)
+            .unwrap()
+            .is_empty());
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        services
+            .room_repo
+            .join(&room_id, "connection", &test_user("member"), None, sender)
+            .await
+            .unwrap();
+        services
+            .kick(&room_id, payload(), test_user("moderator"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            receiver.recv().await,
+            Some(RoomEvent::Kicked { .. })
+        ));
+        let audit_log = membership_repo.get_audit_log(&room_id, None, 10).unwrap();
+        assert_eq!(audit_log.len(), 1);
+        assert_eq!(audit_log[0].action, AuditAction::MemberKicked);
+        assert_eq!(audit_log[0].target_id.as_deref(), Some("member_id"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_message() {
+        let (services, message_repo, membership_db, room_id, member_chat, _) = set_up().await;
+
+        // テスト対象
+        services
+            .delete_message(
+                &message_repo,
+                &room_id,
+                &member_chat.message_id,
+                test_user("moderator"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(message_repo.get_messages(&room_id).unwrap().len(), 1);
+
+        let audit_log = MembershipRepositoryImpl::new(membership_db)
+            .get_audit_log(&room_id, None, 10)
+            .unwrap();
+        assert_eq!(audit_log.len(), 1);
+        assert_eq!(audit_log[0].action, AuditAction::MessageDeleted);
+        assert_eq!(audit_log[0].actor_id, "moderator_id");
+        assert_eq!(
+            audit_log[0].target_id.as_deref(),
+            Some(member_chat.message_id.as_str())
+        );
+        assert_eq!(audit_log[0].detail.as_deref(), Some("member_id"));
+
+        // 削除済みのメッセージは見つからない
+        let result = services
+            .delete_message(
+                &message_repo,
+                &room_id,
+                &member_chat.message_id,
+                test_user("moderator"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_message_requires_rank() {
+        let (services, message_repo, membership_db, room_id, member_chat, moderator_chat) =
+            set_up().await;
+
+        // メンバーは自分のメッセージでも削除できない
+        let result = services
+            .delete_message(
+                &message_repo,
+                &room_id,
+                &member_chat.message_id,
+                test_user("member"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        // 公開ルームのため、参加していないユーザーにも権限がないことを返す
+        let result = services
+            .delete_message(
+                &message_repo,
+                &room_id,
+                &member_chat.message_id,
+                test_user("outsider"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+
+        // モデレーターのメッセージは他のモデレーターは削除できず、オーナーは削除できる
+        MembershipRepositoryImpl::new(membership_db)
+            .add_member(&room_id, &test_user("moderator2"), RoomRole::Moderator)
+            .unwrap();
+        let result = services
+            .delete_message(
+                &message_repo,
+                &room_id,
+                &moderator_chat.message_id,
+                test_user("moderator2"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        services
+            .delete_message(
+                &message_repo,
+                &room_id,
+                &moderator_chat.message_id,
+                test_user("owner"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(message_repo.get_messages(&room_id).unwrap().len(), 1);
+    }
+
+    fn mute_payload(user_id: &str) -> ModerationPayload {
+        ModerationPayload {
+            user_id: user_id.to_string(),
+            duration_secs: Some(60),
+            reason: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensure_outranks() {
+        let (services, _, membership_db, room_id, _, _) = set_up().await;
+        MembershipRepositoryImpl::new(membership_db)
+            .add_member(&room_id, &test_user("moderator2"), RoomRole::Moderator)
+            .unwrap();
+
+        // テスト対象
+        // メンバーはモデレーションできない
+        let result = services
+            .mute(&room_id, mute_payload("outsider_id"), test_user("member"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        // 自分自身は対象にできない
+        let result = services
+            .mute(
+                &room_id,
+                mute_payload("moderator_id"),
+                test_user("moderator"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Validation)));
+        // 同じロールや上のロールは対象にできない
+        let result = services
+            .mute(
+                &room_id,
+                mute_payload("moderator2_id"),
+                test_user("moderator"),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        let result = services
+            .mute(&room_id, mute_payload("owner_id"), test_user("moderator"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+
+        // モデレーターはメンバーと参加していないユーザーを、オーナーはモデレーターを対象にできる
+        services
+            .mute(&room_id, mute_payload("member_id"), test_user("moderator"))
+            .await
+            .unwrap();
+        services
+            .mute(
+                &room_id,
+                mute_payload("outsider_id"),
+                test_user("moderator"),
+            )
+            .await
+            .unwrap();
+        services
+            .mute(&room_id, mute_payload("moderator_id"), test_user("owner"))
+            .await
+            .unwrap();
+    }
+}
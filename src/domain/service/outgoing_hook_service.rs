@@ -0,0 +1,110 @@
+use crate::domain::{
+    entity::{
+        create_outgoing_hook::CreateOutgoingHook, hook_delivery::HookDelivery,
+        hook_target_policy::HookTargetPolicy, outgoing_hook::OutgoingHook,
+        pub_user_info::PubUserInfo, room_info::RoomInfo,
+    },
+    repository::{
+        outgoing_hook_repository::OutgoingHookRepository, room_repository::RoomRepository,
+    },
+};
+
+use super::{error::ServiceError, util::secret_gen::SecretGen};
+
+pub struct OutgoingHookServices<H, R, G>
+where
+    H: OutgoingHookRepository,
+    R: RoomRepository,
+    G: SecretGen,
+{
+    hook_repo: H,
+    room_repo: R,
+    secret_gen: G,
+}
+
+impl<H, R, G> OutgoingHookServices<H, R, G>
+where
+    H: OutgoingHookRepository,
+    R: RoomRepository,
+    G: SecretGen,
+{
+    pub fn new(hook_repo: H, room_repo: R, secret_gen: G) -> Self {
+        Self {
+            hook_repo,
+            room_repo,
+            secret_gen,
+        }
+    }
+
+    pub async fn create_hook(
+        &self,
+        room_id: &str,
+        payload: CreateOutgoingHook,
+        user_info: PubUserInfo,
+        target_policy: HookTargetPolicy,
+    ) -> Result<OutgoingHook, ServiceError> {
+        if !target_policy.allows_url(&payload.callback_url) {
+            return Err(ServiceError::Validation);
+        }
+        self.get_owner_room(room_id, &user_info).await?;
+
+        let secret = self.secret_gen.gen_secret();
+        let hook = self.hook_repo.create_hook(
+            room_id,
+            &payload.callback_url,
+            &secret,
+            &payload.events,
+            &user_info.user_id,
+        )?;
+        Ok(hook)
+    }
+
+    pub async fn get_room_hooks(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<OutgoingHook>, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let hooks = self.hook_repo.get_room_hooks(room_id)?;
+        Ok(hooks)
+    }
+
+    pub async fn revoke_hook(
+        &self,
+        room_id: &str,
+        hook_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        self.hook_repo.delete_hook(room_id, hook_id)?;
+        Ok(())
+    }
+
+    pub async fn get_deliveries(
+        &self,
+        room_id: &str,
+        hook_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<HookDelivery>, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let hooks = self.hook_repo.get_room_hooks(room_id)?;
+        if !hooks.iter().any(|hook| hook.hook_id == hook_id) {
+            return Err(ServiceError::NotFound);
+        }
+        let deliveries = self.hook_repo.get_deliveries(hook_id)?;
+        Ok(deliveries)
+    }
+
+    // オーナー以外にはルームの存在を明かさない
+    async fn get_owner_room(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
+}
@@ -0,0 +1,448 @@
+use chrono::Utc;
+
+use crate::domain::{
+    entity::{
+        audit_entry::AuditAction, membership::Membership, orphan_policy::OrphanPolicy,
+        ownership_transfer::OwnershipTransfer, pub_user_info::PubUserInfo, room_info::RoomInfo,
+        room_role::RoomRole, transfer_ownership::TransferOwnership,
+    },
+    repository::{membership_repository::MembershipRepository, room_repository::RoomRepository},
+};
+
+use super::{audit_service::record_audit, error::ServiceError};
+
+pub struct OwnershipServices<B, R>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+{
+    membership_repo: B,
+    room_repo: R,
+}
+
+impl<B, R> OwnershipServices<B, R>
+where
+    B: MembershipRepository,
+    R: RoomRepository,
+{
+    pub fn new(membership_repo: B, room_repo: R) -> Self {
+        Self {
+            membership_repo,
+            room_repo,
+        }
+    }
+
+    // 譲渡先はルームのメンバーに限る
+    pub async fn request_transfer(
+        &self,
+        room_id: &str,
+        payload: TransferOwnership,
+        user_info: PubUserInfo,
+    ) -> Result<OwnershipTransfer, ServiceError> {
+        let room_info = self.get_owner_room(room_id, &user_info).await?;
+        if payload.user_id == user_info.user_id {
+            return Err(ServiceError::Validation);
+        }
+        let target = self
+            .membership_repo
+            .get_member(room_id, &payload.user_id)
+            .map_err(|_| ServiceError::Validation)?;
+
+        let transfer = OwnershipTransfer {
+            room_id: room_info.room_id,
+            room_name: room_info.room_name,
+            from_id: user_info.user_id,
+            from_name: user_info.user_name,
+            to_id: target.user_id,
+            to_name: target.user_name,
+            created_time: Utc::now(),
+        };
+        self.membership_repo.create_transfer(&transfer)?;
+        Ok(transfer)
+    }
+
+    pub async fn get_room_transfer(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<OwnershipTransfer, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let transfer = self.membership_repo.get_transfer(room_id)?;
+        Ok(transfer)
+    }
+
+    pub async fn cancel_transfer(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        self.membership_repo.delete_transfer(room_id)?;
+        Ok(())
+    }
+
+    pub fn get_user_transfers(
+        &self,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<OwnershipTransfer>, ServiceError> {
+        let transfers = self
+            .membership_repo
+            .get_user_transfers(&user_info.user_id)?;
+        Ok(transfers)
+    }
+
+    // 元のオーナーはモデレーターとしてルームに残る
+    pub async fn accept_transfer(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let transfer = self
+            .membership_repo
+            .take_transfer(room_id, &user_info.user_id)?;
+        // 依頼後にオーナーが変わったり、BANなどでメンバーでなくなった場合は無効になる
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != transfer.from_id
+            || !self
+                .membership_repo
+                .is_member(room_id, &user_info.user_id)?
+        {
+            return Err(ServiceError::NotFound);
+        }
+
+        let room_info = self.room_repo.transfer_room(room_id, &user_info).await?;
+        self.membership_repo
+            .set_role(room_id, &user_info.user_id, RoomRole::Owner)?;
+        let previous_owner = PubUserInfo {
+            user_id: transfer.from_id,
+            user_name: transfer.from_name,
+        };
+        self.membership_repo
+            .put_member(room_id, &previous_owner, RoomRole::Moderator)?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::OwnershipTransferred,
+            &previous_owner,
+            Some(&user_info.user_id),
+            Some("transfer accepted".to_string()),
+        )?;
+        Ok(room_info)
+    }
+
+    pub fn decline_transfer(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<(), ServiceError> {
+        self.membership_repo
+            .take_transfer(room_id, &user_info.user_id)?;
+        Ok(())
+    }
+
+    // 削除されたユーザーがオーナーのルームをポリシーに従って処理し、メンバーシップを削除する
+    // アーカイブしたルームは、管理者がclaim_orphaned_roomで引き継ぐまでオーナーのいないルームになる
+    // 削除したルームのメッセージやWebhookは呼び出し側で削除するため、削除したルームを返す
+    pub async fn release_user_rooms(
+        &self,
+        user_id: &str,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<RoomInfo>, ServiceError> {
+        let mut deleted = Vec::new();
+        for room_info in self.room_repo.get_owner_rooms(user_id).await? {
+            let room_id = room_info.room_id.as_str();
+            // 削除されたオーナーによる操作として記録する
+            let owner = PubUserInfo {
+                user_id: room_info.created_by_id.clone(),
+                user_name: room_info.created_by_name.clone(),
+            };
+            match policy {
+                OrphanPolicy::Transfer => match self.longest_standing_moderator(&room_info)? {
+                    Some(moderator) => {
+                        let new_owner = PubUserInfo {
+                            user_id: moderator.user_id,
+                            user_name: moderator.user_name,
+                        };
+                        self.room_repo.transfer_room(room_id, &new_owner).await?;
+                        self.membership_repo.set_role(
+                            room_id,
+                            &new_owner.user_id,
+                            RoomRole::Owner,
+                        )?;
+                        record_audit(
+                            &self.membership_repo,
+                            room_id,
+                            AuditAction::OwnershipTransferred,
+                            &owner,
+                            Some(&new_owner.user_id),
+                            Some("owner account deleted".to_string()),
+                        )?;
+                    }
+                    None => {
+                        self.room_repo.orphan_room(room_id).await?;
+                        record_audit(
+                            &self.membership_repo,
+                            room_id,
+                            AuditAction::RoomArchived,
+                            &owner,
+                            None,
+                            Some("owner account deleted".to_string()),
+                        )?;
+                    }
+                },
+                OrphanPolicy::Archive => {
+                    self.room_repo.orphan_room(room_id).await?;
+                    record_audit(
+                        &self.membership_repo,
+                        room_id,
+                        AuditAction::RoomArchived,
+                        &owner,
+                        None,
+                        Some("owner account deleted".to_string()),
+                    )?;
+                }
+                OrphanPolicy::Delete => {
+                    self.room_repo.delete_room(room_id).await?;
+                    self.membership_repo.delete_room(room_id)?;
+                    deleted.push(room_info);
+                }
+            }
+        }
+        self.membership_repo.delete_user(user_id)?;
+        Ok(deleted)
+    }
+
+    // 管理者であることは呼び出し側で確認する
+    // 引き継いだ管理者がオーナーになり、アーカイブはオーナーとして解除できる
+    pub async fn claim_orphaned_room(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.orphaned_time.is_none() {
+            return Err(ServiceError::NotFound);
+        }
+        let previous_owner = PubUserInfo {
+            user_id: room_info.created_by_id,
+            user_name: room_info.created_by_name,
+        };
+        let room_info = self.room_repo.transfer_room(room_id, &user_info).await?;
+        self.membership_repo
+            .put_member(room_id, &user_info, RoomRole::Owner)?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::OwnershipTransferred,
+            &previous_owner,
+            Some(&user_info.user_id),
+            Some("orphaned room claimed".to_string()),
+        )?;
+        Ok(room_info)
+    }
+
+    fn longest_standing_moderator(
+        &self,
+        room_info: &RoomInfo,
+    ) -> Result<Option<Membership>, ServiceError> {
+        // メンバーは参加した順に並んでいる
+        let moderator = self
+            .membership_repo
+            .get_room_members(&room_info.room_id)?
+            .into_iter()
+            .find(|membership| {
+                membership.role == RoomRole::Moderator
+                    && membership.user_id != room_info.created_by_id
+            });
+        Ok(moderator)
+    }
+
+    // ルームが存在しない場合とオーナーでない場合を区別しない
+    async fn get_owner_room(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        domain::{entity::visibility::Visibility, repository::error::RepositoryError},
+        infrastructure::repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            room_repository_impl::{set_up_test_room, test_user, RoomRepositoryImpl},
+        },
+    };
+
+    use super::*;
+
+    // ownerの非公開ルームにmemberが参加している
+    async fn set_up() -> (
+        OwnershipServices<MembershipRepositoryImpl, RoomRepositoryImpl>,
+        MembershipRepositoryImpl,
+        RoomRepositoryImpl,
+        String,
+    ) {
+        let members = [("owner", RoomRole::Owner), ("member", RoomRole::Member)];
+        let (room_db, membership_db, room_info) =
+            set_up_test_room(Visibility::Private, &members).await;
+        let services = OwnershipServices::new(
+            MembershipRepositoryImpl::new(membership_db.clone()),
+            RoomRepositoryImpl::new(room_db.clone()),
+        );
+        (
+            services,
+            MembershipRepositoryImpl::new(membership_db),
+            RoomRepositoryImpl::new(room_db),
+            room_info.room_id,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_accept_transfer() {
+        let (services, membership_repo, _, room_id) = set_up().await;
+        let payload = TransferOwnership {
+            user_id: "member_id".to_string(),
+        };
+        services
+            .request_transfer(&room_id, payload, test_user("owner"))
+            .await
+            .unwrap();
+
+        // テスト対象
+        let room_info = services
+            .accept_transfer(&room_id, test_user("member"))
+            .await
+            .unwrap();
+        assert_eq!(room_info.created_by_id, "member_id");
+        let new_owner = membership_repo.get_member(&room_id, "member_id").unwrap();
+        assert_eq!(new_owner.role, RoomRole::Owner);
+        // 元のオーナーはモデレーターとして残る
+        let previous_owner = membership_repo.get_member(&room_id, "owner_id").unwrap();
+        assert_eq!(previous_owner.role, RoomRole::Moderator);
+
+        // 承諾済みの譲渡は残らない
+        let result = services
+            .accept_transfer(&room_id, test_user("member"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_accept_transfer_after_leaving() {
+        let (services, membership_repo, _, room_id) = set_up().await;
+        let payload = TransferOwnership {
+            user_id: "member_id".to_string(),
+        };
+        services
+            .request_transfer(&room_id, payload, test_user("owner"))
+            .await
+            .unwrap();
+        membership_repo
+            .remove_member(&room_id, "member_id")
+            .unwrap();
+
+        // テスト対象
+        // 依頼後にメンバーでなくなった場合は承諾できない
+        let result = services
+            .accept_transfer(&room_id, test_user("member"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_release_user_rooms_transfer() {
+        let (services, membership_repo, room_repo, room_id) = set_up().await;
+        membership_repo
+            .add_member(&room_id, &test_user("moderator"), RoomRole::Moderator)
+            .unwrap();
+
+        // テスト対象
+        let deleted = services
+            .release_user_rooms("owner_id", OrphanPolicy::Transfer)
+            .await
+            .unwrap();
+        assert!(deleted.is_empty());
+        let room_info = room_repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.created_by_id, "moderator_id");
+        let new_owner = membership_repo
+            .get_member(&room_id, "moderator_id")
+            .unwrap();
+        assert_eq!(new_owner.role, RoomRole::Owner);
+        assert!(!membership_repo.is_member(&room_id, "owner_id").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_release_user_rooms_without_moderator() {
+        let (services, _, room_repo, room_id) = set_up().await;
+
+        // テスト対象
+        // 譲渡できるモデレーターがいなければアーカイブする
+        services
+            .release_user_rooms("owner_id", OrphanPolicy::Transfer)
+            .await
+            .unwrap();
+        let room_info = room_repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.created_by_id, "owner_id");
+        assert!(room_info.is_archived());
+        assert!(room_info.orphaned_time.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_claim_orphaned_room() {
+        let (services, membership_repo, room_repo, room_id) = set_up().await;
+
+        // テスト対象
+        // オーナーのいるルームは引き継げない
+        let result = services
+            .claim_orphaned_room(&room_id, test_user("admin"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+
+        services
+            .release_user_rooms("owner_id", OrphanPolicy::Archive)
+            .await
+            .unwrap();
+        let room_info = services
+            .claim_orphaned_room(&room_id, test_user("admin"))
+            .await
+            .unwrap();
+        assert_eq!(room_info.created_by_id, "admin_id");
+        assert_eq!(room_info.orphaned_time, None);
+        // アーカイブは引き継いだオーナーが解除するまで残る
+        assert!(room_info.is_archived());
+        let admin = membership_repo.get_member(&room_id, "admin_id").unwrap();
+        assert_eq!(admin.role, RoomRole::Owner);
+        assert!(room_repo
+            .get_owner_rooms("owner_id")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_release_user_rooms_delete() {
+        let (services, membership_repo, room_repo, room_id) = set_up().await;
+
+        // テスト対象
+        let deleted = services
+            .release_user_rooms("owner_id", OrphanPolicy::Delete)
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].room_id, room_id);
+        let result = room_repo.get_room_info(&room_id).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+        assert!(membership_repo
+            .get_room_members(&room_id)
+            .unwrap()
+            .is_empty());
+    }
+}
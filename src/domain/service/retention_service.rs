@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::{
+    entity::{
+        pub_user_info::PubUserInfo,
+        retention_policy::RetentionLimits,
+        retention_purge::RetentionPurge,
+        room_event::{RoomEvent, EXPIRED_BATCH_SIZE},
+    },
+    repository::{
+        error::RepositoryError, message_repository::MessageRepository,
+        room_repository::RoomRepository,
+    },
+};
+
+use super::error::ServiceError;
+
+pub struct RetentionServices<M, R>
+where
+    M: MessageRepository,
+    R: RoomRepository,
+{
+    message_repo: M,
+    room_repo: R,
+}
+
+impl<M, R> RetentionServices<M, R>
+where
+    M: MessageRepository,
+    R: RoomRepository,
+{
+    pub fn new(message_repo: M, room_repo: R) -> Self {
+        Self {
+            message_repo,
+            room_repo,
+        }
+    }
+
+    // 保持期間を過ぎたメッセージを削除して記録し、クライアントが非表示にできるようにイベントをまとめて流す
+    // 復元できる削除済みのルームにも適用する
+    pub async fn purge_messages(
+        &self,
+        limits: RetentionLimits,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<RetentionPurge>, ServiceError> {
+        let mut rooms = self.room_repo.get_all_room().await?;
+        rooms.extend(self.room_repo.get_deleted_rooms().await?);
+
+        let mut purges = Vec::new();
+        for room_info in rooms {
+            let (days, count) = limits.effective(&room_info.retention);
+            if days.is_none() && count.is_none() {
+                continue;
+            }
+            let room_id = room_info.room_id.as_str();
+            let removed = self.message_repo.remove_outside_retention(
+                room_id,
+                days.map(|days| now - Duration::days(days as i64)),
+                count.map(|count| count as usize),
+            )?;
+            let (Some(oldest), Some(newest)) = (removed.first(), removed.last()) else {
+                continue;
+            };
+            let purge = RetentionPurge {
+                room_id: room_id.to_owned(),
+                policy: room_info.retention,
+                count: removed.len(),
+                oldest_time: oldest.time,
+                newest_time: newest.time,
+                purged_time: now,
+            };
+            self.message_repo.save_purge(&purge)?;
+
+            for chunk in removed.chunks(EXPIRED_BATCH_SIZE) {
+                let event = RoomEvent::MessagesExpired {
+                    message_ids: chunk.iter().map(|chat| chat.message_id.clone()).collect(),
+                };
+                match self.room_repo.publish(room_id, event).await {
+                    Ok(_) => {}
+                    Err(RepositoryError::NotFound) => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            purges.push(purge);
+        }
+        Ok(purges)
+    }
+
+    // オーナー以外には存在しないものとして扱う
+    pub async fn get_room_purges(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<RetentionPurge>, ServiceError> {
+        let room_info = self.room_repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        let purges = self.message_repo.get_purges(room_id)?;
+        Ok(purges)
+    }
+}
@@ -0,0 +1,132 @@
+use crate::domain::{
+    entity::room_info::RoomInfo,
+    repository::{
+        favorite_repository::FavoriteRepository, incoming_hook_repository::IncomingHookRepository,
+        message_repository::MessageRepository,
+    },
+};
+
+use super::{error::ServiceError, util::event_notifier::EventNotifier};
+
+pub struct RoomCleanupServices<M, H, F, N>
+where
+    M: MessageRepository,
+    H: IncomingHookRepository,
+    F: FavoriteRepository,
+    N: EventNotifier,
+{
+    message_repo: M,
+    hook_repo: H,
+    favorite_repo: F,
+    notifier: N,
+}
+
+impl<M, H, F, N> RoomCleanupServices<M, H, F, N>
+where
+    M: MessageRepository,
+    H: IncomingHookRepository,
+    F: FavoriteRepository,
+    N: EventNotifier,
+{
+    pub fn new(message_repo: M, hook_repo: H, favorite_repo: F, notifier: N) -> Self {
+        Self {
+            message_repo,
+            hook_repo,
+            favorite_repo,
+            notifier,
+        }
+    }
+
+    // 完全に削除したルームのメッセージと受信Webhookを削除し、お気に入りから外す
+    // 送信Webhookはルームの削除を通知し、配送が終わってから削除される
+    pub fn delete_room_resources(&self, room_info: RoomInfo) -> Result<(), ServiceError> {
+        let room_id = room_info.room_id.clone();
+        self.notifier.notify_room_deleted(room_info);
+        self.message_repo.delete_room_messages(&room_id)?;
+        self.hook_repo.delete_room_hooks(&room_id)?;
+        self.favorite_repo.delete_room(&room_id)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+
+    use crate::{
+        domain::entity::{chat::Chat, hook_event::HookEvent},
+        infrastructure::repository::{
+            favorite_repository_impl::FavoriteRepositoryImpl,
+            incoming_hook_repository_impl::IncomingHookRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl, room_repository_impl::test_room_info,
+        },
+        FavoriteDb, IncomingHookDb, MessageDb,
+    };
+
+    use super::*;
+
+    // 通知しない
+    struct NoopNotifier;
+
+    impl EventNotifier for NoopNotifier {
+        fn notify(&self, _room_id: &str, _event: HookEvent) {}
+    }
+
+    #[test]
+    fn test_delete_room_resources() {
+        let services = RoomCleanupServices::new(
+            MessageRepositoryImpl::new(MessageDb::new()),
+            IncomingHookRepositoryImpl::new(IncomingHookDb::new()),
+            FavoriteRepositoryImpl::new(FavoriteDb::new()),
+            NoopNotifier,
+        );
+        // 削除するルームと残すルームに同じものを用意する
+        for room_id in ["room_id", "other_id"] {
+            let chat = Chat::from_str("user_id", "user_name", "text");
+            services.message_repo.save_message(room_id, &chat).unwrap();
+            services
+                .hook_repo
+                .create_hook(room_id, "ci", &format!("{}_token", room_id), "user_id")
+                .unwrap();
+            for user_id in ["user_id", "other_user_id"] {
+                services
+                    .favorite_repo
+                    .put_favorite(user_id, room_id, None, None, false, Utc::now())
+                    .unwrap();
+            }
+        }
+
+        // テスト対象
+        services
+            .delete_room_resources(test_room_info("room_id"))
+            .unwrap();
+        assert!(services
+            .message_repo
+            .get_messages("room_id")
+            .unwrap()
+            .is_empty());
+        assert!(services
+            .hook_repo
+            .get_room_hooks("room_id")
+            .unwrap()
+            .is_empty());
+        for user_id in ["user_id", "other_user_id"] {
+            let favorites = services.favorite_repo.get_favorites(user_id).unwrap();
+            assert_eq!(favorites.len(), 1);
+            assert_eq!(favorites[0].room_id, "other_id");
+        }
+        // 他のルームには影響しない
+        assert_eq!(
+            services
+                .message_repo
+                .get_messages("other_id")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            services.hook_repo.get_room_hooks("other_id").unwrap().len(),
+            1
+        );
+    }
+}
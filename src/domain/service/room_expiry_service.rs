@@ -0,0 +1,349 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::{
+    entity::{hook_event::HookEvent, idle_expiry::IdleExpiry, room_info::RoomInfo},
+    repository::{membership_repository::MembershipRepository, room_repository::RoomRepository},
+};
+
+use super::{error::ServiceError, util::event_notifier::EventNotifier};
+
+pub struct RoomExpiryServices<R, B, N>
+where
+    R: RoomRepository,
+    B: MembershipRepository,
+    N: EventNotifier,
+{
+    room_repo: R,
+    membership_repo: B,
+    notifier: N,
+}
+
+impl<R, B, N> RoomExpiryServices<R, B, N>
+where
+    R: RoomRepository,
+    B: MembershipRepository,
+    N: EventNotifier,
+{
+    pub fn new(room_repo: R, membership_repo: B, notifier: N) -> Self {
+        Self {
+            room_repo,
+            membership_repo,
+            notifier,
+        }
+    }
+
+    // 通知した削除予定時刻を過ぎたルームを削除し、期限が近づいたルームはオーナーに通知する
+    // 通知はexpires_timeとして残り、オーナーは送信Webhookか期限の迫ったルームの一覧で確認する
+    // 通知せずに期限を過ぎたルーム(サーバーが止まっていた場合など)は、通知してから猶予を与える
+    // 削除したルームのメッセージやWebhookは呼び出し側で削除するため、削除したルームを返す
+    pub async fn reap_idle_rooms(
+        &self,
+        expiry: IdleExpiry,
+        now: DateTime<Utc>,
+    ) -> Result<RoomSweep, ServiceError> {
+        let mut sweep = RoomSweep::default();
+        for room_info in self.room_repo.get_all_room().await? {
+            let Some(deadline) = idle_deadline(&room_info, expiry.default_ttl) else {
+                continue;
+            };
+            let room_id = room_info.room_id.clone();
+            let result = match room_info.expires_time {
+                Some(expires_time) if now >= expires_time => {
+                    self.remove_room(room_info, &mut sweep).await
+                }
+                None if now >= deadline - expiry.warning => {
+                    self.warn_expiring(&room_id, deadline.max(now + expiry.warning))
+                        .await
+                }
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                sweep.failed.push((room_id, e));
+            }
+        }
+        Ok(sweep)
+    }
+
+    // 削除されてから猶予期間が過ぎたルームを完全に削除する
+    // 送信WebhookのroomDeletedは、呼び出し側がここで完全に削除したルームについて送る
+    pub async fn purge_deleted_rooms(
+        &self,
+        grace: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<RoomSweep, ServiceError> {
+        let mut sweep = RoomSweep::default();
+        for room_info in self.room_repo.get_deleted_rooms().await? {
+            if room_info
+                .deleted_time
+                .is_some_and(|deleted_time| now >= deleted_time + grace)
+            {
+                let room_id = room_info.room_id.clone();
+                if let Err(e) = self.remove_room(room_info, &mut sweep).await {
+                    sweep.failed.push((room_id, e));
+                }
+            }
+        }
+        Ok(sweep)
+    }
+
+    async fn warn_expiring(
+        &self,
+        room_id: &str,
+        expires_time: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        let room_info = self
+            .room_repo
+            .set_expires_time(room_id, expires_time)
+            .await?;
+        let event = HookEvent::RoomExpiring {
+            room_info: (&room_info).into(),
+            expires_time,
+        };
+        self.notifier.notify(room_id, event);
+        Ok(())
+    }
+
+    async fn remove_room(
+        &self,
+        room_info: RoomInfo,
+        sweep: &mut RoomSweep,
+    ) -> Result<(), ServiceError> {
+        let room_id = room_info.room_id.as_str();
+        self.room_repo.delete_room(room_id).await?;
+        self.membership_repo.delete_room(room_id)?;
+        sweep.removed.push(room_info);
+        Ok(())
+    }
+}
+
+// 一部のルームで失敗しても、他のルームの処理は続ける
+#[derive(Debug, Default)]
+pub struct RoomSweep {
+    pub removed: Vec<RoomInfo>,
+    // 処理に失敗したルームのIDとエラー
+    pub failed: Vec<(String, ServiceError)>,
+}
+
+// 接続中のルーム、保持するルーム、アーカイブしたルーム、期限のないルームは削除しない
+fn idle_deadline(room_info: &RoomInfo, default_ttl: Option<Duration>) -> Option<DateTime<Utc>> {
+    if room_info.keep_forever
+        || room_info.is_archived()
+        || room_info.occupancy + room_info.spectators > 0
+    {
+        return None;
+    }
+    let ttl = room_info
+        .idle_ttl_secs
+        .map(|secs| Duration::seconds(secs as i64))
+        .or(default_ttl)?;
+    let idle_since = room_info.last_activity_time.max(room_info.last_seen_time);
+    Some(idle_since + ttl)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        domain::entity::{create_room::CreateRoom, room_role::RoomRole},
+        infrastructure::repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            room_repository_impl::{test_room_info, test_user, RoomRepositoryImpl},
+        },
+        MembershipDb, RoomDb,
+    };
+
+    use super::*;
+
+    // 削除が近づいたことを通知した回数を数える
+    #[derive(Default)]
+    struct CountingNotifier(AtomicUsize);
+
+    impl EventNotifier for CountingNotifier {
+        fn notify(&self, _room_id: &str, event: HookEvent) {
+            if let HookEvent::RoomExpiring { .. } = event {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    type Services =
+        RoomExpiryServices<RoomRepositoryImpl, MembershipRepositoryImpl, CountingNotifier>;
+
+    const EXPIRY: IdleExpiry = IdleExpiry {
+        default_ttl: None,
+        warning: Duration::minutes(10),
+    };
+
+    // 1時間で期限を迎えるルームを作成する
+    async fn open_room(room_repo: &RoomRepositoryImpl) -> RoomInfo {
+        let payload = CreateRoom {
+            room_name: "room".to_string(),
+            idle_ttl_secs: Some(3600),
+            ..Default::default()
+        };
+        room_repo
+            .open_new_room(&payload, &test_user("owner"), None)
+            .await
+            .unwrap()
+    }
+
+    // ルームを1つ作成し、最後に操作があった時刻を返す
+    async fn set_up() -> (Services, String, DateTime<Utc>) {
+        let room_repo = RoomRepositoryImpl::new(RoomDb::new());
+        let room_info = open_room(&room_repo).await;
+        let idle_since = room_info.last_activity_time.max(room_info.last_seen_time);
+        let services = RoomExpiryServices::new(
+            room_repo,
+            MembershipRepositoryImpl::new(MembershipDb::new()),
+            CountingNotifier::default(),
+        );
+        (services, room_info.room_id, idle_since)
+    }
+
+    fn warnings(services: &Services) -> usize {
+        services.notifier.0.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn test_idle_deadline() {
+        let mut room_info = test_room_info("room_id");
+        let idle_since = room_info.last_activity_time.max(room_info.last_seen_time);
+        let default_ttl = Some(Duration::days(1));
+
+        // テスト対象
+        // ルームごとの期限がなければサーバーの設定に従う
+        assert_eq!(idle_deadline(&room_info, None), None);
+        assert_eq!(
+            idle_deadline(&room_info, default_ttl),
+            Some(idle_since + Duration::days(1))
+        );
+        room_info.idle_ttl_secs = Some(60);
+        assert_eq!(
+            idle_deadline(&room_info, default_ttl),
+            Some(idle_since + Duration::seconds(60))
+        );
+
+        // 最後の接続・切断と投稿の遅い方から数える
+        room_info.last_seen_time = idle_since + Duration::hours(1);
+        assert_eq!(
+            idle_deadline(&room_info, default_ttl),
+            Some(idle_since + Duration::hours(1) + Duration::seconds(60))
+        );
+
+        // 接続中のルーム、保持するルーム、アーカイブしたルームは削除しない
+        for update in [
+            |room_info: &mut RoomInfo| room_info.spectators = 1,
+            |room_info: &mut RoomInfo| room_info.keep_forever = true,
+            |room_info: &mut RoomInfo| room_info.archived_time = Some(Utc::now()),
+        ] {
+            let mut room_info = room_info.clone();
+            update(&mut room_info);
+            assert_eq!(idle_deadline(&room_info, default_ttl), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reap_idle_rooms() {
+        let (services, room_id, idle_since) = set_up().await;
+        let deadline = idle_since + Duration::hours(1);
+
+        // テスト対象
+        // 通知する前は何もしない
+        let sweep = services
+            .reap_idle_rooms(EXPIRY, deadline - Duration::minutes(20))
+            .await
+            .unwrap();
+        assert!(sweep.removed.is_empty());
+        assert_eq!(warnings(&services), 0);
+
+        // 期限の10分前から一度だけ通知する
+        for minutes in [10, 5] {
+            let sweep = services
+                .reap_idle_rooms(EXPIRY, deadline - Duration::minutes(minutes))
+                .await
+                .unwrap();
+            assert!(sweep.removed.is_empty());
+        }
+        assert_eq!(warnings(&services), 1);
+        let room_info = services.room_repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.expires_time, Some(deadline));
+
+        // 通知した削除予定時刻を過ぎると削除する
+        let sweep = services.reap_idle_rooms(EXPIRY, deadline).await.unwrap();
+        assert_eq!(sweep.removed.len(), 1);
+        assert!(sweep.failed.is_empty());
+        assert!(services.room_repo.get_room_info(&room_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reap_idle_rooms_without_warning() {
+        let (services, room_id, idle_since) = set_up().await;
+        let now = idle_since + Duration::hours(2);
+
+        // テスト対象
+        // 通知しないまま期限を過ぎたルームは、通知してから猶予を与える
+        let sweep = services.reap_idle_rooms(EXPIRY, now).await.unwrap();
+        assert!(sweep.removed.is_empty());
+        assert_eq!(warnings(&services), 1);
+        let room_info = services.room_repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.expires_time, Some(now + EXPIRY.warning));
+
+        let sweep = services
+            .reap_idle_rooms(EXPIRY, now + EXPIRY.warning)
+            .await
+            .unwrap();
+        assert_eq!(sweep.removed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_deleted_rooms() {
+        let (services, room_id, _) = set_up().await;
+        let other_room_id = open_room(&services.room_repo).await.room_id;
+        services
+            .membership_repo
+            .add_member(&room_id, &test_user("owner"), RoomRole::Owner)
+            .unwrap();
+        let room_info = services.room_repo.soft_delete_room(&room_id).await.unwrap();
+        let deleted_time = room_info.deleted_time.unwrap();
+        let grace = Duration::days(7);
+
+        // テスト対象
+        // 猶予期間中は復元できるように残す
+        let sweep = services
+            .purge_deleted_rooms(grace, deleted_time + grace - Duration::seconds(1))
+            .await
+            .unwrap();
+        assert!(sweep.removed.is_empty());
+        assert_eq!(
+            services.room_repo.get_deleted_rooms().await.unwrap().len(),
+            1
+        );
+
+        // 猶予期間が過ぎるとメンバーも含めて削除する
+        let sweep = services
+            .purge_deleted_rooms(grace, deleted_time + grace)
+            .await
+            .unwrap();
+        assert_eq!(sweep.removed.len(), 1);
+        assert_eq!(sweep.removed[0].room_id, room_id);
+        assert!(sweep.failed.is_empty());
+        assert!(services
+            .room_repo
+            .get_deleted_rooms()
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!services
+            .membership_repo
+            .is_member(&room_id, "owner_id")
+            .unwrap());
+
+        // 削除していないルームは対象外
+        assert!(services
+            .room_repo
+            .get_room_info(&other_room_id)
+            .await
+            .is_ok());
+    }
+}
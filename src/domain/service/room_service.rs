@@ -1,73 +1,1085 @@
-use tokio::sync::broadcast::Sender;
+use std::collections::HashMap;
+
+use chrono::Utc;
 
 use crate::domain::{
     entity::{
-        claims::Claims, create_room::CreateRoom, pub_user_info::PubUserInfo, room_info::RoomInfo,
+        audit_entry::AuditAction,
+        claims::Claims,
+        create_room::CreateRoom,
+        join_room::JoinRoom,
+        membership::Membership,
+        pub_user_info::PubUserInfo,
+        retention_policy::RetentionLimits,
+        room_cursor::RoomCursor,
+        room_info::RoomInfo,
+        room_page::RoomPage,
+        room_query::{NameMatch, RoomQuery, RoomSort, SortOrder},
+        room_role::RoomRole,
+        room_tag::{normalize_tag, RoomTagCount},
+        update_room::UpdateRoom,
+    },
+    repository::{
+        membership_repository::MembershipRepository, message_repository::MessageRepository,
+        room_repository::RoomRepository,
     },
-    repository::room_repository::RoomRepository,
 };
 
-use super::error::ServiceError;
+use super::{
+    audit_service::record_audit,
+    error::ServiceError,
+    membership_service::{can_access, ensure_not_banned, notify_member_joined},
+    util::{event_notifier::EventNotifier, password_hash_service::PasswordHashService},
+};
+
+// ルーム一覧の1ページの件数
+const DEFAULT_ROOM_PAGE_SIZE: usize = 50;
+const MAX_ROOM_PAGE_SIZE: usize = 100;
 
-pub struct RoomServices<R>
+pub struct RoomServices<R, B, P>
 where
     R: RoomRepository,
+    B: MembershipRepository,
+    P: PasswordHashService + Clone + Send + 'static,
 {
     repo: R,
+    membership_repo: B,
+    password_hasher: P,
 }
 
-impl<R> RoomServices<R>
+impl<R, B, P> RoomServices<R, B, P>
 where
     R: RoomRepository,
+    B: MembershipRepository,
+    P: PasswordHashService + Clone + Send + 'static,
 {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+    pub fn new(repo: R, membership_repo: B, password_hasher: P) -> Self {
+        Self {
+            repo,
+            membership_repo,
+            password_hasher,
+        }
     }
 
-    pub fn create_room(
+    // 保持期間を省略した場合はサーバーのデフォルトを使い、上限を超える場合は受け付けない
+    pub async fn create_room(
         &self,
-        payload: CreateRoom,
+        mut payload: CreateRoom,
         user_info: PubUserInfo,
+        retention_limits: RetentionLimits,
+    ) -> Result<RoomInfo, ServiceError> {
+        let retention = payload.retention.unwrap_or(retention_limits.default);
+        if !retention_limits.allows(&retention) {
+            return Err(ServiceError::Validation);
+        }
+        payload.retention = Some(retention);
+        let join_password_hash = match &payload.password {
+            Some(password) => {
+                let password_hasher = self.password_hasher.clone();
+                let password = password.to_owned();
+                Some(run_blocking(move || password_hasher.to_hash_pwd(&password)).await?)
+            }
+            None => None,
+        };
+        let room_info = self
+            .repo
+            .open_new_room(&payload, &user_info, join_password_hash.as_deref())
+            .await?;
+        self.membership_repo
+            .add_member(&room_info.room_id, &user_info, RoomRole::Owner)?;
+
+        Ok(room_info)
+    }
+
+    // 非公開ルームはメンバー以外には存在しないものとして扱う
+    pub async fn get_target_room_info(
+        &self,
+        room_id: &str,
+        user_id: &str,
     ) -> Result<RoomInfo, ServiceError> {
-        let room_info = self.repo.open_new_room(&payload.room_name, &user_info)?;
+        let room_info = self.repo.get_room_info(room_id).await?;
+        if !can_access(&self.membership_repo, &room_info, user_id)? {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
 
+    // パスワード付きのルームは、オーナーとパスワードで参加済みのメンバーのみ接続できる
+    // BANされているユーザーは接続できない
+    pub async fn get_joinable_room_info(
+        &self,
+        room_id: &str,
+        user_id: &str,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.get_target_room_info(room_id, user_id).await?;
+        ensure_not_banned(&self.membership_repo, room_id, user_id)?;
+        if room_info.join_password_hash.is_some()
+            && room_info.created_by_id != user_id
+            && !self.membership_repo.is_member(room_id, user_id)?
+        {
+            return Err(ServiceError::Forbidden);
+        }
+        // 接続時にも改めて判定するため、ここで通っても満員で切断されることがある
+        if self.repo.admission(room_id, user_id).await?.is_none() {
+            return Err(ServiceError::RoomFull);
+        }
         Ok(room_info)
     }
 
-    pub fn get_target_room_info(&self, room_id: &str) -> Result<RoomInfo, ServiceError> {
-        let room = self.repo.listen_room(room_id)?;
-        Ok(room.room_info.to_owned())
+    // パスワードが一致すればメンバーとして登録し、以降はパスワードなしで参加できる
+    // パスワードのないルームではパスワードを送らずに参加できる
+    // 総当たりを防ぐため、パスワード付きのルームへの試行はユーザーごとに回数を制限する
+    // 新しくメンバーになった場合のみ送信WebhookにmemberJoinedを送る
+    pub async fn join_room<N>(
+        &self,
+        room_id: &str,
+        payload: JoinRoom,
+        user_info: PubUserInfo,
+        notifier: &N,
+    ) -> Result<Membership, ServiceError>
+    where
+        N: EventNotifier,
+    {
+        let room_info = self
+            .get_target_room_info(room_id, &user_info.user_id)
+            .await?;
+        ensure_not_banned(&self.membership_repo, room_id, &user_info.user_id)?;
+        if let Some(join_password_hash) = room_info.join_password_hash {
+            if self
+                .membership_repo
+                .is_member(room_id, &user_info.user_id)?
+            {
+                return Ok(self
+                    .membership_repo
+                    .get_member(room_id, &user_info.user_id)?);
+            }
+            let password = payload.password.ok_or(ServiceError::Forbidden)?;
+            if self
+                .repo
+                .acquire_join_attempt(room_id, &user_info.user_id, Utc::now())
+                .await?
+                .is_some()
+            {
+                return Err(ServiceError::RateLimited);
+            }
+            let password_hasher = self.password_hasher.clone();
+            let verified =
+                run_blocking(move || password_hasher.verify_pwd(&password, &join_password_hash))
+                    .await?;
+            if !verified {
+                return Err(ServiceError::Forbidden);
+            }
+        }
+        let since = Utc::now();
+        let membership = self
+            .membership_repo
+            .add_member(room_id, &user_info, RoomRole::Member)?;
+        notify_member_joined(notifier, &membership, since);
+        Ok(membership)
+    }
+
+    pub async fn get_room_by_join_code(
+        &self,
+        join_code: &str,
+        user_id: &str,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.repo.get_room_by_join_code(join_code).await?;
+        if !can_access(&self.membership_repo, &room_info, user_id)? {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
     }
 
-    pub fn get_owner_room_info(&self, claims: Claims) -> Result<Vec<RoomInfo>, ServiceError> {
+    pub async fn get_owner_room_info(&self, claims: Claims) -> Result<Vec<RoomInfo>, ServiceError> {
         let room_owner_id = &claims.user_id;
-        let rooms = self.repo.get_owner_rooms(room_owner_id)?;
+        let rooms = self.repo.get_owner_rooms(room_owner_id).await?;
         Ok(rooms)
     }
 
-    pub fn get_all_room_info(&self) -> Result<Vec<RoomInfo>, ServiceError> {
-        let rooms = self.repo.get_all_room()?;
+    // 接続も投稿もないため削除が予定されているルーム。接続や投稿、設定の変更で取り消せる
+    pub async fn get_expiring_owner_rooms(
+        &self,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<RoomInfo>, ServiceError> {
+        let mut rooms: Vec<RoomInfo> = self
+            .repo
+            .get_owner_rooms(&user_info.user_id)
+            .await?
+            .into_iter()
+            .filter(|room_info| room_info.expires_time.is_some())
+            .collect();
+        rooms.sort_by_key(|room_info| room_info.expires_time);
         Ok(rooms)
     }
 
-    pub fn get_sender(&self, room_id: &str) -> Result<Sender<String>, ServiceError> {
-        let room = self.repo.listen_room(room_id)?;
-        Ok(room.sender)
+    // 絞り込んで並び替えたルームのうち、カーソルより後のルームを1ページ分返す
+    // アクティビティ順では、1ページ目を返した後に投稿があったルームもその時点の位置で並べる
+    // そのため、ページの間に投稿があっても重複や欠落は起きない
+    pub async fn get_all_room_info<M>(
+        &self,
+        message_repo: &M,
+        user_id: &str,
+        query: RoomQuery,
+    ) -> Result<RoomPage, ServiceError>
+    where
+        M: MessageRepository,
+    {
+        let limit = query.limit.unwrap_or(DEFAULT_ROOM_PAGE_SIZE);
+        if limit == 0 || limit > MAX_ROOM_PAGE_SIZE {
+            return Err(ServiceError::Validation);
+        }
+        let sort = query.sort;
+        let order = query.order();
+        let (after, as_of) = match &query.cursor {
+            Some(cursor) => {
+                let (after, as_of) =
+                    RoomCursor::decode(cursor, sort, order).ok_or(ServiceError::Validation)?;
+                (Some(after), as_of)
+            }
+            None => (None, Utc::now()),
+        };
+
+        let rooms = self.repo.get_all_room().await?;
+        let mut visible_rooms = Vec::with_capacity(rooms.len());
+        for room_info in rooms {
+            if !matches_query(&room_info, &query)
+                || !can_access(&self.membership_repo, &room_info, user_id)?
+            {
+                continue;
+            }
+            let cursor = match sort {
+                RoomSort::Activity if room_info.last_activity_time > as_of => {
+                    let last_activity_time = message_repo
+                        .last_message_time(&room_info.room_id, as_of)?
+                        .unwrap_or(room_info.created_time);
+                    RoomCursor::from_activity(&room_info, last_activity_time)
+                }
+                _ => RoomCursor::from_room(&room_info, sort),
+            };
+            visible_rooms.push((cursor, room_info));
+        }
+        visible_rooms.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if order == SortOrder::Desc {
+            visible_rooms.reverse();
+        }
+
+        let mut page: Vec<(RoomCursor, RoomInfo)> = visible_rooms
+            .into_iter()
+            .filter(|(cursor, _)| {
+                after.as_ref().is_none_or(|after| match order {
+                    SortOrder::Asc => cursor > after,
+                    SortOrder::Desc => cursor < after,
+                })
+            })
+            .take(limit + 1)
+            .collect();
+        let next_cursor = if page.len() > limit {
+            page.truncate(limit);
+            page.last()
+                .map(|(cursor, _)| cursor.encode(sort, order, as_of))
+        } else {
+            None
+        };
+        Ok(RoomPage {
+            rooms: page.into_iter().map(|(_, room_info)| room_info).collect(),
+            next_cursor,
+        })
+    }
+
+    // 閲覧できるルームに付いているタグを、付いているルームが多い順に返す
+    pub async fn get_room_tags(&self, user_id: &str) -> Result<Vec<RoomTagCount>, ServiceError> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for room_info in self.repo.get_all_room().await? {
+            if can_access(&self.membership_repo, &room_info, user_id)? {
+                for tag in room_info.tags {
+                    *counts.entry(tag).or_default() += 1;
+                }
+            }
+        }
+        let mut tags: Vec<RoomTagCount> = counts
+            .into_iter()
+            .map(|(tag, count)| RoomTagCount { tag, count })
+            .collect();
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(tags)
     }
 
-    pub fn delete_owner_room(
+    // 変更は接続中のクライアントにroomUpdatedイベントとして通知される
+    pub async fn update_owner_room(
         &self,
         room_id: &str,
+        payload: UpdateRoom,
         user_info: PubUserInfo,
-    ) -> Result<(), ServiceError> {
-        let room = self.repo.listen_room(room_id).map_err(|e| e)?;
-        let room_owner = room.room_info.created_by_id;
+        retention_limits: RetentionLimits,
+    ) -> Result<RoomInfo, ServiceError> {
+        if payload.is_empty() {
+            return Err(ServiceError::Validation);
+        }
+        if payload
+            .retention
+            .is_some_and(|retention| !retention_limits.allows(&retention))
+        {
+            return Err(ServiceError::Validation);
+        }
+        let previous = self.get_owner_room(room_id, &user_info).await?;
+        let setting_names = payload.setting_names();
+        let room_info = self.repo.update_room(room_id, payload).await?;
 
-        if room_owner == user_info.user_id {
-            self.repo.delete_room(room_id)?;
-        } else {
+        if room_info.room_name != previous.room_name {
+            let detail = format!("{} -> {}", previous.room_name, room_info.room_name);
+            record_audit(
+                &self.membership_repo,
+                room_id,
+                AuditAction::RoomRenamed,
+                &user_info,
+                None,
+                Some(detail),
+            )?;
+        }
+        if !setting_names.is_empty() {
+            record_audit(
+                &self.membership_repo,
+                room_id,
+                AuditAction::RoomUpdated,
+                &user_info,
+                None,
+                Some(setting_names.join(", ")),
+            )?;
+        }
+        Ok(room_info)
+    }
+
+    // 投稿を受け付けなくなる。履歴は引き続き閲覧できる
+    pub async fn archive_owner_room(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let room_info = self.repo.archive_room(room_id).await?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::RoomArchived,
+            &user_info,
+            None,
+            None,
+        )?;
+        Ok(room_info)
+    }
+
+    pub async fn unarchive_owner_room(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let room_info = self.repo.unarchive_room(room_id).await?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::RoomUnarchived,
+            &user_info,
+            None,
+            None,
+        )?;
+        Ok(room_info)
+    }
+
+    // 猶予期間が過ぎるまではメッセージやメンバーを残し、restore_owner_roomで復元できる
+    // 送信WebhookのroomDeletedは復元できなくなる猶予期間の終わりまで送らない
+    pub async fn delete_owner_room(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        self.get_owner_room(room_id, &user_info).await?;
+        let room_info = self.repo.soft_delete_room(room_id).await?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::RoomDeleted,
+            &user_info,
+            None,
+            None,
+        )?;
+        Ok(room_info)
+    }
+
+    pub async fn get_deleted_owner_rooms(
+        &self,
+        user_info: PubUserInfo,
+    ) -> Result<Vec<RoomInfo>, ServiceError> {
+        let rooms = self
+            .repo
+            .get_deleted_rooms()
+            .await?
+            .into_iter()
+            .filter(|room_info| room_info.created_by_id == user_info.user_id)
+            .collect();
+        Ok(rooms)
+    }
+
+    pub async fn restore_owner_room(
+        &self,
+        room_id: &str,
+        user_info: PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let deleted = self
+            .get_deleted_owner_rooms(user_info.clone())
+            .await?
+            .into_iter()
+            .any(|room_info| room_info.room_id == room_id);
+        if !deleted {
             return Err(ServiceError::NotFound);
         }
-        Ok(())
+        let room_info = self.repo.restore_room(room_id).await?;
+        record_audit(
+            &self.membership_repo,
+            room_id,
+            AuditAction::RoomRestored,
+            &user_info,
+            None,
+            None,
+        )?;
+        Ok(room_info)
+    }
+
+    // ルームが存在しない場合とオーナーでない場合を区別しない
+    async fn get_owner_room(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+    ) -> Result<RoomInfo, ServiceError> {
+        let room_info = self.repo.get_room_info(room_id).await?;
+        if room_info.created_by_id != user_info.user_id {
+            return Err(ServiceError::NotFound);
+        }
+        Ok(room_info)
+    }
+}
+
+// Argon2は計算に時間がかかるため、非同期のワーカースレッドを塞がないよう別スレッドで実行する
+pub async fn run_blocking<T, F>(f: F) -> Result<T, ServiceError>
+where
+    F: FnOnce() -> Result<T, ServiceError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|_| ServiceError::Server)?
+}
+
+fn matches_query(room_info: &RoomInfo, query: &RoomQuery) -> bool {
+    if query
+        .owner_id
+        .as_ref()
+        .is_some_and(|owner_id| *owner_id != room_info.created_by_id)
+    {
+        return false;
+    }
+    if query
+        .tag
+        .as_deref()
+        .is_some_and(|tag| !room_info.tags.contains(&normalize_tag(tag)))
+    {
+        return false;
+    }
+    if query.category.as_deref().is_some_and(|category| {
+        room_info
+            .category
+            .as_deref()
+            .is_none_or(|room_category| *room_category != normalize_tag(category))
+    }) {
+        return false;
+    }
+    let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) else {
+        return true;
+    };
+    let room_name = room_info.room_name.to_lowercase();
+    let q = q.to_lowercase();
+    match query.name_match {
+        NameMatch::Contains => room_name.contains(&q),
+        NameMatch::Prefix => room_name.starts_with(&q),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+
+    use crate::{
+        domain::{
+            entity::{
+                chat::Chat,
+                hook_event::HookEventKind,
+                room_event::RoomEvent,
+                sanction::{Sanction, SanctionKind},
+                visibility::Visibility,
+            },
+            repository::error::RepositoryError,
+            service::util::event_notifier::RecordingNotifier,
+        },
+        infrastructure::{
+            repository::{
+                membership_repository_impl::MembershipRepositoryImpl,
+                message_repository_impl::MessageRepositoryImpl,
+                room_repository_impl::{test_user, RoomRepositoryImpl},
+            },
+            service::password_hash_service_impl::PasswordHashServiceImpl,
+        },
+        MembershipDb, MessageDb, RoomDb,
+    };
+
+    use super::*;
+
+    type Services =
+        RoomServices<RoomRepositoryImpl, MembershipRepositoryImpl, PasswordHashServiceImpl>;
+
+    fn join_with(password: Option<&str>) -> JoinRoom {
+        JoinRoom {
+            password: password.map(str::to_string),
+        }
+    }
+
+    fn new_services() -> (Services, MembershipDb) {
+        let membership_db = MembershipDb::new();
+        let services = RoomServices::new(
+            RoomRepositoryImpl::new(RoomDb::new()),
+            MembershipRepositoryImpl::new(membership_db.clone()),
+            PasswordHashServiceImpl,
+        );
+        (services, membership_db)
+    }
+
+    // ownerが公開ルームを作成する
+    async fn create(services: &Services, room_name: &str, password: Option<&str>) -> String {
+        let payload = CreateRoom {
+            room_name: room_name.to_string(),
+            password: password.map(str::to_string),
+            ..Default::default()
+        };
+        services
+            .create_room(payload, test_user("owner"), RetentionLimits::default())
+            .await
+            .unwrap()
+            .room_id
+    }
+
+    async fn set_up(password: Option<&str>) -> (Services, MembershipDb, String) {
+        let (services, membership_db) = new_services();
+        let room_id = create(&services, "room", password).await;
+        (services, membership_db, room_id)
+    }
+
+    // メッセージを保存してルームに流し、ルームの最後の投稿の時刻を進める
+    async fn post(services: &Services, message_repo: &MessageRepositoryImpl, room_id: &str) {
+        let chat = Chat::from_str("owner_id", "owner", "hello");
+        message_repo.save_message(room_id, &chat).unwrap();
+        services
+            .repo
+            .publish(room_id, RoomEvent::Chat(chat))
+            .await
+            .unwrap();
+    }
+
+    // カーソルをたどって全てのページのルーム名を集める
+    async fn collect_pages(
+        services: &Services,
+        message_repo: &MessageRepositoryImpl,
+        query: RoomQuery,
+    ) -> Vec<Vec<String>> {
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = services
+                .get_all_room_info(
+                    message_repo,
+                    "user_id",
+                    RoomQuery {
+                        cursor: cursor.take(),
+                        ..query.clone()
+                    },
+                )
+                .await
+                .unwrap();
+            pages.push(page.rooms.into_iter().map(|room| room.room_name).collect());
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => return pages,
+            }
+        }
+    }
+
+    fn sorted(sort: RoomSort, order: Option<SortOrder>) -> RoomQuery {
+        RoomQuery {
+            sort,
+            order,
+            limit: Some(2),
+            ..RoomQuery::default()
+        }
+    }
+
+    // タグとカテゴリを設定したルームを作成する
+    async fn create_tagged(
+        services: &Services,
+        room_name: &str,
+        tags: &[&str],
+        category: Option<&str>,
+    ) -> String {
+        let room_id = create(services, room_name, None).await;
+        let payload = UpdateRoom {
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            category: category.map(str::to_string),
+            ..Default::default()
+        };
+        services.repo.update_room(&room_id, payload).await.unwrap();
+        room_id
+    }
+
+    #[tokio::test]
+    async fn test_join_room_without_password() {
+        let (services, _, room_id) = set_up(None).await;
+        let notifier = RecordingNotifier::default();
+
+        // テスト対象
+        let membership = services
+            .join_room(
+                &room_id,
+                JoinRoom::default(),
+                test_user("member"),
+                &notifier,
+            )
+            .await
+            .unwrap();
+        assert_eq!(membership.user_id, "member_id");
+        assert_eq!(membership.role, RoomRole::Member);
+        // 既にメンバーの場合はmemberJoinedを送らない
+        services
+            .join_room(
+                &room_id,
+                JoinRoom::default(),
+                test_user("member"),
+                &notifier,
+            )
+            .await
+            .unwrap();
+        assert_eq!(notifier.kinds(), vec![HookEventKind::MemberJoined]);
+    }
+
+    #[tokio::test]
+    async fn test_join_room_with_password() {
+        let (services, _, room_id) = set_up(Some("secret")).await;
+        let notifier = RecordingNotifier::default();
+        let result = services.get_joinable_room_info(&room_id, "member_id").await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+
+        // テスト対象
+        let result = services
+            .join_room(
+                &room_id,
+                JoinRoom::default(),
+                test_user("member"),
+                &notifier,
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        let result = services
+            .join_room(
+                &room_id,
+                join_with(Some("wrong")),
+                test_user("member"),
+                &notifier,
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Forbidden)));
+        let membership = services
+            .join_room(
+                &room_id,
+                join_with(Some("secret")),
+                test_user("member"),
+                &notifier,
+            )
+            .await
+            .unwrap();
+        assert_eq!(membership.role, RoomRole::Member);
+
+        // 参加後はパスワードなしで接続でき、再度の参加もパスワードを求めない
+        services
+            .get_joinable_room_info(&room_id, "member_id")
+            .await
+            .unwrap();
+        services
+            .join_room(
+                &room_id,
+                JoinRoom::default(),
+                test_user("member"),
+                &notifier,
+            )
+            .await
+            .unwrap();
+        assert_eq!(notifier.kinds(), vec![HookEventKind::MemberJoined]);
+    }
+
+    #[tokio::test]
+    async fn test_join_room_rate_limit() {
+        let (services, _, room_id) = set_up(Some("secret")).await;
+        for _ in 0..5 {
+            let result = services
+                .join_room(
+                    &room_id,
+                    join_with(Some("wrong")),
+                    test_user("member"),
+                    &RecordingNotifier::default(),
+                )
+                .await;
+            assert!(matches!(result, Err(ServiceError::Forbidden)));
+        }
+
+        // テスト対象
+        // 上限に達した後は正しいパスワードでも照合しない
+        let result = services
+            .join_room(
+                &room_id,
+                join_with(Some("secret")),
+                test_user("member"),
+                &RecordingNotifier::default(),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::RateLimited)));
+        // 他のユーザーは影響を受けない
+        services
+            .join_room(
+                &room_id,
+                join_with(Some("secret")),
+                test_user("other"),
+                &RecordingNotifier::default(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_joinable_room_info() {
+        let (services, membership_db, room_id) = set_up(Some("secret")).await;
+
+        // テスト対象
+        // オーナーはパスワードなしで接続できる
+        services
+            .get_joinable_room_info(&room_id, "owner_id")
+            .await
+            .unwrap();
+
+        // BANされたユーザーはパスワードで参加することも接続することもできない
+        services
+            .join_room(
+                &room_id,
+                join_with(Some("secret")),
+                test_user("member"),
+                &RecordingNotifier::default(),
+            )
+            .await
+            .unwrap();
+        MembershipRepositoryImpl::new(membership_db)
+            .add_sanction(&Sanction {
+                room_id: room_id.clone(),
+                user_id: "member_id".to_string(),
+                kind: SanctionKind::Ban,
+                reason: None,
+                issued_by_id: "owner_id".to_string(),
+                created_time: Utc::now(),
+                expires_time: None,
+            })
+            .unwrap();
+        let result = services.get_joinable_room_info(&room_id, "member_id").await;
+        assert!(matches!(result, Err(ServiceError::Banned)));
+        let result = services
+            .join_room(
+                &room_id,
+                join_with(Some("secret")),
+                test_user("member"),
+                &RecordingNotifier::default(),
+            )
+            .await;
+        assert!(matches!(result, Err(ServiceError::Banned)));
+    }
+
+    #[tokio::test]
+    async fn test_get_all_room_info_sort() {
+        let (services, _) = new_services();
+        let message_repo = MessageRepositoryImpl::new(MessageDb::new());
+        for room_name in ["Charlie", "alpha", "Bravo"] {
+            create(&services, room_name, None).await;
+        }
+        // アクティビティ順はBravo(投稿なし), alpha, Charlieの順に古い
+        let rooms = services.repo.get_all_room().await.unwrap();
+        for room_name in ["alpha", "Charlie"] {
+            let room_info = rooms.iter().find(|room| room.room_name == room_name);
+            post(&services, &message_repo, &room_info.unwrap().room_id).await;
+        }
+
+        // テスト対象
+        let cases = [
+            (RoomSort::Created, None, ["Bravo", "alpha", "Charlie"]),
+            (
+                RoomSort::Created,
+                Some(SortOrder::Asc),
+                ["Charlie", "alpha", "Bravo"],
+            ),
+            (RoomSort::Name, None, ["alpha", "Bravo", "Charlie"]),
+            (
+                RoomSort::Name,
+                Some(SortOrder::Desc),
+                ["Charlie", "Bravo", "alpha"],
+            ),
+            (RoomSort::Activity, None, ["Charlie", "alpha", "Bravo"]),
+            (
+                RoomSort::Activity,
+                Some(SortOrder::Asc),
+                ["Bravo", "alpha", "Charlie"],
+            ),
+        ];
+        for (sort, order, expected) in cases {
+            let pages = collect_pages(&services, &message_repo, sorted(sort, order)).await;
+            assert_eq!(
+                pages,
+                vec![vec![expected[0], expected[1]], vec![expected[2]]],
+                "{:?} {:?}",
+                sort,
+                order
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_all_room_info_cursor_mismatch() {
+        let (services, _) = new_services();
+        let message_repo = MessageRepositoryImpl::new(MessageDb::new());
+        for room_name in ["a", "b", "c"] {
+            create(&services, room_name, None).await;
+        }
+        let page = services
+            .get_all_room_info(&message_repo, "user_id", sorted(RoomSort::Name, None))
+            .await
+            .unwrap();
+
+        // テスト対象
+        // 別の並び順のカーソルは受け付けない
+        let query = RoomQuery {
+            cursor: page.next_cursor,
+            ..sorted(RoomSort::Created, None)
+        };
+        let result = services
+            .get_all_room_info(&message_repo, "user_id", query)
+            .await;
+        assert!(matches!(result, Err(ServiceError::Validation)));
+    }
+
+    #[tokio::test]
+    async fn test_get_all_room_info_name_match() {
+        let (services, _) = new_services();
+        let message_repo = MessageRepositoryImpl::new(MessageDb::new());
+        for room_name in ["Charlie", "alpha", "Bravo"] {
+            create(&services, room_name, None).await;
+        }
+        let search = |q: &str, name_match: NameMatch| RoomQuery {
+            q: Some(q.to_string()),
+            name_match,
+            sort: RoomSort::Name,
+            ..RoomQuery::default()
+        };
+
+        // テスト対象
+        // 大文字・小文字を区別しない
+        let pages =
+            collect_pages(&services, &message_repo, search("AL", NameMatch::Contains)).await;
+        assert_eq!(pages, vec![vec!["alpha"]]);
+        let pages = collect_pages(&services, &message_repo, search("a", NameMatch::Contains)).await;
+        assert_eq!(pages, vec![vec!["alpha", "Bravo", "Charlie"]]);
+        let pages = collect_pages(&services, &message_repo, search("a", NameMatch::Prefix)).await;
+        assert_eq!(pages, vec![vec!["alpha"]]);
+        let pages = collect_pages(&services, &message_repo, search("b", NameMatch::Prefix)).await;
+        assert_eq!(pages, vec![vec!["Bravo"]]);
+        // 空白のみの検索語は絞り込まない
+        let pages = collect_pages(&services, &message_repo, search("  ", NameMatch::Prefix)).await;
+        assert_eq!(pages, vec![vec!["alpha", "Bravo", "Charlie"]]);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_room_info_activity_cursor_is_stable() {
+        let (services, _) = new_services();
+        let message_repo = MessageRepositoryImpl::new(MessageDb::new());
+        let mut room_ids = Vec::new();
+        for room_name in ["a", "b", "c", "d"] {
+            let room_id = create(&services, room_name, None).await;
+            post(&services, &message_repo, &room_id).await;
+            room_ids.push(room_id);
+        }
+        let query = sorted(RoomSort::Activity, None);
+        let first = services
+            .get_all_room_info(&message_repo, "user_id", query.clone())
+            .await
+            .unwrap();
+        let names: Vec<&str> = first
+            .rooms
+            .iter()
+            .map(|room| room.room_name.as_str())
+            .collect();
+        assert_eq!(names, ["d", "c"]);
+
+        // テスト対象
+        // 次のページのbと、返し済みのdに投稿があっても並び順は1ページ目の時点のまま
+        post(&services, &message_repo, &room_ids[1]).await;
+        post(&services, &message_repo, &room_ids[3]).await;
+        let second = services
+            .get_all_room_info(
+                &message_repo,
+                "user_id",
+                RoomQuery {
+                    cursor: first.next_cursor,
+                    ..query.clone()
+                },
+            )
+            .await
+            .unwrap();
+        let names: Vec<&str> = second
+            .rooms
+            .iter()
+            .map(|room| room.room_name.as_str())
+            .collect();
+        assert_eq!(names, ["b", "a"]);
+        assert!(second.next_cursor.is_none());
+
+        // 新しく検索し直すと投稿を反映した順になる
+        let pages = collect_pages(&services, &message_repo, query).await;
+        assert_eq!(pages, vec![vec!["d", "b"], vec!["c", "a"]]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_owner_room() {
+        let (services, membership_db, room_id) = set_up(None).await;
+        services
+            .delete_owner_room(&room_id, test_user("owner"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            services.repo.get_room_info(&room_id).await,
+            Err(RepositoryError::NotFound)
+        ));
+
+        // テスト対象
+        // オーナー以外には削除済みのルームも存在しないものとして扱う
+        let result = services
+            .restore_owner_room(&room_id, test_user("other"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+        let deleted = services
+            .get_deleted_owner_rooms(test_user("other"))
+            .await
+            .unwrap();
+        assert!(deleted.is_empty());
+
+        let deleted = services
+            .get_deleted_owner_rooms(test_user("owner"))
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 1);
+        let room_info = services
+            .restore_owner_room(&room_id, test_user("owner"))
+            .await
+            .unwrap();
+        assert_eq!(room_info.deleted_time, None);
+        assert!(services.repo.get_room_info(&room_id).await.is_ok());
+        assert!(services
+            .get_deleted_owner_rooms(test_user("owner"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // 削除していないルームは復元できない
+        let result = services
+            .restore_owner_room(&room_id, test_user("owner"))
+            .await;
+        assert!(matches!(result, Err(ServiceError::NotFound)));
+
+        let actions: Vec<AuditAction> = MembershipRepositoryImpl::new(membership_db)
+            .get_audit_log(&room_id, None, 10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action)
+            .collect();
+        assert!(actions.contains(&AuditAction::RoomDeleted));
+        assert!(actions.contains(&AuditAction::RoomRestored));
+    }
+
+    #[tokio::test]
+    async fn test_get_room_tags() {
+        let (services, _) = new_services();
+        create_tagged(&services, "a", &["Rust", "beginner"], None).await;
+        create_tagged(&services, "b", &[" rust "], None).await;
+        create_tagged(&services, "c", &["web"], None).await;
+        // 見えない非公開ルームのタグは数えない
+        let payload = CreateRoom {
+            room_name: "private".to_string(),
+            visibility: Visibility::Private,
+            tags: vec!["rust".to_string(), "secret".to_string()],
+            ..Default::default()
+        };
+        services
+            .create_room(payload, test_user("owner"), RetentionLimits::default())
+            .await
+            .unwrap();
+
+        // テスト対象
+        let tags = services.get_room_tags("user_id").await.unwrap();
+        let tags: Vec<(&str, usize)> = tags
+            .iter()
+            .map(|tag| (tag.tag.as_str(), tag.count))
+            .collect();
+        // 多い順、同じ数ならタグ名の順
+        assert_eq!(tags, [("rust", 2), ("beginner", 1), ("web", 1)]);
+
+        // オーナーには非公開ルームのタグも数える
+        let tags = services.get_room_tags("owner_id").await.unwrap();
+        assert_eq!(
+            tags[0],
+            RoomTagCount {
+                tag: "rust".to_string(),
+                count: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_tag_and_category() {
+        let (services, _) = new_services();
+        let message_repo = MessageRepositoryImpl::new(MessageDb::new());
+        let room_id = create_tagged(&services, "a", &["Rust"], Some(" Tech ")).await;
+        create_tagged(&services, "b", &["web"], Some("tech")).await;
+        create_tagged(&services, "c", &["rust"], Some("Music")).await;
+        create_tagged(&services, "d", &[], None).await;
+
+        // カテゴリもタグと同じく正規化して保存する
+        let room_info = services.repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.tags, ["rust"]);
+        assert_eq!(room_info.category.as_deref(), Some("tech"));
+
+        // テスト対象
+        // 大文字・小文字や前後の空白を区別せず完全一致で絞り込む
+        let query = |tag: Option<&str>, category: Option<&str>| RoomQuery {
+            tag: tag.map(str::to_string),
+            category: category.map(str::to_string),
+            sort: RoomSort::Name,
+            ..RoomQuery::default()
+        };
+        let pages = collect_pages(&services, &message_repo, query(Some("RUST"), None)).await;
+        assert_eq!(pages, [["a", "c"]]);
+        let pages = collect_pages(&services, &message_repo, query(None, Some("TECH "))).await;
+        assert_eq!(pages, [["a", "b"]]);
+        let pages =
+            collect_pages(&services, &message_repo, query(Some("rust"), Some("tech"))).await;
+        assert_eq!(pages, [["a"]]);
+        let pages = collect_pages(&services, &message_repo, query(Some("ru"), None)).await;
+        assert_eq!(pages, [Vec::<String>::new()]);
     }
 }
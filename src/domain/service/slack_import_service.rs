@@ -0,0 +1,596 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use crate::domain::{
+    entity::{
+        chat::Chat,
+        create_room::CreateRoom,
+        import_report::{ImportReport, ImportedRoom, SkippedItem},
+        pub_user_info::PubUserInfo,
+        retention_policy::RetentionPolicy,
+        room_role::RoomRole,
+        slack_archive::{SlackArchive, SlackMessage, SlackUser},
+        user::User,
+    },
+    repository::{
+        membership_repository::MembershipRepository, message_repository::MessageRepository,
+        room_repository::RoomRepository, user_repository::UserRepository,
+    },
+};
+
+use super::{
+    error::ServiceError,
+    room_service::run_blocking,
+    user_service::ensure_admin,
+    util::{password_hash_service::PasswordHashService, secret_gen::SecretGen, uuid_gen::UUIDGen},
+};
+
+// 通常のメッセージとして取り込むsubtype
+// channel_joinなどのシステムメッセージは取り込まない
+const IMPORTED_SUBTYPES: [&str; 3] = ["bot_message", "thread_broadcast", "me_message"];
+const MAX_ROOM_NAME_LEN: usize = 30;
+const MAX_USER_NAME_LEN: usize = 50;
+
+pub struct SlackImportServices<U, R, M, B, P, G, I>
+where
+    U: UserRepository,
+    R: RoomRepository,
+    M: MessageRepository,
+    B: MembershipRepository,
+    P: PasswordHashService,
+    G: SecretGen,
+    I: UUIDGen,
+{
+    user_repo: U,
+    room_repo: R,
+    message_repo: M,
+    membership_repo: B,
+    password_hasher: P,
+    secret_gen: G,
+    uuid: I,
+}
+
+impl<U, R, M, B, P, G, I> SlackImportServices<U, R, M, B, P, G, I>
+where
+    U: UserRepository,
+    R: RoomRepository,
+    M: MessageRepository,
+    B: MembershipRepository,
+    P: PasswordHashService + Clone + Send + 'static,
+    G: SecretGen,
+    I: UUIDGen,
+{
+    pub fn new(
+        user_repo: U,
+        room_repo: R,
+        message_repo: M,
+        membership_repo: B,
+        password_hasher: P,
+        secret_gen: G,
+        uuid: I,
+    ) -> Self {
+        Self {
+            user_repo,
+            room_repo,
+            message_repo,
+            membership_repo,
+            password_hasher,
+            secret_gen,
+            uuid,
+        }
+    }
+
+    pub async fn ensure_admin(&self, user_id: &str) -> Result<(), ServiceError> {
+        ensure_admin(&self.user_repo, user_id).await
+    }
+
+    // チャンネルごとにルームを作成し、メッセージを元の時刻とスレッド構造のまま保存する
+    // ルームのオーナーは取り込みを実行した管理者になる
+    // 全ての内容を組み立ててから書き込み、途中で失敗した場合は作成したルームとユーザーを削除する
+    // dry_runの場合は何も書き込まずに結果だけを返す
+    // 作成したルームにはretentionの保持期間を適用する
+    pub async fn import(
+        &self,
+        archive: SlackArchive,
+        user_info: PubUserInfo,
+        retention: RetentionPolicy,
+        dry_run: bool,
+    ) -> Result<ImportReport, ServiceError> {
+        let (plan, mut report) = self.plan(archive, dry_run).await?;
+        if dry_run {
+            return Ok(report);
+        }
+
+        let mut created = Created::default();
+        if let Err(e) = self
+            .write(plan, &user_info, retention, &mut report, &mut created)
+            .await
+        {
+            self.rollback(created).await;
+            return Err(e);
+        }
+        Ok(report)
+    }
+
+    async fn plan(
+        &self,
+        archive: SlackArchive,
+        dry_run: bool,
+    ) -> Result<(ImportPlan, ImportReport), ServiceError> {
+        let mut plan = ImportPlan::default();
+        let mut report = ImportReport {
+            dry_run,
+            skipped: archive.skipped,
+            ..Default::default()
+        };
+        let slack_users: HashMap<&str, &SlackUser> = archive
+            .users
+            .iter()
+            .map(|user| (user.id.as_str(), user))
+            .collect();
+        let mut placeholders: HashMap<String, PubUserInfo> = HashMap::new();
+
+        for channel in archive.channels {
+            let room_name: String = channel.name.chars().take(MAX_ROOM_NAME_LEN).collect();
+            if room_name.is_empty() {
+                report.skipped.push(SkippedItem {
+                    location: channel.name,
+                    reason: "empty channel name".to_string(),
+                });
+                continue;
+            }
+
+            let mut messages: Vec<(DateTime<Utc>, SlackMessage)> = Vec::new();
+            for message in channel.messages {
+                let location = format!("{}/{}", channel.name, message.ts);
+                match check_message(&message) {
+                    Ok(time) => messages.push((time, message)),
+                    Err(reason) => report.skipped.push(SkippedItem {
+                        location,
+                        reason: reason.to_string(),
+                    }),
+                }
+            }
+            // 親メッセージを先に処理するため時刻順に並べる
+            messages.sort_by_key(|(time, _)| *time);
+
+            let mut message_ids: HashMap<String, String> = HashMap::new();
+            let mut chats = Vec::with_capacity(messages.len());
+            for (time, message) in messages {
+                let text = unescape_text(&message.text);
+                let mut chat = match (&message.user, &message.bot_id) {
+                    (Some(slack_user_id), _) => {
+                        let author = self
+                            .placeholder_user(
+                                slack_user_id,
+                                slack_users.get(slack_user_id.as_str()).copied(),
+                                &mut placeholders,
+                                &mut plan.users,
+                            )
+                            .await?;
+                        Chat::from_str(&author.user_id, &author.user_name, &text)
+                    }
+                    (None, Some(bot_id)) => {
+                        let name = message.username.as_deref().unwrap_or(bot_id);
+                        Chat::from_bot(bot_id, name, &text)
+                    }
+                    (None, None) => {
+                        report.skipped.push(SkippedItem {
+                            location: format!("{}/{}", channel.name, message.ts),
+                            reason: "unknown author".to_string(),
+                        });
+                        continue;
+                    }
+                };
+                chat.time = time;
+                // 親が取り込まれなかった返信はスレッド外のメッセージとして扱う
+                chat.reply_to = message
+                    .thread_ts
+                    .as_ref()
+                    .filter(|thread_ts| **thread_ts != message.ts)
+                    .and_then(|thread_ts| message_ids.get(thread_ts))
+                    .cloned();
+                message_ids.insert(message.ts, chat.message_id.clone());
+                chats.push(chat);
+            }
+
+            report.messages_imported += chats.len();
+            report.rooms.push(ImportedRoom {
+                channel_name: channel.name,
+                room_id: None,
+                message_count: chats.len(),
+            });
+            plan.rooms.push((room_name, chats));
+        }
+        report.users_created = plan.users.len();
+        Ok((plan, report))
+    }
+
+    async fn write(
+        &self,
+        plan: ImportPlan,
+        user_info: &PubUserInfo,
+        retention: RetentionPolicy,
+        report: &mut ImportReport,
+        created: &mut Created,
+    ) -> Result<(), ServiceError> {
+        for mut user in plan.users {
+            // パスワードはランダムな値にしてログインできないようにする
+            let password_hasher = self.password_hasher.clone();
+            let password = self.secret_gen.gen_secret();
+            user.user_pass = run_blocking(move || password_hasher.to_hash_pwd(&password)).await?;
+            self.user_repo.insert(&user).await?;
+            created.user_ids.push(user.user_id);
+        }
+
+        for ((room_name, chats), imported) in plan.rooms.into_iter().zip(report.rooms.iter_mut()) {
+            let payload = CreateRoom {
+                room_name,
+                retention: Some(retention),
+                ..Default::default()
+            };
+            let room_info = self
+                .room_repo
+                .open_new_room(&payload, user_info, None)
+                .await?;
+            created.room_ids.push(room_info.room_id.clone());
+            // create_roomで作成したルームと同じく、オーナーをメンバーに加える
+            self.membership_repo
+                .add_member(&room_info.room_id, user_info, RoomRole::Owner)?;
+            for chat in &chats {
+                self.message_repo.save_message(&room_info.room_id, chat)?;
+            }
+            imported.room_id = Some(room_info.room_id);
+        }
+        Ok(())
+    }
+
+    // 途中までに作成したルームとユーザーを削除して取り込み前の状態に戻す
+    async fn rollback(&self, created: Created) {
+        for room_id in &created.room_ids {
+            if let Err(e) = self.message_repo.delete_room_messages(room_id) {
+                warn!("failed to roll back imported messages: {:?}", e);
+            }
+            if let Err(e) = self.membership_repo.delete_room(room_id) {
+                warn!("failed to roll back imported memberships: {:?}", e);
+            }
+            if let Err(e) = self.room_repo.delete_room(room_id).await {
+                warn!("failed to roll back imported room: {:?}", e);
+            }
+        }
+        for user_id in &created.user_ids {
+            if let Err(e) = self.user_repo.delete(user_id).await {
+                warn!("failed to roll back imported user: {:?}", e);
+            }
+        }
+    }
+
+    // Slackのユーザーに対応するユーザーを取得する
+    // 存在しない場合は作成するプレースホルダーユーザーをnew_usersに加える
+    async fn placeholder_user(
+        &self,
+        slack_user_id: &str,
+        slack_user: Option<&SlackUser>,
+        placeholders: &mut HashMap<String, PubUserInfo>,
+        new_users: &mut Vec<User>,
+    ) -> Result<PubUserInfo, ServiceError> {
+        if let Some(user_info) = placeholders.get(slack_user_id) {
+            return Ok(user_info.clone());
+        }
+
+        let user_mail = format!("slack-{}@import.invalid", slack_user_id.to_lowercase());
+        let user_name: String = slack_user
+            .map(|user| user.display_name())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(slack_user_id)
+            .chars()
+            .take(MAX_USER_NAME_LEN)
+            .collect();
+
+        let user_info = match self.user_repo.get_info_mail(&user_mail).await {
+            Ok(user_info) => user_info,
+            Err(sqlx::Error::RowNotFound) => {
+                let new_user = User {
+                    user_id: self.uuid.gen(),
+                    user_name,
+                    user_mail,
+                    user_pass: String::new(),
+                };
+                let user_info = PubUserInfo {
+                    user_id: new_user.user_id.clone(),
+                    user_name: new_user.user_name.clone(),
+                };
+                new_users.push(new_user);
+                user_info
+            }
+            Err(e) => return Err(e.into()),
+        };
+        placeholders.insert(slack_user_id.to_string(), user_info.clone());
+        Ok(user_info)
+    }
+}
+
+// 書き込む前に組み立てた取り込み内容
+#[derive(Default)]
+struct ImportPlan {
+    // パスワードは書き込み時に設定する
+    users: Vec<User>,
+    // ImportReport.roomsと同じ順の(ルーム名, メッセージ)
+    rooms: Vec<(String, Vec<Chat>)>,
+}
+
+// ロールバックのために記録する作成済みのユーザーとルーム
+#[derive(Default)]
+struct Created {
+    user_ids: Vec<String>,
+    room_ids: Vec<String>,
+}
+
+// 取り込めないメッセージの場合は理由を返す
+fn check_message(message: &SlackMessage) -> Result<DateTime<Utc>, &'static str> {
+    if let Some(subtype) = &message.subtype {
+        if !IMPORTED_SUBTYPES.contains(&subtype.as_str()) {
+            return Err("unsupported subtype");
+        }
+    }
+    if message.text.trim().is_empty() {
+        return Err("empty message");
+    }
+    parse_ts(&message.ts).ok_or("invalid timestamp")
+}
+
+// Slackのtsは"<秒>.<マイクロ秒>"形式
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    let (secs, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+    if micros.is_empty() || micros.len() > 6 || !micros.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let secs: i64 = secs.parse().ok()?;
+    let micros: u32 = format!("{:0<6}", micros).parse().ok()?;
+    DateTime::from_timestamp(secs, micros * 1000)
+}
+
+// Slackのエクスポートでは&, <, >のみエスケープされている
+fn unescape_text(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use std::{future::Future, pin::Pin, sync::Mutex};
+
+    use crate::{
+        domain::entity::slack_archive::SlackChannel,
+        infrastructure::{
+            repository::{
+                membership_repository_impl::MembershipRepositoryImpl,
+                message_repository_impl::MessageRepositoryImpl,
+                room_repository_impl::RoomRepositoryImpl,
+            },
+            service::{
+                password_hash_service_impl::PasswordHashServiceImpl,
+                secret_gen_impl::SecretGenImpl, uuid_gen_impl::UUIDGenIMpl,
+            },
+        },
+        MembershipDb, MessageDb, RoomDb,
+    };
+
+    use super::*;
+
+    // メモリ上にユーザーを保存し、fail_at件目の作成を失敗させるユーザーリポジトリ
+    #[derive(Default)]
+    struct FakeUserRepository {
+        users: Mutex<Vec<User>>,
+        inserted: Mutex<usize>,
+        fail_at: Option<usize>,
+    }
+
+    impl FakeUserRepository {
+        fn user_info(&self, f: impl Fn(&User) -> bool) -> Result<PubUserInfo, sqlx::Error> {
+            self.users
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|user| f(user))
+                .map(|user| PubUserInfo {
+                    user_id: user.user_id.clone(),
+                    user_name: user.user_name.clone(),
+                })
+                .ok_or(sqlx::Error::RowNotFound)
+        }
+    }
+
+    impl UserRepository for FakeUserRepository {
+        fn insert<'a>(
+            &'a self,
+            user: &'a User,
+        ) -> Pin<Box<dyn Future<Output = Result<PubUserInfo, sqlx::Error>> + Send + 'a>> {
+            Box::pin(async move {
+                let mut inserted = self.inserted.lock().unwrap();
+                *inserted += 1;
+                if self.fail_at == Some(*inserted) {
+                    return Err(sqlx::Error::PoolTimedOut);
+                }
+                self.users.lock().unwrap().push(user.clone());
+                Ok(PubUserInfo {
+                    user_id: user.user_id.clone(),
+                    user_name: user.user_name.clone(),
+                })
+            })
+        }
+
+        fn get_user_data<'a>(
+            &'a self,
+            user_mail: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Result<User, sqlx::Error>> + Send + 'a>> {
+            Box::pin(async move {
+                self.users
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|user| user.user_mail == user_mail)
+                    .cloned()
+                    .ok_or(sqlx::Error::RowNotFound)
+            })
+        }
+
+        fn get_user_info_id<'a>(
+            &'a self,
+            user_id: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Result<PubUserInfo, sqlx::Error>> + Send + 'a>> {
+            Box::pin(async move { self.user_info(|user| user.user_id == user_id) })
+        }
+
+        fn get_info_mail<'a>(
+            &'a self,
+            user_mail: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Result<PubUserInfo, sqlx::Error>> + Send + 'a>> {
+            Box::pin(async move { self.user_info(|user| user.user_mail == user_mail) })
+        }
+
+        fn delete<'a>(
+            &'a self,
+            user_id: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+            Box::pin(async move {
+                self.users
+                    .lock()
+                    .unwrap()
+                    .retain(|user| user.user_id != user_id);
+                Ok(())
+            })
+        }
+
+        fn is_admin<'a>(
+            &'a self,
+            _user_id: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>> {
+            Box::pin(async move { Ok(true) })
+        }
+    }
+
+    fn message(user: &str, text: &str, ts: &str) -> SlackMessage {
+        SlackMessage {
+            user: Some(user.to_string()),
+            text: text.to_string(),
+            ts: ts.to_string(),
+            thread_ts: None,
+            subtype: None,
+            bot_id: None,
+            username: None,
+        }
+    }
+
+    fn archive() -> SlackArchive {
+        SlackArchive {
+            channels: vec![
+                SlackChannel {
+                    name: "general".to_string(),
+                    messages: vec![
+                        message("U1", "hello", "1727740800.000100"),
+                        message("U2", "hi", "1727740801.000100"),
+                    ],
+                },
+                SlackChannel {
+                    name: "random".to_string(),
+                    messages: vec![message("U1", "random", "1727740802.000100")],
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn admin() -> PubUserInfo {
+        PubUserInfo {
+            user_id: "admin".to_string(),
+            user_name: "admin".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import() {
+        let room_db = RoomDb::new();
+        let message_db = MessageDb::new();
+        let services = SlackImportServices::new(
+            FakeUserRepository::default(),
+            RoomRepositoryImpl::new(room_db.clone()),
+            MessageRepositoryImpl::new(message_db.clone()),
+            MembershipRepositoryImpl::new(MembershipDb::new()),
+            PasswordHashServiceImpl,
+            SecretGenImpl,
+            UUIDGenIMpl,
+        );
+
+        // テスト対象
+        let report = services
+            .import(archive(), admin(), RetentionPolicy::Forever, false)
+            .await
+            .unwrap();
+        assert_eq!(report.users_created, 2);
+        assert_eq!(report.messages_imported, 3);
+        assert_eq!(services.user_repo.users.lock().unwrap().len(), 2);
+        let room_id = report.rooms[0].room_id.as_ref().unwrap();
+        let messages = services.message_repo.get_messages(room_id).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].text, "hello");
+        // 取り込みを実行した管理者がオーナーのメンバーになる
+        let owner = services
+            .membership_repo
+            .get_member(room_id, "admin")
+            .unwrap();
+        assert_eq!(owner.role, RoomRole::Owner);
+    }
+
+    #[tokio::test]
+    async fn test_import_dry_run() {
+        let room_db = RoomDb::new();
+        let services = SlackImportServices::new(
+            FakeUserRepository::default(),
+            RoomRepositoryImpl::new(room_db),
+            MessageRepositoryImpl::new(MessageDb::new()),
+            MembershipRepositoryImpl::new(MembershipDb::new()),
+            PasswordHashServiceImpl,
+            SecretGenImpl,
+            UUIDGenIMpl,
+        );
+
+        // テスト対象
+        let report = services
+            .import(archive(), admin(), RetentionPolicy::Forever, true)
+            .await
+            .unwrap();
+        assert_eq!(report.users_created, 2);
+        assert!(report.rooms.iter().all(|room| room.room_id.is_none()));
+        assert!(services.user_repo.users.lock().unwrap().is_empty());
+        assert!(services.room_repo.get_all_room().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_rolls_back_on_failure() {
+        let services = SlackImportServices::new(
+            FakeUserRepository {
+                fail_at: Some(2),
+                ..Default::default()
+            },
+            RoomRepositoryImpl::new(RoomDb::new()),
+            MessageRepositoryImpl::new(MessageDb::new()),
+            MembershipRepositoryImpl::new(MembershipDb::new()),
+            PasswordHashServiceImpl,
+            SecretGenImpl,
+            UUIDGenIMpl,
+        );
+
+        // テスト対象
+        // 2人目のユーザーの作成で失敗すると、1人目のユーザーも削除される
+        let result = services
+            .import(archive(), admin(), RetentionPolicy::Forever, false)
+            .await;
+        assert!(result.is_err());
+        assert!(services.user_repo.users.lock().unwrap().is_empty());
+        assert!(services.room_repo.get_all_room().await.unwrap().is_empty());
+    }
+}
@@ -37,7 +37,12 @@ where
         &self,
         new_user_payload: CreateUserPayload,
     ) -> Result<PubUserInfo, ServiceError> {
-        if let Ok(_) = self.repo.get_info_mail(&new_user_payload.user_mail).await {
+        if self
+            .repo
+            .get_info_mail(&new_user_payload.user_mail)
+            .await
+            .is_ok()
+        {
             return Err(ServiceError::UserAlreadyExist);
         }
         // パスワードのHASH化
@@ -65,7 +70,19 @@ where
     }
 
     pub async fn delete_user(&self, user_id: &str) -> Result<(), ServiceError> {
-        self.repo.delete(&user_id).await?;
+        self.repo.delete(user_id).await?;
         Ok(())
     }
 }
+
+// 管理者でなければForbiddenを返す
+pub async fn ensure_admin<R>(repo: &R, user_id: &str) -> Result<(), ServiceError>
+where
+    R: UserRepository,
+{
+    if repo.is_admin(user_id).await? {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden)
+    }
+}
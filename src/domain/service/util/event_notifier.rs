@@ -0,0 +1,33 @@
+use crate::domain::entity::{hook_event::HookEvent, room_info::RoomInfo};
+
+pub trait EventNotifier {
+    // 配送はバックグラウンドで行い、呼び出し側をブロックしない
+    fn notify(&self, room_id: &str, event: HookEvent);
+
+    // 完全に削除したルームを通知する。配送が終わった後にルームの送信Webhookを削除する実装もある
+    fn notify_room_deleted(&self, room_info: RoomInfo) {
+        let room_id = room_info.room_id.clone();
+        self.notify(&room_id, HookEvent::RoomDeleted((&room_info).into()));
+    }
+}
+
+// テスト用に、通知されたイベントの種類を順に記録する
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct RecordingNotifier(
+    std::sync::Mutex<Vec<crate::domain::entity::hook_event::HookEventKind>>,
+);
+
+#[cfg(test)]
+impl RecordingNotifier {
+    pub(crate) fn kinds(&self) -> Vec<crate::domain::entity::hook_event::HookEventKind> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl EventNotifier for RecordingNotifier {
+    fn notify(&self, _room_id: &str, event: HookEvent) {
+        self.0.lock().unwrap().push(event.kind());
+    }
+}
@@ -1,3 +1,6 @@
+pub mod event_notifier;
 pub mod password_hash_service;
+pub mod secret_gen;
 pub mod token_service;
+pub mod transcript_encoder;
 pub mod uuid_gen;
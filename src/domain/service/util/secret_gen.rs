@@ -0,0 +1,3 @@
+pub trait SecretGen {
+    fn gen_secret(&self) -> String;
+}
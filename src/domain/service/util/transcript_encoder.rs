@@ -0,0 +1,10 @@
+use crate::domain::entity::{chat::Chat, room_info::RoomInfo};
+
+pub trait TranscriptEncoder {
+    fn content_type(&self) -> &'static str;
+    fn file_extension(&self) -> &'static str;
+    fn header(&self, room_info: &RoomInfo) -> String;
+    // indexは0から始まる出力済みの件数
+    fn row(&self, chat: &Chat, index: usize) -> String;
+    fn footer(&self) -> String;
+}
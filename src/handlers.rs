@@ -1,4 +1,14 @@
+pub mod audit;
 pub mod auth;
 pub mod chat;
+pub mod export;
+pub mod favorites;
+pub mod hooks;
+pub mod import;
+pub mod invite_links;
+pub mod membership;
+pub mod moderation;
+pub mod outgoing_hooks;
+pub mod ownership;
 pub mod room;
 pub mod users;
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use http::{HeaderMap, HeaderValue, StatusCode};
+
+use crate::{
+    domain::{
+        entity::{audit_query::AuditQuery, claims::Claims, pub_user_info::PubUserInfo},
+        service::{audit_service::AuditServices, error::ServiceError},
+    },
+    infrastructure::repository::{
+        membership_repository_impl::MembershipRepositoryImpl,
+        room_repository_impl::RoomRepositoryImpl,
+    },
+    MembershipDb, RoomDb,
+};
+
+use super::room::NEXT_CURSOR_HEADER;
+
+pub async fn get_audit_log_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    Query(query): Query<AuditQuery>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = AuditServices::new(
+        MembershipRepositoryImpl::new(membership_db),
+        RoomRepositoryImpl::new(room_db),
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let audit_page = services.get_audit_log(&room_id, query, user_info).await?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(next_cursor) = audit_page.next_cursor {
+        if let Ok(value) = HeaderValue::from_str(&next_cursor) {
+            headers.insert(NEXT_CURSOR_HEADER, value);
+        }
+    }
+    Ok((StatusCode::OK, headers, Json(audit_page.entries)))
+}
@@ -1,27 +1,58 @@
-use axum::extract::{Path, State, WebSocketUpgrade};
+use axum::extract::{Path, Query, State, WebSocketUpgrade};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
 use serde_json::json;
 use tracing::warn;
 
+use crate::domain::entity::chat_query::ChatQuery;
 use crate::domain::entity::claims::Claims;
 use crate::domain::entity::pub_user_info::PubUserInfo;
 use crate::domain::service::chat_service::ChatServices;
+use crate::domain::service::error::ServiceError;
 use crate::domain::service::room_service::RoomServices;
+use crate::infrastructure::repository::membership_repository_impl::MembershipRepositoryImpl;
+use crate::infrastructure::repository::message_repository_impl::MessageRepositoryImpl;
 use crate::infrastructure::repository::room_repository_impl::RoomRepositoryImpl;
-use crate::RoomDb;
+use crate::infrastructure::service::event_notifier_impl::EventNotifierImpl;
+use crate::infrastructure::service::password_hash_service_impl::PasswordHashServiceImpl;
+use crate::{MembershipDb, MessageDb, OutgoingHookDb, RoomDb};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn chat_handler_with_upgrade(
     claims: Claims,
     Path(room_id): Path<String>,
+    Query(query): Query<ChatQuery>,
     State(repo): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    State(message_db): State<MessageDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    let service = RoomServices::new(RoomRepositoryImpl::new(repo));
+    let service = RoomServices::new(
+        RoomRepositoryImpl::new(repo.clone()),
+        MembershipRepositoryImpl::new(membership_db.clone()),
+        PasswordHashServiceImpl,
+    );
 
-    let room_sender = match service.get_sender(&room_id) {
-        Ok(sender) => sender,
+    let room_info = match service
+        .get_joinable_room_info(&room_id, &claims.user_id)
+        .await
+    {
+        Ok(room_info) => room_info,
+        Err(ServiceError::Banned) => {
+            let body = Json(json!({
+                "error": "Banned",
+            }));
+            return (StatusCode::FORBIDDEN, body).into_response();
+        }
+        Err(ServiceError::Forbidden) => {
+            let body = Json(json!({
+                "error": "Password required",
+            }));
+            return (StatusCode::FORBIDDEN, body).into_response();
+        }
+        Err(e @ ServiceError::RoomFull) => return e.into_response(),
         Err(_) => {
             let body = Json(json!({
                 "error": "Room not found",
@@ -36,7 +67,16 @@ pub async fn chat_handler_with_upgrade(
 
     ws.on_failed_upgrade(|e| warn!("websocket upgrade error {}", e))
         .on_upgrade(move |socket| {
-            let chat_services = ChatServices::new(socket, room_sender, user_info);
+            let chat_services = ChatServices::new(
+                socket,
+                room_info,
+                user_info,
+                query.since,
+                MessageRepositoryImpl::new(message_db),
+                EventNotifierImpl::new(outgoing_hook_db),
+                RoomRepositoryImpl::new(repo),
+                MembershipRepositoryImpl::new(membership_db),
+            );
             chat_services.ws_task()
         })
 }
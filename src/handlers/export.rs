@@ -0,0 +1,69 @@
+use std::io;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use futures::TryStreamExt;
+use http::{
+    header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    StatusCode,
+};
+
+use crate::{
+    domain::{
+        entity::{claims::Claims, export_query::ExportQuery, pub_user_info::PubUserInfo},
+        service::{
+            error::ServiceError, export_service::ExportServices,
+            util::transcript_encoder::TranscriptEncoder,
+        },
+    },
+    infrastructure::{
+        repository::{
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl,
+        },
+        service::transcript_encoder_impl::TranscriptEncoderImpl,
+    },
+    MessageDb, RoomDb,
+};
+
+pub async fn export_room_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    Path(room_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let encoder = TranscriptEncoderImpl::new(query.format);
+    let content_type = encoder.content_type();
+    let content_disposition = format!(
+        "attachment; filename=\"room-{}.{}\"",
+        room_id,
+        encoder.file_extension()
+    );
+
+    let services = ExportServices::new(
+        MessageRepositoryImpl::new(message_db),
+        RoomRepositoryImpl::new(room_db),
+        encoder,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let stream = services
+        .export_room(&room_id, user_info, query)
+        .await?
+        .map_err(|e| io::Error::other(format!("{:?}", e)));
+
+    Ok((
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (CONTENT_DISPOSITION, content_disposition),
+        ],
+        Body::from_stream(stream),
+    ))
+}
@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{claims::Claims, update_favorite::UpdateFavorite},
+        service::{error::ServiceError, favorite_service::FavoriteServices},
+    },
+    infrastructure::repository::{
+        favorite_repository_impl::FavoriteRepositoryImpl,
+        membership_repository_impl::MembershipRepositoryImpl,
+        room_repository_impl::RoomRepositoryImpl,
+    },
+    util::ValidatedJson,
+    FavoriteDb, MembershipDb, RoomDb,
+};
+
+pub fn favorite_services(
+    favorite_db: FavoriteDb,
+    room_db: RoomDb,
+    membership_db: MembershipDb,
+) -> FavoriteServices<FavoriteRepositoryImpl, RoomRepositoryImpl, MembershipRepositoryImpl> {
+    FavoriteServices::new(
+        FavoriteRepositoryImpl::new(favorite_db),
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+    )
+}
+
+pub async fn put_favorite_handler(
+    claims: Claims,
+    State(favorite_db): State<FavoriteDb>,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    Path(room_id): Path<String>,
+    payload: Option<ValidatedJson<UpdateFavorite>>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let payload = payload
+        .map(|ValidatedJson(payload)| payload)
+        .unwrap_or_default();
+    let services = favorite_services(favorite_db, room_db, membership_db);
+    services
+        .put_favorite(&claims.user_id, &room_id, payload)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_favorites_handler(
+    claims: Claims,
+    State(favorite_db): State<FavoriteDb>,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = favorite_services(favorite_db, room_db, membership_db);
+    let favorite_rooms = services.get_favorite_rooms(&claims.user_id).await?;
+    Ok((StatusCode::OK, Json(favorite_rooms)))
+}
+
+pub async fn delete_favorite_handler(
+    claims: Claims,
+    State(favorite_db): State<FavoriteDb>,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = favorite_services(favorite_db, room_db, membership_db);
+    services.delete_favorite(&claims.user_id, &room_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
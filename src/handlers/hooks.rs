@@ -0,0 +1,113 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{
+            claims::Claims, create_hook::CreateHook, hook_payload::HookPayload,
+            pub_user_info::PubUserInfo,
+        },
+        service::{error::ServiceError, incoming_hook_service::IncomingHookServices},
+    },
+    infrastructure::{
+        repository::{
+            incoming_hook_repository_impl::IncomingHookRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl,
+        },
+        service::{event_notifier_impl::EventNotifierImpl, secret_gen_impl::SecretGenImpl},
+    },
+    util::ValidatedJson,
+    IncomingHookDb, MessageDb, OutgoingHookDb, RoomDb,
+};
+
+fn hook_services(
+    hook_db: IncomingHookDb,
+    room_db: RoomDb,
+    message_db: MessageDb,
+    outgoing_hook_db: OutgoingHookDb,
+) -> IncomingHookServices<
+    IncomingHookRepositoryImpl,
+    RoomRepositoryImpl,
+    MessageRepositoryImpl,
+    SecretGenImpl,
+    EventNotifierImpl,
+> {
+    IncomingHookServices::new(
+        IncomingHookRepositoryImpl::new(hook_db),
+        RoomRepositoryImpl::new(room_db),
+        MessageRepositoryImpl::new(message_db),
+        SecretGenImpl,
+        EventNotifierImpl::new(outgoing_hook_db),
+    )
+}
+
+pub async fn create_hook_handler(
+    claims: Claims,
+    State(hook_db): State<IncomingHookDb>,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<CreateHook>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = hook_services(hook_db, room_db, message_db, outgoing_hook_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let hook = services.create_hook(&room_id, payload, user_info).await?;
+    Ok((StatusCode::OK, Json(hook)))
+}
+
+pub async fn get_room_hooks_handler(
+    claims: Claims,
+    State(hook_db): State<IncomingHookDb>,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = hook_services(hook_db, room_db, message_db, outgoing_hook_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let hooks = services.get_room_hooks(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(hooks)))
+}
+
+pub async fn revoke_hook_handler(
+    claims: Claims,
+    State(hook_db): State<IncomingHookDb>,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path((room_id, hook_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = hook_services(hook_db, room_db, message_db, outgoing_hook_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services.revoke_hook(&room_id, &hook_id, user_info).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Cookieによる認証は不要で、URLのトークンで認証する
+pub async fn post_hook_message_handler(
+    State(hook_db): State<IncomingHookDb>,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path(token): Path<String>,
+    ValidatedJson(payload): ValidatedJson<HookPayload>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = hook_services(hook_db, room_db, message_db, outgoing_hook_db);
+    let chat = services.post_message(&token, payload).await?;
+    Ok((StatusCode::OK, Json(chat)))
+}
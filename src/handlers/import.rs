@@ -0,0 +1,82 @@
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{
+            claims::Claims, import_config::ImportConfig, pub_user_info::PubUserInfo,
+            retention_policy::RetentionLimits, slack_import_query::SlackImportQuery,
+        },
+        service::{error::ServiceError, slack_import_service::SlackImportServices},
+    },
+    infrastructure::{
+        repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl, user_repository_impl::UserRepositoryImpl,
+        },
+        service::{
+            password_hash_service_impl::PasswordHashServiceImpl,
+            secret_gen_impl::SecretGenImpl,
+            slack_archive_reader::{read_archive_path, read_archive_zip, resolve_import_path},
+            uuid_gen_impl::UUIDGenIMpl,
+        },
+    },
+    MembershipDb, MessageDb, RoomDb, UserDb,
+};
+
+// pathを指定した場合は取り込み用ディレクトリ内のエクスポートを、省略した場合はボディのZIPを取り込む
+#[allow(clippy::too_many_arguments)]
+pub async fn import_slack_handler(
+    claims: Claims,
+    State(user_db): State<UserDb>,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    State(membership_db): State<MembershipDb>,
+    State(retention_limits): State<RetentionLimits>,
+    State(import_config): State<ImportConfig>,
+    Query(query): Query<SlackImportQuery>,
+    body: Bytes,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = SlackImportServices::new(
+        UserRepositoryImpl::new(&user_db.pool),
+        RoomRepositoryImpl::new(room_db),
+        MessageRepositoryImpl::new(message_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+        SecretGenImpl,
+        UUIDGenIMpl,
+    );
+    // アーカイブを読む前に権限と保持期間を確認する
+    services.ensure_admin(&claims.user_id).await?;
+    // 取り込んだ履歴はサーバーのデフォルトではなく、許される限り長く保持する
+    let retention = query.retention.unwrap_or(retention_limits.max);
+    if !retention_limits.allows(&retention) {
+        return Err(ServiceError::Validation);
+    }
+
+    let archive = tokio::task::spawn_blocking(move || match query.path {
+        Some(path) => {
+            // 取り込み用ディレクトリが設定されていない場合はサーバー上のファイルを読まない
+            let import_dir = import_config.import_dir.ok_or(ServiceError::Forbidden)?;
+            read_archive_path(&resolve_import_path(&import_dir, &path)?)
+        }
+        None => read_archive_zip(&body),
+    })
+    .await
+    .map_err(|_| ServiceError::Server)??;
+
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let report = services
+        .import(archive, user_info, retention, query.dry_run)
+        .await?;
+    Ok((StatusCode::OK, Json(report)))
+}
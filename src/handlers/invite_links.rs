@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{
+            claims::Claims, create_invite_link::CreateInviteLink, pub_user_info::PubUserInfo,
+        },
+        service::{error::ServiceError, invite_link_service::InviteLinkServices},
+    },
+    infrastructure::{
+        repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl,
+        },
+        service::{event_notifier_impl::EventNotifierImpl, secret_gen_impl::SecretGenImpl},
+    },
+    util::ValidatedJson,
+    MembershipDb, OutgoingHookDb, RoomDb,
+};
+
+fn invite_link_services(
+    membership_db: MembershipDb,
+    room_db: RoomDb,
+) -> InviteLinkServices<MembershipRepositoryImpl, RoomRepositoryImpl, SecretGenImpl> {
+    InviteLinkServices::new(
+        MembershipRepositoryImpl::new(membership_db),
+        RoomRepositoryImpl::new(room_db),
+        SecretGenImpl,
+    )
+}
+
+pub async fn create_invite_link_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<CreateInviteLink>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = invite_link_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let invite_link = services
+        .create_invite_link(&room_id, payload, user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(invite_link)))
+}
+
+pub async fn get_room_invite_links_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = invite_link_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let invite_links = services.get_room_invite_links(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(invite_links)))
+}
+
+pub async fn revoke_invite_link_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path((room_id, invite_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = invite_link_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services
+        .revoke_invite_link(&room_id, &invite_id, user_info)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn accept_invite_link_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = invite_link_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let membership = services
+        .accept_invite_link(&token, user_info, &EventNotifierImpl::new(outgoing_hook_db))
+        .await?;
+    Ok((StatusCode::OK, Json(membership)))
+}
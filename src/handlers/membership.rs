@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{claims::Claims, create_invitation::CreateInvitation, pub_user_info::PubUserInfo},
+        service::{error::ServiceError, membership_service::MembershipServices},
+    },
+    infrastructure::{
+        repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl, user_repository_impl::UserRepositoryImpl,
+        },
+        service::event_notifier_impl::EventNotifierImpl,
+    },
+    util::ValidatedJson,
+    MembershipDb, OutgoingHookDb, RoomDb, UserDb,
+};
+
+fn membership_services(
+    membership_db: MembershipDb,
+    room_db: RoomDb,
+    user_db: &UserDb,
+) -> MembershipServices<MembershipRepositoryImpl, RoomRepositoryImpl, UserRepositoryImpl<'_>> {
+    MembershipServices::new(
+        MembershipRepositoryImpl::new(membership_db),
+        RoomRepositoryImpl::new(room_db),
+        UserRepositoryImpl::new(&user_db.pool),
+    )
+}
+
+pub async fn create_invitation_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<CreateInvitation>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let invitation = services.invite(&room_id, payload, user_info).await?;
+    Ok((StatusCode::OK, Json(invitation)))
+}
+
+pub async fn get_room_invitations_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let invitations = services.get_room_invitations(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(invitations)))
+}
+
+pub async fn revoke_invitation_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+    Path((room_id, invitation_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services
+        .revoke_invitation(&room_id, &invitation_id, user_info)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_room_members_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let members = services.get_room_members(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(members)))
+}
+
+pub async fn get_my_invitations_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let invitations = services.get_user_invitations(user_info)?;
+    Ok((StatusCode::OK, Json(invitations)))
+}
+
+pub async fn accept_invitation_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path(invitation_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let membership = services
+        .accept_invitation(
+            &invitation_id,
+            user_info,
+            &EventNotifierImpl::new(outgoing_hook_db),
+        )
+        .await?;
+    Ok((StatusCode::OK, Json(membership)))
+}
+
+pub async fn decline_invitation_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(user_db): State<UserDb>,
+    Path(invitation_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = membership_services(membership_db, room_db, &user_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services.decline_invitation(&invitation_id, user_info)?;
+    Ok(StatusCode::NO_CONTENT)
+}
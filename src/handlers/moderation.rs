@@ -0,0 +1,208 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{
+            claims::Claims, moderation_payload::ModerationPayload, pub_user_info::PubUserInfo,
+            sanction::SanctionKind, update_role::UpdateRole,
+        },
+        service::{error::ServiceError, moderation_service::ModerationServices},
+    },
+    infrastructure::repository::{
+        membership_repository_impl::MembershipRepositoryImpl,
+        message_repository_impl::MessageRepositoryImpl, room_repository_impl::RoomRepositoryImpl,
+    },
+    util::ValidatedJson,
+    MembershipDb, MessageDb, RoomDb,
+};
+
+fn moderation_services(
+    membership_db: MembershipDb,
+    room_db: RoomDb,
+) -> ModerationServices<MembershipRepositoryImpl, RoomRepositoryImpl> {
+    ModerationServices::new(
+        MembershipRepositoryImpl::new(membership_db),
+        RoomRepositoryImpl::new(room_db),
+    )
+}
+
+pub async fn update_role_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path((room_id, user_id)): Path<(String, String)>,
+    ValidatedJson(payload): ValidatedJson<UpdateRole>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let membership = services
+        .set_role(&room_id, &user_id, payload, user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(membership)))
+}
+
+pub async fn kick_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<ModerationPayload>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services.kick(&room_id, payload, user_info).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn ban_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<ModerationPayload>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let sanction = services.ban(&room_id, payload, user_info).await?;
+    Ok((StatusCode::OK, Json(sanction)))
+}
+
+pub async fn mute_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<ModerationPayload>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let sanction = services.mute(&room_id, payload, user_info).await?;
+    Ok((StatusCode::OK, Json(sanction)))
+}
+
+pub async fn get_bans_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    get_sanctions(claims, membership_db, room_db, room_id, SanctionKind::Ban).await
+}
+
+pub async fn get_mutes_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    get_sanctions(claims, membership_db, room_db, room_id, SanctionKind::Mute).await
+}
+
+pub async fn unban_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path((room_id, user_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    lift_sanction(
+        claims,
+        membership_db,
+        room_db,
+        room_id,
+        user_id,
+        SanctionKind::Ban,
+    )
+    .await
+}
+
+pub async fn unmute_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path((room_id, user_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    lift_sanction(
+        claims,
+        membership_db,
+        room_db,
+        room_id,
+        user_id,
+        SanctionKind::Mute,
+    )
+    .await
+}
+
+pub async fn delete_message_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    Path((room_id, message_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services
+        .delete_message(
+            &MessageRepositoryImpl::new(message_db),
+            &room_id,
+            &message_id,
+            user_info,
+        )
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_sanctions(
+    claims: Claims,
+    membership_db: MembershipDb,
+    room_db: RoomDb,
+    room_id: String,
+    kind: SanctionKind,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let sanctions = services.get_sanctions(&room_id, kind, user_info).await?;
+    Ok((StatusCode::OK, Json(sanctions)))
+}
+
+async fn lift_sanction(
+    claims: Claims,
+    membership_db: MembershipDb,
+    room_db: RoomDb,
+    room_id: String,
+    user_id: String,
+    kind: SanctionKind,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = moderation_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services
+        .lift_sanction(&room_id, &user_id, kind, user_info)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
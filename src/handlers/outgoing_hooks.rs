@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{
+            claims::Claims, create_outgoing_hook::CreateOutgoingHook, pub_user_info::PubUserInfo,
+        },
+        service::{error::ServiceError, outgoing_hook_service::OutgoingHookServices},
+    },
+    infrastructure::{
+        repository::{
+            outgoing_hook_repository_impl::OutgoingHookRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl,
+        },
+        service::secret_gen_impl::SecretGenImpl,
+    },
+    util::ValidatedJson,
+    OutgoingHookDb, RoomDb,
+};
+
+pub async fn create_outgoing_hook_handler(
+    claims: Claims,
+    State(hook_db): State<OutgoingHookDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<CreateOutgoingHook>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let target_policy = hook_db.target_policy;
+    let services = OutgoingHookServices::new(
+        OutgoingHookRepositoryImpl::new(hook_db),
+        RoomRepositoryImpl::new(room_db),
+        SecretGenImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let hook = services
+        .create_hook(&room_id, payload, user_info, target_policy)
+        .await?;
+    Ok((StatusCode::OK, Json(hook)))
+}
+
+pub async fn get_outgoing_hooks_handler(
+    claims: Claims,
+    State(hook_db): State<OutgoingHookDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = OutgoingHookServices::new(
+        OutgoingHookRepositoryImpl::new(hook_db),
+        RoomRepositoryImpl::new(room_db),
+        SecretGenImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let hooks = services.get_room_hooks(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(hooks)))
+}
+
+pub async fn revoke_outgoing_hook_handler(
+    claims: Claims,
+    State(hook_db): State<OutgoingHookDb>,
+    State(room_db): State<RoomDb>,
+    Path((room_id, hook_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = OutgoingHookServices::new(
+        OutgoingHookRepositoryImpl::new(hook_db),
+        RoomRepositoryImpl::new(room_db),
+        SecretGenImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services.revoke_hook(&room_id, &hook_id, user_info).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_hook_deliveries_handler(
+    claims: Claims,
+    State(hook_db): State<OutgoingHookDb>,
+    State(room_db): State<RoomDb>,
+    Path((room_id, hook_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = OutgoingHookServices::new(
+        OutgoingHookRepositoryImpl::new(hook_db),
+        RoomRepositoryImpl::new(room_db),
+        SecretGenImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let deliveries = services
+        .get_deliveries(&room_id, &hook_id, user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(deliveries)))
+}
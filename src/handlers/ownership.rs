@@ -0,0 +1,143 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+
+use crate::{
+    domain::{
+        entity::{
+            claims::Claims, pub_user_info::PubUserInfo, transfer_ownership::TransferOwnership,
+        },
+        service::{
+            error::ServiceError, ownership_service::OwnershipServices, user_service::ensure_admin,
+        },
+    },
+    infrastructure::repository::{
+        membership_repository_impl::MembershipRepositoryImpl,
+        room_repository_impl::RoomRepositoryImpl, user_repository_impl::UserRepositoryImpl,
+    },
+    util::ValidatedJson,
+    MembershipDb, RoomDb, UserDb,
+};
+
+fn ownership_services(
+    membership_db: MembershipDb,
+    room_db: RoomDb,
+) -> OwnershipServices<MembershipRepositoryImpl, RoomRepositoryImpl> {
+    OwnershipServices::new(
+        MembershipRepositoryImpl::new(membership_db),
+        RoomRepositoryImpl::new(room_db),
+    )
+}
+
+pub async fn request_transfer_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<TransferOwnership>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let transfer = services
+        .request_transfer(&room_id, payload, user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(transfer)))
+}
+
+pub async fn get_room_transfer_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let transfer = services.get_room_transfer(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(transfer)))
+}
+
+pub async fn cancel_transfer_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services.cancel_transfer(&room_id, user_info).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_my_transfers_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let transfers = services.get_user_transfers(user_info)?;
+    Ok((StatusCode::OK, Json(transfers)))
+}
+
+pub async fn accept_transfer_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let room_info = services.accept_transfer(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(room_info)))
+}
+
+pub async fn decline_transfer_handler(
+    claims: Claims,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    services.decline_transfer(&room_id, user_info)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// オーナーのアカウントが削除されてアーカイブされたルームを管理者が引き継ぐ
+pub async fn claim_orphaned_room_handler(
+    claims: Claims,
+    State(user_db): State<UserDb>,
+    State(membership_db): State<MembershipDb>,
+    State(room_db): State<RoomDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    ensure_admin(&UserRepositoryImpl::new(&user_db.pool), &claims.user_id).await?;
+    let services = ownership_services(membership_db, room_db);
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let room_info = services.claim_orphaned_room(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(room_info)))
+}
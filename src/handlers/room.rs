@@ -1,73 +1,348 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
     Json,
 };
-use http::StatusCode;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
 
 use crate::{
     domain::{
-        entity::{claims::Claims, create_room::CreateRoom, pub_user_info::PubUserInfo},
-        service::{error::ServiceError, room_service::RoomServices},
+        entity::{
+            claims::Claims, create_room::CreateRoom, join_room::JoinRoom,
+            pub_user_info::PubUserInfo, retention_policy::RetentionLimits, room_query::RoomQuery,
+            update_room::UpdateRoom,
+        },
+        service::{
+            error::ServiceError, retention_service::RetentionServices, room_service::RoomServices,
+        },
+    },
+    infrastructure::{
+        repository::{
+            membership_repository_impl::MembershipRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl,
+        },
+        service::{
+            event_notifier_impl::EventNotifierImpl,
+            password_hash_service_impl::PasswordHashServiceImpl,
+        },
     },
-    infrastructure::repository::room_repository_impl::RoomRepositoryImpl,
     util::ValidatedJson,
-    RoomDb,
+    FavoriteDb, MembershipDb, MessageDb, OutgoingHookDb, RoomDb,
 };
 
+use super::favorites::favorite_services;
+
+// ルーム一覧の次のページのカーソル。最後のページでは付かない
+pub const NEXT_CURSOR_HEADER: HeaderName = HeaderName::from_static("x-next-cursor");
+
 pub async fn create_room_handler(
     claims: Claims,
     State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    State(retention_limits): State<RetentionLimits>,
     ValidatedJson(payload): ValidatedJson<CreateRoom>,
 ) -> Result<impl IntoResponse, ServiceError> {
-    let room_services = RoomServices::new(RoomRepositoryImpl::new(room_db));
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
     let user_info = PubUserInfo {
         user_id: claims.user_id,
         user_name: claims.user_name,
     };
-    let room_info = room_services.create_room(payload, user_info)?;
+    let room_info = room_services
+        .create_room(payload, user_info, retention_limits)
+        .await?;
     Ok((StatusCode::OK, Json(room_info)))
 }
 
 pub async fn get_specific_room_info(
-    _claims: Claims,
+    claims: Claims,
     State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
     Path(room_id): Path<String>,
 ) -> Result<impl IntoResponse, ServiceError> {
-    let room_services = RoomServices::new(RoomRepositoryImpl::new(room_db));
-    let room_info = room_services.get_target_room_info(room_id.as_str())?;
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let room_info = room_services
+        .get_target_room_info(room_id.as_str(), &claims.user_id)
+        .await?;
+    Ok((StatusCode::OK, Json(room_info)))
+}
+
+pub async fn join_room_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    Path(room_id): Path<String>,
+    payload: Option<ValidatedJson<JoinRoom>>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let payload = payload
+        .map(|ValidatedJson(payload)| payload)
+        .unwrap_or_default();
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let membership = room_services
+        .join_room(
+            room_id.as_str(),
+            payload,
+            user_info,
+            &EventNotifierImpl::new(outgoing_hook_db),
+        )
+        .await?;
+    Ok((StatusCode::OK, Json(membership)))
+}
+
+pub async fn get_room_by_join_code_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    Path(join_code): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let room_info = room_services
+        .get_room_by_join_code(join_code.as_str(), &claims.user_id)
+        .await?;
     Ok((StatusCode::OK, Json(room_info)))
 }
 
 pub async fn get_owner_room_handler(
     claims: Claims,
     State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
 ) -> Result<impl IntoResponse, ServiceError> {
-    let room_services = RoomServices::new(RoomRepositoryImpl::new(room_db));
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
 
-    let owner_room_info = room_services.get_owner_room_info(claims).map_err(|e| e)?;
+    let owner_room_info = room_services.get_owner_room_info(claims).await?;
     Ok((StatusCode::OK, Json(owner_room_info)))
 }
 
 pub async fn get_all_room_info_handler(
-    _claims: Claims,
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    State(message_db): State<MessageDb>,
+    State(favorite_db): State<FavoriteDb>,
+    Query(query): Query<RoomQuery>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db.clone()),
+        MembershipRepositoryImpl::new(membership_db.clone()),
+        PasswordHashServiceImpl,
+    );
+    let room_page = room_services
+        .get_all_room_info(
+            &MessageRepositoryImpl::new(message_db),
+            &claims.user_id,
+            query,
+        )
+        .await?;
+    let rooms = favorite_services(favorite_db, room_db, membership_db)
+        .mark_rooms(&claims.user_id, room_page.rooms)?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(next_cursor) = room_page.next_cursor {
+        // カーソルはbase64urlのため常にヘッダーの値にできる
+        if let Ok(value) = HeaderValue::from_str(&next_cursor) {
+            headers.insert(NEXT_CURSOR_HEADER, value);
+        }
+    }
+    Ok((StatusCode::OK, headers, Json(rooms)))
+}
+
+pub async fn get_room_tags_handler(
+    claims: Claims,
     State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
 ) -> Result<impl IntoResponse, ServiceError> {
-    let room_services = RoomServices::new(RoomRepositoryImpl::new(room_db));
-    let room_infos = room_services.get_all_room_info()?;
-    Ok((StatusCode::OK, Json(room_infos)))
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let tags = room_services.get_room_tags(&claims.user_id).await?;
+    Ok((StatusCode::OK, Json(tags)))
+}
+
+pub async fn update_room_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    State(retention_limits): State<RetentionLimits>,
+    Path(room_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateRoom>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let room_info = room_services
+        .update_owner_room(room_id.as_str(), payload, user_info, retention_limits)
+        .await?;
+    Ok((StatusCode::OK, Json(room_info)))
 }
 
+pub async fn archive_room_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let room_info = room_services
+        .archive_owner_room(room_id.as_str(), user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(room_info)))
+}
+
+pub async fn unarchive_room_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let room_info = room_services
+        .unarchive_owner_room(room_id.as_str(), user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(room_info)))
+}
+
+// メッセージやWebhookは猶予期間が過ぎてから削除し、その時点で送信WebhookにroomDeletedを送る
 pub async fn delete_room_handler(
     claims: Claims,
     State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
     Path(room_id): Path<String>,
 ) -> Result<impl IntoResponse, ServiceError> {
-    let room_services = RoomServices::new(RoomRepositoryImpl::new(room_db));
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
     let user_info = PubUserInfo {
         user_id: claims.user_id,
         user_name: claims.user_name,
     };
-    room_services.delete_owner_room(room_id.as_str(), user_info)?;
+    room_services
+        .delete_owner_room(room_id.as_str(), user_info)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+pub async fn get_deleted_rooms_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let rooms = room_services.get_deleted_owner_rooms(user_info).await?;
+    Ok((StatusCode::OK, Json(rooms)))
+}
+
+pub async fn get_expiring_rooms_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let rooms = room_services.get_expiring_owner_rooms(user_info).await?;
+    Ok((StatusCode::OK, Json(rooms)))
+}
+
+pub async fn get_retention_purges_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(message_db): State<MessageDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let services = RetentionServices::new(
+        MessageRepositoryImpl::new(message_db),
+        RoomRepositoryImpl::new(room_db),
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let purges = services.get_room_purges(&room_id, user_info).await?;
+    Ok((StatusCode::OK, Json(purges)))
+}
+
+pub async fn restore_room_handler(
+    claims: Claims,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    Path(room_id): Path<String>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let room_services = RoomServices::new(
+        RoomRepositoryImpl::new(room_db),
+        MembershipRepositoryImpl::new(membership_db),
+        PasswordHashServiceImpl,
+    );
+    let user_info = PubUserInfo {
+        user_id: claims.user_id,
+        user_name: claims.user_name,
+    };
+    let room_info = room_services
+        .restore_owner_room(room_id.as_str(), user_info)
+        .await?;
+    Ok((StatusCode::OK, Json(room_info)))
+}
@@ -6,24 +6,33 @@ use crate::{
     domain::{
         entity::{
             auth_payload::AuthPayload, claims::Claims, create_user_payload::CreateUserPayload,
-            pub_user_info::PubUserInfo,
+            orphan_policy::OrphanPolicy, pub_user_info::PubUserInfo,
         },
         service::{
-            auth_service::AuthorizeServices, error::ServiceError, user_service::UserService,
+            auth_service::AuthorizeServices, error::ServiceError,
+            ownership_service::OwnershipServices, room_cleanup_service::RoomCleanupServices,
+            user_service::UserService,
         },
     },
     infrastructure::{
-        repository::user_repository_impl::UserRepositoryImpl,
+        repository::{
+            favorite_repository_impl::FavoriteRepositoryImpl,
+            incoming_hook_repository_impl::IncomingHookRepositoryImpl,
+            membership_repository_impl::MembershipRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl, user_repository_impl::UserRepositoryImpl,
+        },
         service::{
+            event_notifier_impl::EventNotifierImpl,
             password_hash_service_impl::PasswordHashServiceImpl,
             token_service_impl::TokenServiceImpl, uuid_gen_impl::UUIDGenIMpl,
         },
     },
     util::ValidatedJson,
-    UserDb,
+    FavoriteDb, IncomingHookDb, MembershipDb, MessageDb, OutgoingHookDb, RoomDb, UserDb,
 };
 
-use super::auth::COOKIE_KEY;
+use super::{auth::COOKIE_KEY, favorites::favorite_services};
 
 pub async fn add_new_user(
     State(db): State<UserDb>,
@@ -56,10 +65,18 @@ pub async fn get_user_info_handle(
     Ok((StatusCode::OK, Json(query_res)))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn delete_user_handle(
     claims: Claims,
     jar: CookieJar,
     State(db): State<UserDb>,
+    State(room_db): State<RoomDb>,
+    State(membership_db): State<MembershipDb>,
+    State(message_db): State<MessageDb>,
+    State(hook_db): State<IncomingHookDb>,
+    State(outgoing_hook_db): State<OutgoingHookDb>,
+    State(favorite_db): State<FavoriteDb>,
+    State(orphan_policy): State<OrphanPolicy>,
     ValidatedJson(auth_payload): ValidatedJson<AuthPayload>,
 ) -> Result<impl IntoResponse, ServiceError> {
     // ユーザーデータの削除には再認証が必要
@@ -80,6 +97,25 @@ pub async fn delete_user_handle(
         user_name: claims.user_name,
     };
 
+    // オーナーがいなくなったルームを設定されたポリシーに従って処理する
+    // 途中で失敗した場合にやり直せるよう、ユーザー自体は最後に削除する
+    let ownership_services = OwnershipServices::new(
+        MembershipRepositoryImpl::new(membership_db.clone()),
+        RoomRepositoryImpl::new(room_db.clone()),
+    );
+    let deleted_rooms = ownership_services
+        .release_user_rooms(&user_info.user_id, orphan_policy)
+        .await?;
+    let cleanup_services = RoomCleanupServices::new(
+        MessageRepositoryImpl::new(message_db),
+        IncomingHookRepositoryImpl::new(hook_db),
+        FavoriteRepositoryImpl::new(favorite_db.clone()),
+        EventNotifierImpl::new(outgoing_hook_db),
+    );
+    for room_info in deleted_rooms {
+        cleanup_services.delete_room_resources(room_info)?;
+    }
+    favorite_services(favorite_db, room_db, membership_db).delete_user(&user_info.user_id)?;
     user_service.delete_user(&user_info.user_id).await?;
 
     // JWTが残らないようにCookieから削除
@@ -1,2 +1,3 @@
 pub mod repository;
+pub mod room_actor;
 pub mod service;
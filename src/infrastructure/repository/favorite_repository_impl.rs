@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::{
+        entity::favorite::Favorite,
+        repository::{error::RepositoryError, favorite_repository::FavoriteRepository},
+    },
+    FavoriteDb,
+};
+
+type Favorites = HashMap<String, Vec<Favorite>>;
+
+pub struct FavoriteRepositoryImpl {
+    db: FavoriteDb,
+}
+
+impl FavoriteRepositoryImpl {
+    pub fn new(db: FavoriteDb) -> Self {
+        Self { db }
+    }
+}
+
+impl FavoriteRepository for FavoriteRepositoryImpl {
+    fn put_favorite(
+        &self,
+        user_id: &str,
+        room_id: &str,
+        position: Option<usize>,
+        muted: Option<bool>,
+        read: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Favorite, RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let favorites = guard.entry(user_id.to_owned()).or_default();
+        let current = favorites
+            .iter()
+            .position(|favorite| favorite.room_id == room_id);
+
+        let (mut favorite, index) = match current {
+            Some(index) => (favorites.remove(index), index),
+            None => (
+                Favorite {
+                    room_id: room_id.to_owned(),
+                    muted: false,
+                    added_time: now,
+                    last_read_time: now,
+                },
+                favorites.len(),
+            ),
+        };
+        if let Some(muted) = muted {
+            favorite.muted = muted;
+        }
+        if read {
+            favorite.last_read_time = now;
+        }
+        let index = position.unwrap_or(index).min(favorites.len());
+        favorites.insert(index, favorite.clone());
+        Ok(favorite)
+    }
+
+    fn get_favorites(&self, user_id: &str) -> Result<Vec<Favorite>, RepositoryError> {
+        let guard = get_read_lock(self)?;
+        Ok(guard.get(user_id).cloned().unwrap_or_default())
+    }
+
+    fn delete_favorite(&self, user_id: &str, room_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let favorites = guard.get_mut(user_id).ok_or(RepositoryError::NotFound)?;
+        let index = favorites
+            .iter()
+            .position(|favorite| favorite.room_id == room_id)
+            .ok_or(RepositoryError::NotFound)?;
+        favorites.remove(index);
+        Ok(())
+    }
+
+    fn delete_room(&self, room_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        for favorites in guard.values_mut() {
+            favorites.retain(|favorite| favorite.room_id != room_id);
+        }
+        Ok(())
+    }
+
+    fn delete_user(&self, user_id: &str) -> Result<(), RepositoryError> {
+        get_write_lock(self)?.remove(user_id);
+        Ok(())
+    }
+}
+
+fn get_write_lock(
+    repo: &FavoriteRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, Favorites>, RepositoryError> {
+    let lock = repo.db.pool.write().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_read_lock(
+    repo: &FavoriteRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, Favorites>, RepositoryError> {
+    let lock = repo.db.pool.read().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn room_ids(repo: &FavoriteRepositoryImpl) -> Vec<String> {
+        repo.get_favorites("user_id")
+            .unwrap()
+            .into_iter()
+            .map(|favorite| favorite.room_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_put_favorite() {
+        let repo = FavoriteRepositoryImpl::new(FavoriteDb::new());
+        let now = Utc::now();
+        for room_id in ["a", "b", "c"] {
+            repo.put_favorite("user_id", room_id, None, None, false, now)
+                .unwrap();
+        }
+        assert_eq!(room_ids(&repo), vec!["a", "b", "c"]);
+
+        // テスト対象
+        let favorite = repo
+            .put_favorite("user_id", "c", Some(0), Some(true), false, now)
+            .unwrap();
+        assert!(favorite.muted);
+        assert_eq!(room_ids(&repo), vec!["c", "a", "b"]);
+        // 位置を省略するとそのまま、長さを超えると末尾に置く
+        let favorite = repo
+            .put_favorite("user_id", "c", None, None, false, now)
+            .unwrap();
+        assert!(favorite.muted);
+        assert_eq!(room_ids(&repo), vec!["c", "a", "b"]);
+        repo.put_favorite("user_id", "a", Some(10), None, false, now)
+            .unwrap();
+        assert_eq!(room_ids(&repo), vec!["c", "b", "a"]);
+
+        repo.delete_favorite("user_id", "b").unwrap();
+        assert_eq!(room_ids(&repo), vec!["c", "a"]);
+        let result = repo.delete_favorite("user_id", "b");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+        assert!(repo.get_favorites("other").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_room() {
+        let repo = FavoriteRepositoryImpl::new(FavoriteDb::new());
+        let now = Utc::now();
+        for user_id in ["user_id", "other"] {
+            for room_id in ["a", "b"] {
+                repo.put_favorite(user_id, room_id, None, None, false, now)
+                    .unwrap();
+            }
+        }
+
+        // テスト対象
+        repo.delete_room("a").unwrap();
+        assert_eq!(room_ids(&repo), vec!["b"]);
+        assert_eq!(repo.get_favorites("other").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        entity::incoming_hook::IncomingHook,
+        repository::{error::RepositoryError, incoming_hook_repository::IncomingHookRepository},
+    },
+    IncomingHookDb,
+};
+
+pub struct IncomingHookRepositoryImpl {
+    db: IncomingHookDb,
+}
+
+impl IncomingHookRepositoryImpl {
+    pub fn new(db: IncomingHookDb) -> Self {
+        Self { db }
+    }
+}
+
+impl IncomingHookRepository for IncomingHookRepositoryImpl {
+    fn create_hook(
+        &self,
+        room_id: &str,
+        hook_name: &str,
+        token: &str,
+        created_by_id: &str,
+    ) -> Result<IncomingHook, RepositoryError> {
+        let now = Utc::now();
+        let hook = IncomingHook {
+            hook_id: Uuid::new_v4().to_string(),
+            hook_name: hook_name.to_owned(),
+            token: token.to_owned(),
+            room_id: room_id.to_owned(),
+            created_by_id: created_by_id.to_owned(),
+            created_time: now,
+            window_start: now,
+            window_count: 0,
+        };
+
+        let mut guard = get_write_lock(self)?;
+        guard.insert(hook.token.clone(), hook.clone());
+        Ok(hook)
+    }
+
+    fn get_room_hooks(&self, room_id: &str) -> Result<Vec<IncomingHook>, RepositoryError> {
+        let guard = get_read_lock(self)?;
+        let hooks = guard
+            .values()
+            .filter(|hook| hook.room_id == room_id)
+            .map(|hook| hook.to_owned())
+            .collect();
+        Ok(hooks)
+    }
+
+    fn acquire_hook(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<IncomingHook>, RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let hook = guard.get_mut(token).ok_or(RepositoryError::NotFound)?;
+
+        if hook.try_acquire(now) {
+            Ok(Some(hook.to_owned()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn delete_hook(&self, room_id: &str, hook_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let token = guard
+            .values()
+            .find(|hook| hook.room_id == room_id && hook.hook_id == hook_id)
+            .map(|hook| hook.token.to_owned())
+            .ok_or(RepositoryError::NotFound)?;
+        guard.remove(&token);
+        Ok(())
+    }
+
+    fn delete_room_hooks(&self, room_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        guard.retain(|_, hook| hook.room_id != room_id);
+        Ok(())
+    }
+}
+
+fn get_write_lock(
+    repo: &IncomingHookRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, HashMap<String, IncomingHook>>, RepositoryError> {
+    let lock = repo.db.pool.write().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_read_lock(
+    repo: &IncomingHookRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, HashMap<String, IncomingHook>>, RepositoryError> {
+    let lock = repo.db.pool.read().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set_up_repo() -> IncomingHookRepositoryImpl {
+        IncomingHookRepositoryImpl::new(IncomingHookDb::new())
+    }
+
+    #[test]
+    fn test_acquire_hook_rate_limit() {
+        let repo = set_up_repo();
+        repo.create_hook("room_id", "ci", "token", "user_id")
+            .unwrap();
+        let now = Utc::now();
+
+        // テスト対象
+        for _ in 0..30 {
+            assert!(repo.acquire_hook("token", now).unwrap().is_some());
+        }
+        assert!(repo.acquire_hook("token", now).unwrap().is_none());
+
+        // ウィンドウが経過すると再度投稿できる
+        let next_window = now + chrono::Duration::seconds(60);
+        assert!(repo.acquire_hook("token", next_window).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_acquire_unknown_hook() {
+        let repo = set_up_repo();
+        let result = repo.acquire_hook("unknown", Utc::now());
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+    }
+
+    #[test]
+    fn test_delete_hook() {
+        let repo = set_up_repo();
+        let hook = repo
+            .create_hook("room_id", "ci", "token", "user_id")
+            .unwrap();
+
+        // 別のルームのフックとしては削除できない
+        let result = repo.delete_hook("other_room_id", &hook.hook_id);
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+
+        // テスト対象
+        repo.delete_hook("room_id", &hook.hook_id).unwrap();
+        assert!(repo.get_room_hooks("room_id").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,878 @@
+use std::{
+    collections::HashMap,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        entity::{
+            audit_entry::AuditEntry,
+            invitation::Invitation,
+            invite_link::InviteLink,
+            membership::Membership,
+            ownership_transfer::OwnershipTransfer,
+            pub_user_info::PubUserInfo,
+            room_info::RoomInfo,
+            room_role::RoomRole,
+            sanction::{Sanction, SanctionKind},
+        },
+        repository::{error::RepositoryError, membership_repository::MembershipRepository},
+    },
+    MembershipDb,
+};
+
+type Members = HashMap<String, HashMap<String, Membership>>;
+type Sanctions = HashMap<String, Vec<Sanction>>;
+type Transfers = HashMap<String, OwnershipTransfer>;
+type InviteLinks = HashMap<String, InviteLink>;
+type AuditLog = HashMap<String, Vec<AuditEntry>>;
+
+pub struct MembershipRepositoryImpl {
+    db: MembershipDb,
+}
+
+impl MembershipRepositoryImpl {
+    pub fn new(db: MembershipDb) -> Self {
+        Self { db }
+    }
+}
+
+impl MembershipRepository for MembershipRepositoryImpl {
+    fn add_member(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+        role: RoomRole,
+    ) -> Result<Membership, RepositoryError> {
+        let mut guard = get_members_write_lock(self)?;
+        let membership = guard
+            .entry(room_id.to_owned())
+            .or_default()
+            .entry(user_info.user_id.clone())
+            .or_insert_with(|| Membership {
+                room_id: room_id.to_owned(),
+                user_id: user_info.user_id.clone(),
+                user_name: user_info.user_name.clone(),
+                role,
+                joined_time: Utc::now(),
+            });
+        Ok(membership.to_owned())
+    }
+
+    fn put_member(
+        &self,
+        room_id: &str,
+        user_info: &PubUserInfo,
+        role: RoomRole,
+    ) -> Result<Membership, RepositoryError> {
+        let mut guard = get_members_write_lock(self)?;
+        let membership = guard
+            .entry(room_id.to_owned())
+            .or_default()
+            .entry(user_info.user_id.clone())
+            .and_modify(|membership| membership.role = role)
+            .or_insert_with(|| Membership {
+                room_id: room_id.to_owned(),
+                user_id: user_info.user_id.clone(),
+                user_name: user_info.user_name.clone(),
+                role,
+                joined_time: Utc::now(),
+            });
+        Ok(membership.to_owned())
+    }
+
+    fn is_member(&self, room_id: &str, user_id: &str) -> Result<bool, RepositoryError> {
+        let guard = get_members_read_lock(self)?;
+        let is_member = guard
+            .get(room_id)
+            .is_some_and(|members| members.contains_key(user_id));
+        Ok(is_member)
+    }
+
+    fn get_member(&self, room_id: &str, user_id: &str) -> Result<Membership, RepositoryError> {
+        let guard = get_members_read_lock(self)?;
+        guard
+            .get(room_id)
+            .and_then(|members| members.get(user_id))
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn set_role(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        role: RoomRole,
+    ) -> Result<Membership, RepositoryError> {
+        let mut guard = get_members_write_lock(self)?;
+        let membership = guard
+            .get_mut(room_id)
+            .and_then(|members| members.get_mut(user_id))
+            .ok_or(RepositoryError::NotFound)?;
+        membership.role = role;
+        Ok(membership.to_owned())
+    }
+
+    fn remove_member(&self, room_id: &str, user_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_members_write_lock(self)?;
+        if let Some(members) = guard.get_mut(room_id) {
+            members.remove(user_id);
+        }
+        Ok(())
+    }
+
+    fn get_room_members(&self, room_id: &str) -> Result<Vec<Membership>, RepositoryError> {
+        let guard = get_members_read_lock(self)?;
+        let mut members: Vec<Membership> = guard
+            .get(room_id)
+            .map(|members| members.values().cloned().collect())
+            .unwrap_or_default();
+        members.sort_by_key(|membership| membership.joined_time);
+        Ok(members)
+    }
+
+    fn create_invitation(
+        &self,
+        room_info: &RoomInfo,
+        invitee_id: &str,
+        invited_by: &PubUserInfo,
+    ) -> Result<Invitation, RepositoryError> {
+        let mut guard = get_invitations_write_lock(self)?;
+        let pending = guard.values().find(|invitation| {
+            invitation.room_id == room_info.room_id && invitation.invitee_id == invitee_id
+        });
+        if let Some(invitation) = pending {
+            return Ok(invitation.to_owned());
+        }
+
+        let invitation = Invitation {
+            invitation_id: Uuid::new_v4().to_string(),
+            room_id: room_info.room_id.clone(),
+            room_name: room_info.room_name.clone(),
+            invitee_id: invitee_id.to_owned(),
+            invited_by_id: invited_by.user_id.clone(),
+            invited_by_name: invited_by.user_name.clone(),
+            created_time: Utc::now(),
+        };
+        guard.insert(invitation.invitation_id.clone(), invitation.clone());
+        Ok(invitation)
+    }
+
+    fn get_room_invitations(&self, room_id: &str) -> Result<Vec<Invitation>, RepositoryError> {
+        let guard = get_invitations_read_lock(self)?;
+        let invitations = guard
+            .values()
+            .filter(|invitation| invitation.room_id == room_id)
+            .map(|invitation| invitation.to_owned())
+            .collect();
+        Ok(invitations)
+    }
+
+    fn get_user_invitations(&self, user_id: &str) -> Result<Vec<Invitation>, RepositoryError> {
+        let guard = get_invitations_read_lock(self)?;
+        let invitations = guard
+            .values()
+            .filter(|invitation| invitation.invitee_id == user_id)
+            .map(|invitation| invitation.to_owned())
+            .collect();
+        Ok(invitations)
+    }
+
+    fn take_invitation(
+        &self,
+        invitation_id: &str,
+        invitee_id: &str,
+    ) -> Result<Invitation, RepositoryError> {
+        let mut guard = get_invitations_write_lock(self)?;
+        match guard.get(invitation_id) {
+            Some(invitation) if invitation.invitee_id == invitee_id => {}
+            _ => return Err(RepositoryError::NotFound),
+        }
+        guard.remove(invitation_id).ok_or(RepositoryError::NotFound)
+    }
+
+    fn delete_invitation(&self, room_id: &str, invitation_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_invitations_write_lock(self)?;
+        match guard.get(invitation_id) {
+            Some(invitation) if invitation.room_id == room_id => {}
+            _ => return Err(RepositoryError::NotFound),
+        }
+        guard.remove(invitation_id);
+        Ok(())
+    }
+
+    fn create_invite_link(&self, invite_link: &InviteLink) -> Result<(), RepositoryError> {
+        let mut guard = get_invite_links_write_lock(self)?;
+        guard.insert(invite_link.token.clone(), invite_link.to_owned());
+        Ok(())
+    }
+
+    fn get_invite_link(&self, token: &str) -> Result<InviteLink, RepositoryError> {
+        let guard = get_invite_links_read_lock(self)?;
+        guard.get(token).cloned().ok_or(RepositoryError::NotFound)
+    }
+
+    fn get_room_invite_links(&self, room_id: &str) -> Result<Vec<InviteLink>, RepositoryError> {
+        let guard = get_invite_links_read_lock(self)?;
+        let mut invite_links: Vec<InviteLink> = guard
+            .values()
+            .filter(|invite_link| invite_link.room_id == room_id)
+            .map(|invite_link| invite_link.to_owned())
+            .collect();
+        invite_links.sort_by_key(|invite_link| invite_link.created_time);
+        Ok(invite_links)
+    }
+
+    fn redeem_invite_link(
+        &self,
+        token: &str,
+        user_info: &PubUserInfo,
+        now: DateTime<Utc>,
+    ) -> Result<Membership, RepositoryError> {
+        let mut members = get_members_write_lock(self)?;
+        let mut invite_links = get_invite_links_write_lock(self)?;
+        let invite_link = invite_links
+            .get_mut(token)
+            .ok_or(RepositoryError::NotFound)?;
+        let room_members = members.entry(invite_link.room_id.clone()).or_default();
+        if let Some(membership) = room_members.get(&user_info.user_id) {
+            return Ok(membership.to_owned());
+        }
+        if !invite_link.is_usable(now) {
+            return Err(RepositoryError::NotFound);
+        }
+        invite_link.uses += 1;
+        let membership = Membership {
+            room_id: invite_link.room_id.clone(),
+            user_id: user_info.user_id.clone(),
+            user_name: user_info.user_name.clone(),
+            role: invite_link.role,
+            joined_time: now,
+        };
+        room_members.insert(user_info.user_id.clone(), membership.clone());
+        Ok(membership)
+    }
+
+    fn delete_invite_link(&self, room_id: &str, invite_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_invite_links_write_lock(self)?;
+        let token = guard
+            .values()
+            .find(|invite_link| {
+                invite_link.room_id == room_id && invite_link.invite_id == invite_id
+            })
+            .map(|invite_link| invite_link.token.clone())
+            .ok_or(RepositoryError::NotFound)?;
+        guard.remove(&token);
+        Ok(())
+    }
+
+    fn add_sanction(&self, sanction: &Sanction) -> Result<(), RepositoryError> {
+        let mut guard = get_sanctions_write_lock(self)?;
+        let sanctions = guard.entry(sanction.room_id.clone()).or_default();
+        sanctions.retain(|s| !(s.user_id == sanction.user_id && s.kind == sanction.kind));
+        sanctions.push(sanction.to_owned());
+        Ok(())
+    }
+
+    fn remove_sanction(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        kind: SanctionKind,
+    ) -> Result<(), RepositoryError> {
+        let mut guard = get_sanctions_write_lock(self)?;
+        let sanctions = guard.get_mut(room_id).ok_or(RepositoryError::NotFound)?;
+        let len = sanctions.len();
+        sanctions.retain(|s| !(s.user_id == user_id && s.kind == kind));
+        if sanctions.len() == len {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn get_active_sanction(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        kind: SanctionKind,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Sanction>, RepositoryError> {
+        let guard = get_sanctions_read_lock(self)?;
+        let sanction = guard.get(room_id).and_then(|sanctions| {
+            sanctions
+                .iter()
+                .find(|s| s.user_id == user_id && s.kind == kind && s.is_active(now))
+                .cloned()
+        });
+        Ok(sanction)
+    }
+
+    fn get_room_sanctions(
+        &self,
+        room_id: &str,
+        kind: SanctionKind,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Sanction>, RepositoryError> {
+        let guard = get_sanctions_read_lock(self)?;
+        let sanctions = guard
+            .get(room_id)
+            .map(|sanctions| {
+                sanctions
+                    .iter()
+                    .filter(|s| s.kind == kind && s.is_active(now))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(sanctions)
+    }
+
+    fn remove_expired_sanctions(&self, now: DateTime<Utc>) -> Result<usize, RepositoryError> {
+        let mut guard = get_sanctions_write_lock(self)?;
+        let mut removed = 0;
+        guard.retain(|_, sanctions| {
+            let len = sanctions.len();
+            sanctions.retain(|s| s.is_active(now));
+            removed += len - sanctions.len();
+            !sanctions.is_empty()
+        });
+        Ok(removed)
+    }
+
+    fn create_transfer(&self, transfer: &OwnershipTransfer) -> Result<(), RepositoryError> {
+        let mut guard = get_transfers_write_lock(self)?;
+        guard.insert(transfer.room_id.clone(), transfer.to_owned());
+        Ok(())
+    }
+
+    fn get_transfer(&self, room_id: &str) -> Result<OwnershipTransfer, RepositoryError> {
+        let guard = get_transfers_read_lock(self)?;
+        guard.get(room_id).cloned().ok_or(RepositoryError::NotFound)
+    }
+
+    fn get_user_transfers(&self, user_id: &str) -> Result<Vec<OwnershipTransfer>, RepositoryError> {
+        let guard = get_transfers_read_lock(self)?;
+        let transfers = guard
+            .values()
+            .filter(|transfer| transfer.to_id == user_id)
+            .map(|transfer| transfer.to_owned())
+            .collect();
+        Ok(transfers)
+    }
+
+    fn take_transfer(
+        &self,
+        room_id: &str,
+        to_id: &str,
+    ) -> Result<OwnershipTransfer, RepositoryError> {
+        let mut guard = get_transfers_write_lock(self)?;
+        match guard.get(room_id) {
+            Some(transfer) if transfer.to_id == to_id => {}
+            _ => return Err(RepositoryError::NotFound),
+        }
+        guard.remove(room_id).ok_or(RepositoryError::NotFound)
+    }
+
+    fn delete_transfer(&self, room_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_transfers_write_lock(self)?;
+        guard.remove(room_id).ok_or(RepositoryError::NotFound)?;
+        Ok(())
+    }
+
+    fn append_audit(&self, mut entry: AuditEntry) -> Result<AuditEntry, RepositoryError> {
+        let mut guard = get_audit_log_write_lock(self)?;
+        let entries = guard.entry(entry.room_id.clone()).or_default();
+        entry.seq = entries.len() as u64 + 1;
+        entries.push(entry.clone());
+        Ok(entry)
+    }
+
+    fn get_audit_log(
+        &self,
+        room_id: &str,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let guard = get_audit_log_read_lock(self)?;
+        let Some(entries) = guard.get(room_id) else {
+            return Ok(Vec::new());
+        };
+        // seqは1から連番のため、beforeより前のエントリーは先頭からbefore - 1件
+        let end = before.map_or(entries.len(), |before| {
+            (before.saturating_sub(1) as usize).min(entries.len())
+        });
+        let page = entries[..end].iter().rev().take(limit).cloned().collect();
+        Ok(page)
+    }
+
+    fn delete_room(&self, room_id: &str) -> Result<(), RepositoryError> {
+        get_members_write_lock(self)?.remove(room_id);
+        get_sanctions_write_lock(self)?.remove(room_id);
+        get_invitations_write_lock(self)?.retain(|_, invitation| invitation.room_id != room_id);
+        get_invite_links_write_lock(self)?.retain(|_, invite_link| invite_link.room_id != room_id);
+        get_transfers_write_lock(self)?.remove(room_id);
+        Ok(())
+    }
+
+    fn delete_user(&self, user_id: &str) -> Result<(), RepositoryError> {
+        for members in get_members_write_lock(self)?.values_mut() {
+            members.remove(user_id);
+        }
+        get_invitations_write_lock(self)?.retain(|_, invitation| invitation.invitee_id != user_id);
+        get_transfers_write_lock(self)?
+            .retain(|_, transfer| transfer.from_id != user_id && transfer.to_id != user_id);
+        Ok(())
+    }
+}
+
+fn get_members_write_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, Members>, RepositoryError> {
+    let lock = repo.db.pool.write().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_members_read_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, Members>, RepositoryError> {
+    let lock = repo.db.pool.read().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_invitations_write_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, HashMap<String, Invitation>>, RepositoryError> {
+    let lock = repo
+        .db
+        .invitations
+        .write()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_invitations_read_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, HashMap<String, Invitation>>, RepositoryError> {
+    let lock = repo
+        .db
+        .invitations
+        .read()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_invite_links_write_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, InviteLinks>, RepositoryError> {
+    let lock = repo
+        .db
+        .invite_links
+        .write()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_invite_links_read_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, InviteLinks>, RepositoryError> {
+    let lock = repo
+        .db
+        .invite_links
+        .read()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_sanctions_write_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, Sanctions>, RepositoryError> {
+    let lock = repo
+        .db
+        .sanctions
+        .write()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_sanctions_read_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, Sanctions>, RepositoryError> {
+    let lock = repo
+        .db
+        .sanctions
+        .read()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_transfers_write_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, Transfers>, RepositoryError> {
+    let lock = repo
+        .db
+        .transfers
+        .write()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_transfers_read_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, Transfers>, RepositoryError> {
+    let lock = repo
+        .db
+        .transfers
+        .read()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_audit_log_write_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, AuditLog>, RepositoryError> {
+    let lock = repo
+        .db
+        .audit_log
+        .write()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_audit_log_read_lock(
+    repo: &MembershipRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, AuditLog>, RepositoryError> {
+    let lock = repo
+        .db
+        .audit_log
+        .read()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        domain::entity::audit_entry::AuditAction,
+        infrastructure::repository::room_repository_impl::test_room_info,
+    };
+
+    use super::*;
+
+    fn set_up_repo() -> MembershipRepositoryImpl {
+        MembershipRepositoryImpl::new(MembershipDb::new())
+    }
+
+    fn user_info(user_id: &str) -> PubUserInfo {
+        PubUserInfo {
+            user_id: user_id.to_string(),
+            user_name: "user_name".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_add_member() {
+        let repo = set_up_repo();
+        repo.add_member("room_id", &user_info("user_id"), RoomRole::Member)
+            .unwrap();
+        repo.add_member("room_id", &user_info("user_id"), RoomRole::Member)
+            .unwrap();
+
+        assert!(repo.is_member("room_id", "user_id").unwrap());
+        assert!(!repo.is_member("room_id", "other").unwrap());
+        assert!(!repo.is_member("other_room_id", "user_id").unwrap());
+        assert_eq!(repo.get_room_members("room_id").unwrap().len(), 1);
+
+        let membership = repo
+            .set_role("room_id", "user_id", RoomRole::Moderator)
+            .unwrap();
+        assert_eq!(membership.role, RoomRole::Moderator);
+        let result = repo.set_role("room_id", "other", RoomRole::Moderator);
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+    }
+
+    #[test]
+    fn test_take_invitation() {
+        let repo = set_up_repo();
+        let invitation = repo
+            .create_invitation(&test_room_info("room_id"), "user_id", &user_info("owner"))
+            .unwrap();
+        // 未処理の招待がある間は同じ招待を返す
+        let again = repo
+            .create_invitation(&test_room_info("room_id"), "user_id", &user_info("owner"))
+            .unwrap();
+        assert_eq!(invitation.invitation_id, again.invitation_id);
+        assert_eq!(repo.get_user_invitations("user_id").unwrap().len(), 1);
+
+        // 招待されたユーザー以外は取り出せない
+        let result = repo.take_invitation(&invitation.invitation_id, "other");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+
+        // テスト対象
+        repo.take_invitation(&invitation.invitation_id, "user_id")
+            .unwrap();
+        assert!(repo.get_room_invitations("room_id").unwrap().is_empty());
+        let result = repo.take_invitation(&invitation.invitation_id, "user_id");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+    }
+
+    #[test]
+    fn test_redeem_invite_link() {
+        let repo = set_up_repo();
+        let now = Utc::now();
+        let invite_link = InviteLink {
+            invite_id: "invite_id".to_string(),
+            token: "token".to_string(),
+            room_id: "room_id".to_string(),
+            room_name: "room_name".to_string(),
+            role: RoomRole::Moderator,
+            max_uses: Some(2),
+            uses: 0,
+            expires_time: now + chrono::Duration::hours(1),
+            created_by_id: "owner".to_string(),
+            created_time: now,
+        };
+        repo.create_invite_link(&invite_link).unwrap();
+
+        // テスト対象
+        let membership = repo
+            .redeem_invite_link("token", &user_info("first"), now)
+            .unwrap();
+        assert_eq!(membership.role, RoomRole::Moderator);
+        assert!(repo.is_member("room_id", "first").unwrap());
+        // 既にメンバーの場合は使用回数を消費しない
+        repo.redeem_invite_link("token", &user_info("first"), now)
+            .unwrap();
+        assert_eq!(repo.get_invite_link("token").unwrap().uses, 1);
+        repo.redeem_invite_link("token", &user_info("second"), now)
+            .unwrap();
+        assert_eq!(repo.get_invite_link("token").unwrap().uses, 2);
+        // 上限に達した後は使えない
+        let result = repo.redeem_invite_link("token", &user_info("third"), now);
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+        assert!(!repo.is_member("room_id", "third").unwrap());
+
+        // 期限切れの招待リンクは使えない
+        let unlimited = InviteLink {
+            token: "unlimited".to_string(),
+            invite_id: "unlimited_id".to_string(),
+            max_uses: None,
+            ..invite_link
+        };
+        repo.create_invite_link(&unlimited).unwrap();
+        let result =
+            repo.redeem_invite_link("unlimited", &user_info("third"), unlimited.expires_time);
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+
+        let result = repo.delete_invite_link("other_room_id", "unlimited_id");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+        repo.delete_invite_link("room_id", "unlimited_id").unwrap();
+        assert_eq!(repo.get_room_invite_links("room_id").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_room() {
+        let repo = set_up_repo();
+        repo.add_member("room_id", &user_info("user_id"), RoomRole::Member)
+            .unwrap();
+        repo.create_invitation(&test_room_info("room_id"), "invitee", &user_info("owner"))
+            .unwrap();
+        repo.create_invitation(
+            &test_room_info("other_room_id"),
+            "invitee",
+            &user_info("owner"),
+        )
+        .unwrap();
+
+        // テスト対象
+        repo.delete_room("room_id").unwrap();
+        assert!(!repo.is_member("room_id", "user_id").unwrap());
+        assert!(repo.get_room_invitations("room_id").unwrap().is_empty());
+        assert_eq!(repo.get_user_invitations("invitee").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_take_transfer() {
+        let repo = set_up_repo();
+        let transfer = OwnershipTransfer {
+            room_id: "room_id".to_string(),
+            room_name: "room".to_string(),
+            from_id: "owner".to_string(),
+            from_name: "owner".to_string(),
+            to_id: "user_id".to_string(),
+            to_name: "user_name".to_string(),
+            created_time: Utc::now(),
+        };
+        repo.create_transfer(&transfer).unwrap();
+        // 同じルームの譲渡は置き換わる
+        repo.create_transfer(&OwnershipTransfer {
+            to_id: "other".to_string(),
+            ..transfer.clone()
+        })
+        .unwrap();
+        assert!(repo.get_user_transfers("user_id").unwrap().is_empty());
+        assert_eq!(repo.get_user_transfers("other").unwrap().len(), 1);
+
+        // 譲渡先のユーザー以外は取り出せない
+        let result = repo.take_transfer("room_id", "user_id");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+
+        // テスト対象
+        repo.take_transfer("room_id", "other").unwrap();
+        let result = repo.get_transfer("room_id");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+    }
+
+    #[test]
+    fn test_delete_user() {
+        let repo = set_up_repo();
+        repo.add_member("room_id", &user_info("user_id"), RoomRole::Member)
+            .unwrap();
+        repo.add_member("other_room_id", &user_info("user_id"), RoomRole::Moderator)
+            .unwrap();
+        repo.add_member("room_id", &user_info("other"), RoomRole::Member)
+            .unwrap();
+        repo.create_invitation(
+            &test_room_info("invited_room_id"),
+            "user_id",
+            &user_info("owner"),
+        )
+        .unwrap();
+
+        // テスト対象
+        repo.delete_user("user_id").unwrap();
+        assert!(!repo.is_member("room_id", "user_id").unwrap());
+        assert!(!repo.is_member("other_room_id", "user_id").unwrap());
+        assert!(repo.is_member("room_id", "other").unwrap());
+        assert!(repo.get_user_invitations("user_id").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_active_sanction() {
+        let repo = set_up_repo();
+        let now = Utc::now();
+        let sanction = Sanction {
+            room_id: "room_id".to_string(),
+            user_id: "user_id".to_string(),
+            kind: SanctionKind::Mute,
+            reason: None,
+            issued_by_id: "owner".to_string(),
+            created_time: now,
+            expires_time: Some(now + chrono::Duration::seconds(60)),
+        };
+        repo.add_sanction(&sanction).unwrap();
+
+        // テスト対象
+        let active = repo.get_active_sanction("room_id", "user_id", SanctionKind::Mute, now);
+        assert!(active.unwrap().is_some());
+        let banned = repo.get_active_sanction("room_id", "user_id", SanctionKind::Ban, now);
+        assert!(banned.unwrap().is_none());
+        // 期限を過ぎると無効になる
+        let later = now + chrono::Duration::seconds(60);
+        let expired = repo.get_active_sanction("room_id", "user_id", SanctionKind::Mute, later);
+        assert!(expired.unwrap().is_none());
+
+        // 同じ種類の制裁は置き換わる
+        repo.add_sanction(&Sanction {
+            expires_time: None,
+            ..sanction
+        })
+        .unwrap();
+        let mutes = repo
+            .get_room_sanctions("room_id", SanctionKind::Mute, later)
+            .unwrap();
+        assert_eq!(mutes.len(), 1);
+
+        repo.remove_sanction("room_id", "user_id", SanctionKind::Mute)
+            .unwrap();
+        let result = repo.remove_sanction("room_id", "user_id", SanctionKind::Mute);
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+    }
+
+    #[test]
+    fn test_remove_expired_sanctions() {
+        let repo = set_up_repo();
+        let now = Utc::now();
+        let sanction = Sanction {
+            room_id: "room_id".to_string(),
+            user_id: "user_id".to_string(),
+            kind: SanctionKind::Mute,
+            reason: None,
+            issued_by_id: "owner".to_string(),
+            created_time: now,
+            expires_time: Some(now + chrono::Duration::seconds(60)),
+        };
+        repo.add_sanction(&sanction).unwrap();
+        repo.add_sanction(&Sanction {
+            kind: SanctionKind::Ban,
+            expires_time: None,
+            ..sanction.clone()
+        })
+        .unwrap();
+        repo.add_sanction(&Sanction {
+            room_id: "other_room_id".to_string(),
+            ..sanction
+        })
+        .unwrap();
+
+        // テスト対象
+        assert_eq!(repo.remove_expired_sanctions(now).unwrap(), 0);
+        let later = now + chrono::Duration::seconds(60);
+        assert_eq!(repo.remove_expired_sanctions(later).unwrap(), 2);
+        // 期限のない制裁は残り、空になったルームの記録は消える
+        let bans = repo
+            .get_room_sanctions("room_id", SanctionKind::Ban, later)
+            .unwrap();
+        assert_eq!(bans.len(), 1);
+        assert!(!repo
+            .db
+            .sanctions
+            .read()
+            .unwrap()
+            .contains_key("other_room_id"));
+    }
+
+    #[test]
+    fn test_audit_log() {
+        let repo = set_up_repo();
+        for i in 0..5 {
+            let entry = repo
+                .append_audit(AuditEntry {
+                    seq: 0,
+                    room_id: "room_id".to_string(),
+                    action: AuditAction::MemberKicked,
+                    actor_id: "owner".to_string(),
+                    actor_name: "owner".to_string(),
+                    target_id: Some(format!("user{}", i)),
+                    detail: None,
+                    time: Utc::now(),
+                })
+                .unwrap();
+            assert_eq!(entry.seq, i + 1);
+        }
+
+        // テスト対象
+        // 新しい順に返り、beforeで続きを読める
+        let seqs = |entries: Vec<AuditEntry>| -> Vec<u64> {
+            entries.into_iter().map(|entry| entry.seq).collect()
+        };
+        let page = repo.get_audit_log("room_id", None, 2).unwrap();
+        assert_eq!(seqs(page), vec![5, 4]);
+        let page = repo.get_audit_log("room_id", Some(4), 2).unwrap();
+        assert_eq!(seqs(page), vec![3, 2]);
+        let page = repo.get_audit_log("room_id", Some(2), 2).unwrap();
+        assert_eq!(seqs(page), vec![1]);
+        assert!(repo
+            .get_audit_log("room_id", Some(1), 2)
+            .unwrap()
+            .is_empty());
+        assert!(repo.get_audit_log("other", None, 2).unwrap().is_empty());
+
+        // ルームを削除しても監査ログは残る
+        repo.delete_room("room_id").unwrap();
+        let page = repo.get_audit_log("room_id", None, 2).unwrap();
+        assert_eq!(seqs(page), vec![5, 4]);
+    }
+}
@@ -0,0 +1,505 @@
+use std::{
+    collections::HashMap,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    domain::{
+        entity::{
+            chat::Chat,
+            message_cursor::MessageCursor,
+            retention_purge::RetentionPurge,
+            submission::{Submission, SubmissionKey},
+        },
+        repository::{error::RepositoryError, message_repository::MessageRepository},
+    },
+    MessageDb,
+};
+
+pub struct MessageRepositoryImpl {
+    db: MessageDb,
+}
+
+impl MessageRepositoryImpl {
+    pub fn new(db: MessageDb) -> Self {
+        Self { db }
+    }
+}
+
+impl MessageRepository for MessageRepositoryImpl {
+    fn save_message(&self, room_id: &str, chat: &Chat) -> Result<(), RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let messages = guard.entry(room_id.to_owned()).or_default();
+        // ページングで二分探索できるよう(time, message_id)順に保つ。インポート以外は末尾に追加される
+        let index = messages.partition_point(|saved| sort_key(saved) <= sort_key(chat));
+        messages.insert(index, chat.to_owned());
+        Ok(())
+    }
+
+    fn save_message_once(
+        &self,
+        key: &SubmissionKey,
+        chat: &Chat,
+        window: Duration,
+    ) -> Result<Option<Submission>, RepositoryError> {
+        // 同時に再送された場合に二重に保存しないよう、判定から保存までロックを持つ
+        let mut submissions = self
+            .db
+            .submissions
+            .write()
+            .map_err(|_| RepositoryError::DbError)?;
+        if let Some(submission) = submissions.get(key) {
+            if submission.dedupe_until > chat.time {
+                return Ok(Some(submission.to_owned()));
+            }
+        }
+
+        self.save_message(&key.room_id, chat)?;
+        submissions.insert(
+            key.to_owned(),
+            Submission {
+                message_id: chat.message_id.to_owned(),
+                time: chat.time,
+                dedupe_until: chat.time + window,
+            },
+        );
+        Ok(None)
+    }
+
+    fn discard_submission(
+        &self,
+        key: &SubmissionKey,
+        message_id: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut submissions = self
+            .db
+            .submissions
+            .write()
+            .map_err(|_| RepositoryError::DbError)?;
+        if submissions
+            .get(key)
+            .is_some_and(|submission| submission.message_id == message_id)
+        {
+            submissions.remove(key);
+        }
+        self.delete_message(&key.room_id, message_id)?;
+        Ok(())
+    }
+
+    fn get_messages(&self, room_id: &str) -> Result<Vec<Chat>, RepositoryError> {
+        let now = Utc::now();
+        let guard = get_read_lock(self)?;
+        // スイーパーが未実行でも期限切れのメッセージは返さない
+        let messages = guard
+            .get(room_id)
+            .map(|messages| {
+                messages
+                    .iter()
+                    .filter(|chat| !chat.is_expired(now))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(messages)
+    }
+
+    fn get_messages_page(
+        &self,
+        room_id: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after: Option<&MessageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let now = Utc::now();
+        let guard = get_read_lock(self)?;
+        let Some(messages) = guard.get(room_id) else {
+            return Ok(Vec::new());
+        };
+
+        let from = from.map_or(0, |from| messages.partition_point(|chat| chat.time < from));
+        let after = after.map_or(0, |after| {
+            messages.partition_point(|chat| sort_key(chat) <= (after.time, &after.message_id))
+        });
+        let page = messages[from.max(after)..]
+            .iter()
+            .take_while(|chat| to.is_none_or(|to| chat.time <= to))
+            .filter(|chat| !chat.is_expired(now))
+            .take(limit)
+            .cloned()
+            .collect();
+        Ok(page)
+    }
+
+    fn last_message_time(
+        &self,
+        room_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+        let guard = get_read_lock(self)?;
+        let time = guard.get(room_id).and_then(|messages| {
+            let end = messages.partition_point(|chat| chat.time <= at);
+            end.checked_sub(1).map(|last| messages[last].time)
+        });
+        Ok(time)
+    }
+
+    fn remove_expired(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<HashMap<String, Vec<String>>, RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let mut removed: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (room_id, messages) in guard.iter_mut() {
+            messages.retain(|chat| {
+                if chat.is_expired(now) {
+                    removed
+                        .entry(room_id.to_owned())
+                        .or_default()
+                        .push(chat.message_id.to_owned());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        Ok(removed)
+    }
+
+    fn remove_outside_retention(
+        &self,
+        room_id: &str,
+        older_than: Option<DateTime<Utc>>,
+        keep_last: Option<usize>,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let Some(messages) = guard.get_mut(room_id) else {
+            return Ok(Vec::new());
+        };
+
+        let over_count = keep_last.map_or(0, |keep_last| messages.len().saturating_sub(keep_last));
+        let too_old = older_than.map_or(0, |older_than| {
+            messages.partition_point(|chat| chat.time < older_than)
+        });
+        let removed = messages.drain(..over_count.max(too_old)).collect();
+        Ok(removed)
+    }
+
+    fn save_purge(&self, purge: &RetentionPurge) -> Result<(), RepositoryError> {
+        let mut purges = self
+            .db
+            .purges
+            .write()
+            .map_err(|_| RepositoryError::DbError)?;
+        purges
+            .entry(purge.room_id.to_owned())
+            .or_default()
+            .push(purge.to_owned());
+        Ok(())
+    }
+
+    fn get_purges(&self, room_id: &str) -> Result<Vec<RetentionPurge>, RepositoryError> {
+        let purges = self
+            .db
+            .purges
+            .read()
+            .map_err(|_| RepositoryError::DbError)?;
+        Ok(purges.get(room_id).cloned().unwrap_or_default())
+    }
+
+    fn remove_expired_submissions(&self, now: DateTime<Utc>) -> Result<usize, RepositoryError> {
+        let mut submissions = self
+            .db
+            .submissions
+            .write()
+            .map_err(|_| RepositoryError::DbError)?;
+        let before = submissions.len();
+        submissions.retain(|_, submission| submission.dedupe_until > now);
+        Ok(before - submissions.len())
+    }
+
+    fn delete_message(&self, room_id: &str, message_id: &str) -> Result<Chat, RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        let messages = guard.get_mut(room_id).ok_or(RepositoryError::NotFound)?;
+        let index = messages
+            .iter()
+            .position(|chat| chat.message_id == message_id)
+            .ok_or(RepositoryError::NotFound)?;
+        Ok(messages.remove(index))
+    }
+
+    fn delete_room_messages(&self, room_id: &str) -> Result<(), RepositoryError> {
+        let mut guard = get_write_lock(self)?;
+        guard.remove(room_id);
+        self.db
+            .purges
+            .write()
+            .map_err(|_| RepositoryError::DbError)?
+            .remove(room_id);
+        Ok(())
+    }
+}
+
+fn sort_key(chat: &Chat) -> (DateTime<Utc>, &String) {
+    (chat.time, &chat.message_id)
+}
+
+fn get_write_lock(
+    repo: &MessageRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, HashMap<String, Vec<Chat>>>, RepositoryError> {
+    let lock = repo.db.pool.write().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_read_lock(
+    repo: &MessageRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, HashMap<String, Vec<Chat>>>, RepositoryError> {
+    let lock = repo.db.pool.read().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn set_up_repo() -> MessageRepositoryImpl {
+        MessageRepositoryImpl::new(MessageDb::new())
+    }
+
+    #[test]
+    fn test_save_and_get_messages() {
+        let repo = set_up_repo();
+        let chat = Chat::from_str("user_id", "user_name", "hello");
+
+        repo.save_message("room_id", &chat).unwrap();
+
+        let messages = repo.get_messages("room_id").unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message_id, chat.message_id);
+    }
+
+    #[test]
+    fn test_get_messages_page() {
+        let repo = set_up_repo();
+        let chats: Vec<Chat> = (0..5)
+            .map(|i| Chat::from_str("user_id", "user_name", &format!("message{}", i)))
+            .collect();
+        for chat in &chats {
+            repo.save_message("room_id", chat).unwrap();
+        }
+
+        // テスト対象
+        // カーソルで読み進めると全件が重複なく順に返る
+        let mut cursor = None;
+        let mut texts = Vec::new();
+        loop {
+            let page = repo
+                .get_messages_page("room_id", None, None, cursor.as_ref(), 2)
+                .unwrap();
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = Some(MessageCursor::from_chat(last));
+            texts.extend(page.into_iter().map(|chat| chat.text));
+        }
+        assert_eq!(texts.len(), 5);
+        for i in 0..5 {
+            assert!(texts.contains(&format!("message{}", i)));
+        }
+
+        // 後から保存した過去のメッセージも時刻順に返る
+        let mut imported = Chat::from_str("user_id", "user_name", "imported");
+        imported.time = chats[0].time - Duration::seconds(10);
+        repo.save_message("room_id", &imported).unwrap();
+        let page = repo
+            .get_messages_page("room_id", None, None, None, 2)
+            .unwrap();
+        assert_eq!(page[0].text, "imported");
+        let cursor = MessageCursor::from_chat(&page[1]);
+        let rest = repo
+            .get_messages_page("room_id", None, None, Some(&cursor), 10)
+            .unwrap();
+        assert_eq!(rest.len(), 4);
+        assert!(rest
+            .iter()
+            .all(|chat| MessageCursor::from_chat(chat) > cursor));
+
+        // 範囲外のメッセージは返らない
+        let future = Utc::now() + Duration::seconds(10);
+        let page = repo
+            .get_messages_page("room_id", Some(future), None, None, 10)
+            .unwrap();
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn test_last_message_time() {
+        let repo = set_up_repo();
+        let mut first = Chat::from_str("user_id", "user_name", "first");
+        let mut second = Chat::from_str("user_id", "user_name", "second");
+        let now = Utc::now();
+        first.time = now - Duration::seconds(20);
+        second.time = now - Duration::seconds(10);
+        repo.save_message("room_id", &second).unwrap();
+        repo.save_message("room_id", &first).unwrap();
+
+        // テスト対象
+        let time = repo.last_message_time("room_id", now).unwrap();
+        assert_eq!(time, Some(second.time));
+        let time = repo
+            .last_message_time("room_id", now - Duration::seconds(15))
+            .unwrap();
+        assert_eq!(time, Some(first.time));
+        let time = repo
+            .last_message_time("room_id", now - Duration::seconds(30))
+            .unwrap();
+        assert_eq!(time, None);
+        assert_eq!(repo.last_message_time("other_id", now).unwrap(), None);
+    }
+
+    #[test]
+    fn test_remove_expired() {
+        let repo = set_up_repo();
+        let chat = Chat::from_str("user_id", "user_name", "hello");
+        let ephemeral =
+            Chat::from_str("user_id", "user_name", "secret").expire_in(Duration::seconds(1));
+        repo.save_message("room_id", &chat).unwrap();
+        repo.save_message("room_id", &ephemeral).unwrap();
+
+        // 期限前は削除されない
+        let removed = repo.remove_expired(Utc::now()).unwrap();
+        assert!(removed.is_empty());
+
+        // テスト対象
+        let removed = repo
+            .remove_expired(Utc::now() + Duration::seconds(2))
+            .unwrap();
+        assert_eq!(removed["room_id"], vec![ephemeral.message_id]);
+
+        let messages = repo.get_messages("room_id").unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message_id, chat.message_id);
+    }
+
+    #[test]
+    fn test_remove_outside_retention() {
+        let repo = set_up_repo();
+        let now = Utc::now();
+        let chats: Vec<Chat> = (0..5)
+            .map(|i| {
+                let mut chat = Chat::from_str("user_id", "user_name", &format!("message{}", i));
+                chat.time = now - Duration::days(5 - i);
+                chat
+            })
+            .collect();
+        for chat in chats.iter().rev() {
+            repo.save_message("room_id", chat).unwrap();
+        }
+
+        // テスト対象
+        // 3日より前の2件を削除する
+        let removed = repo
+            .remove_outside_retention("room_id", Some(now - Duration::days(3)), None)
+            .unwrap();
+        let texts: Vec<&str> = removed.iter().map(|chat| chat.text.as_str()).collect();
+        assert_eq!(texts, vec!["message0", "message1"]);
+        // 日数と件数のうち多く削除する方を適用する
+        let removed = repo
+            .remove_outside_retention("room_id", Some(now - Duration::days(3)), Some(1))
+            .unwrap();
+        assert_eq!(removed.len(), 2);
+        let messages = repo.get_messages("room_id").unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].text, "message4");
+        assert!(repo
+            .remove_outside_retention("other", None, Some(0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_delete_room_messages() {
+        let repo = set_up_repo();
+        let chat = Chat::from_str("user_id", "user_name", "hello");
+        repo.save_message("room_id", &chat).unwrap();
+
+        // テスト対象
+        let result = repo.delete_message("room_id", "unknown");
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+        let deleted = repo.delete_message("room_id", &chat.message_id).unwrap();
+        assert_eq!(deleted.text, "hello");
+        assert!(repo.get_messages("room_id").unwrap().is_empty());
+
+        repo.save_message("room_id", &chat).unwrap();
+        repo.delete_room_messages("room_id").unwrap();
+
+        assert!(repo.get_messages("room_id").unwrap().is_empty());
+    }
+
+    fn submission_key(client_msg_id: &str) -> SubmissionKey {
+        SubmissionKey {
+            room_id: "room_id".to_string(),
+            user_id: "user_id".to_string(),
+            client_msg_id: client_msg_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_message_once() {
+        let repo = set_up_repo();
+        let window = Duration::minutes(10);
+        let chat = Chat::from_str("user_id", "user_name", "hello");
+        let retried = Chat::from_str("user_id", "user_name", "hello");
+
+        assert!(repo
+            .save_message_once(&submission_key("c1"), &chat, window)
+            .unwrap()
+            .is_none());
+        // 再送されたメッセージは保存されず、最初のメッセージが返る
+        let duplicated = repo
+            .save_message_once(&submission_key("c1"), &retried, window)
+            .unwrap()
+            .unwrap();
+        assert_eq!(duplicated.message_id, chat.message_id);
+        // 別のIDであれば保存される
+        assert!(repo
+            .save_message_once(&submission_key("c2"), &retried, window)
+            .unwrap()
+            .is_none());
+        assert_eq!(repo.get_messages("room_id").unwrap().len(), 2);
+
+        // 取り消すとメッセージが消え、同じIDで再送できる
+        repo.discard_submission(&submission_key("c1"), &chat.message_id)
+            .unwrap();
+        assert_eq!(repo.get_messages("room_id").unwrap().len(), 1);
+        let resent = Chat::from_str("user_id", "user_name", "hello");
+        assert!(repo
+            .save_message_once(&submission_key("c1"), &resent, window)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_remove_expired_submissions() {
+        let repo = set_up_repo();
+        let chat = Chat::from_str("user_id", "user_name", "hello");
+        repo.save_message_once(&submission_key("c1"), &chat, Duration::minutes(10))
+            .unwrap();
+
+        assert_eq!(repo.remove_expired_submissions(Utc::now()).unwrap(), 0);
+        let later = Utc::now() + Duration::minutes(11);
+        assert_eq!(repo.remove_expired_submissions(later).unwrap(), 1);
+
+        // 期間が過ぎた後は同じIDでも新しいメッセージとして保存される
+        let retried = Chat::from_str("user_id", "user_name", "hello");
+        assert!(repo
+            .save_message_once(&submission_key("c1"), &retried, Duration::minutes(10))
+            .unwrap()
+            .is_none());
+    }
+}
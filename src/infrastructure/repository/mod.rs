@@ -1,2 +1,7 @@
+pub mod favorite_repository_impl;
+pub mod incoming_hook_repository_impl;
+pub mod membership_repository_impl;
+pub mod message_repository_impl;
+pub mod outgoing_hook_repository_impl;
 pub mod room_repository_impl;
 pub mod user_repository_impl;
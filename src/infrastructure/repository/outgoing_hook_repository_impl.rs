@@ -0,0 +1,253 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        entity::{
+            hook_delivery::HookDelivery, hook_event::HookEventKind, outgoing_hook::OutgoingHook,
+        },
+        repository::{error::RepositoryError, outgoing_hook_repository::OutgoingHookRepository},
+    },
+    OutgoingHookDb,
+};
+
+// フックごとに保持する配送ログの最大件数
+const MAX_DELIVERY_LOGS: usize = 50;
+
+pub struct OutgoingHookRepositoryImpl {
+    db: OutgoingHookDb,
+}
+
+impl OutgoingHookRepositoryImpl {
+    pub fn new(db: OutgoingHookDb) -> Self {
+        Self { db }
+    }
+}
+
+impl OutgoingHookRepository for OutgoingHookRepositoryImpl {
+    fn create_hook(
+        &self,
+        room_id: &str,
+        callback_url: &str,
+        secret: &str,
+        events: &[HookEventKind],
+        created_by_id: &str,
+    ) -> Result<OutgoingHook, RepositoryError> {
+        let hook = OutgoingHook {
+            hook_id: Uuid::new_v4().to_string(),
+            room_id: room_id.to_owned(),
+            callback_url: callback_url.to_owned(),
+            secret: secret.to_owned(),
+            events: events.to_vec(),
+            created_by_id: created_by_id.to_owned(),
+            created_time: Utc::now(),
+        };
+
+        let mut guard = get_write_lock(self)?;
+        guard.insert(hook.hook_id.clone(), hook.clone());
+        Ok(hook)
+    }
+
+    fn get_room_hooks(&self, room_id: &str) -> Result<Vec<OutgoingHook>, RepositoryError> {
+        let guard = get_read_lock(self)?;
+        let hooks = guard
+            .values()
+            .filter(|hook| hook.room_id == room_id)
+            .map(|hook| hook.to_owned())
+            .collect();
+        Ok(hooks)
+    }
+
+    fn get_subscribed_hooks(
+        &self,
+        room_id: &str,
+        kind: HookEventKind,
+    ) -> Result<Vec<OutgoingHook>, RepositoryError> {
+        let guard = get_read_lock(self)?;
+        let hooks = guard
+            .values()
+            .filter(|hook| hook.room_id == room_id && hook.is_subscribed(kind))
+            .map(|hook| hook.to_owned())
+            .collect();
+        Ok(hooks)
+    }
+
+    fn delete_hook(&self, room_id: &str, hook_id: &str) -> Result<(), RepositoryError> {
+        {
+            let mut guard = get_write_lock(self)?;
+            match guard.get(hook_id) {
+                Some(hook) if hook.room_id == room_id => guard.remove(hook_id),
+                _ => return Err(RepositoryError::NotFound),
+            };
+        }
+        get_deliveries_write_lock(self)?.remove(hook_id);
+        Ok(())
+    }
+
+    fn delete_room_hooks(&self, room_id: &str) -> Result<(), RepositoryError> {
+        let removed: Vec<String> = {
+            let mut guard = get_write_lock(self)?;
+            let removed = guard
+                .values()
+                .filter(|hook| hook.room_id == room_id)
+                .map(|hook| hook.hook_id.to_owned())
+                .collect();
+            guard.retain(|_, hook| hook.room_id != room_id);
+            removed
+        };
+
+        let mut deliveries = get_deliveries_write_lock(self)?;
+        for hook_id in removed {
+            deliveries.remove(&hook_id);
+        }
+        Ok(())
+    }
+
+    fn save_delivery(&self, delivery: &HookDelivery) -> Result<(), RepositoryError> {
+        // 配送中にフックが削除された場合はログを残さない
+        if !get_read_lock(self)?.contains_key(&delivery.hook_id) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        let mut guard = get_deliveries_write_lock(self)?;
+        let logs = guard.entry(delivery.hook_id.to_owned()).or_default();
+        match logs
+            .iter_mut()
+            .find(|log| log.delivery_id == delivery.delivery_id)
+        {
+            Some(log) => *log = delivery.to_owned(),
+            None => {
+                logs.push_back(delivery.to_owned());
+                if logs.len() > MAX_DELIVERY_LOGS {
+                    logs.pop_front();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_deliveries(&self, hook_id: &str) -> Result<Vec<HookDelivery>, RepositoryError> {
+        let guard = self
+            .db
+            .deliveries
+            .read()
+            .map_err(|_| RepositoryError::DbError)?;
+        let deliveries = guard
+            .get(hook_id)
+            .map(|logs| logs.iter().cloned().collect())
+            .unwrap_or_default();
+        Ok(deliveries)
+    }
+}
+
+fn get_write_lock(
+    repo: &OutgoingHookRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, HashMap<String, OutgoingHook>>, RepositoryError> {
+    let lock = repo.db.pool.write().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_read_lock(
+    repo: &OutgoingHookRepositoryImpl,
+) -> Result<RwLockReadGuard<'_, HashMap<String, OutgoingHook>>, RepositoryError> {
+    let lock = repo.db.pool.read().map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+fn get_deliveries_write_lock(
+    repo: &OutgoingHookRepositoryImpl,
+) -> Result<RwLockWriteGuard<'_, HashMap<String, VecDeque<HookDelivery>>>, RepositoryError> {
+    let lock = repo
+        .db
+        .deliveries
+        .write()
+        .map_err(|_| RepositoryError::DbError)?;
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set_up_repo() -> OutgoingHookRepositoryImpl {
+        OutgoingHookRepositoryImpl::new(OutgoingHookDb::new())
+    }
+
+    fn gen_delivery(hook_id: &str, delivery_id: &str) -> HookDelivery {
+        HookDelivery {
+            delivery_id: delivery_id.to_owned(),
+            hook_id: hook_id.to_owned(),
+            event: HookEventKind::MessagePosted,
+            attempts: 1,
+            success: false,
+            status_code: Some(500),
+            last_error: None,
+            created_time: Utc::now(),
+            updated_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_get_subscribed_hooks() {
+        let repo = set_up_repo();
+        let hook = repo
+            .create_hook(
+                "room_id",
+                "http://localhost/callback",
+                "secret",
+                &[HookEventKind::MessagePosted],
+                "user_id",
+            )
+            .unwrap();
+
+        // テスト対象
+        let hooks = repo
+            .get_subscribed_hooks("room_id", HookEventKind::MessagePosted)
+            .unwrap();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].hook_id, hook.hook_id);
+
+        let hooks = repo
+            .get_subscribed_hooks("room_id", HookEventKind::RoomDeleted)
+            .unwrap();
+        assert!(hooks.is_empty());
+    }
+
+    #[test]
+    fn test_save_delivery() {
+        let repo = set_up_repo();
+        let hook = repo
+            .create_hook(
+                "room_id",
+                "http://localhost/callback",
+                "secret",
+                &[HookEventKind::MessagePosted],
+                "user_id",
+            )
+            .unwrap();
+        let mut delivery = gen_delivery(&hook.hook_id, "delivery_id");
+        repo.save_delivery(&delivery).unwrap();
+
+        // テスト対象
+        // 同じ配送のリトライはログを上書きする
+        delivery.attempts = 2;
+        delivery.success = true;
+        repo.save_delivery(&delivery).unwrap();
+
+        let deliveries = repo.get_deliveries(&hook.hook_id).unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert!(deliveries[0].success);
+
+        // 削除済みのフックにはログを残さない
+        repo.delete_hook("room_id", &hook.hook_id).unwrap();
+        let result = repo.save_delivery(&gen_delivery(&hook.hook_id, "other_id"));
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+        assert!(repo.get_deliveries(&hook.hook_id).unwrap().is_empty());
+    }
+}
@@ -1,20 +1,34 @@
-use std::{
-    collections::HashMap,
-    sync::{RwLockReadGuard, RwLockWriteGuard},
-};
+use std::{future::Future, pin::Pin};
 
-use chrono::Utc;
-use tokio::sync::broadcast;
+use chrono::{DateTime, Duration, Utc};
+use rand_core::{OsRng, RngCore};
+use tokio::sync::mpsc::UnboundedSender;
 use uuid::Uuid;
 
 use crate::{
     domain::{
-        entity::{pub_user_info::PubUserInfo, room::Room, room_info::RoomInfo},
+        entity::{
+            chat::Chat,
+            create_room::CreateRoom,
+            post_policy::PostPolicy,
+            post_rejection::PostRejection,
+            pub_user_info::PubUserInfo,
+            room::{Admission, Connection, Resume, Room},
+            room_event::RoomEvent,
+            room_info::RoomInfo,
+            room_tag::{normalize_category, normalize_tags},
+            update_room::UpdateRoom,
+        },
         repository::{error::RepositoryError, room_repository::RoomRepository},
     },
     RoomDb,
 };
 
+// 読み間違えやすいI, Oを除いた英字
+const JOIN_CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+// 参加コードが重複した場合に生成し直す回数の上限
+const MAX_JOIN_CODE_ATTEMPTS: usize = 10;
+
 pub struct RoomRepositoryImpl {
     db: RoomDb,
 }
@@ -23,90 +37,1231 @@ impl RoomRepositoryImpl {
     pub fn new(db: RoomDb) -> Self {
         Self { db }
     }
-}
 
-impl RoomRepository for RoomRepositoryImpl {
-    fn open_new_room(
+    // 削除済みのルームを除いた一覧
+    async fn list_rooms(&self) -> Result<Vec<RoomInfo>, RepositoryError> {
+        let rooms = self.db.supervisor.list().await?;
+        Ok(rooms
+            .into_iter()
+            .filter(|room_info| room_info.deleted_time.is_none())
+            .collect())
+    }
+
+    // ルーム情報を変更して、変更後のルーム情報をブロードキャストする
+    async fn modify_room(
         &self,
-        room_name: &str,
-        user_info: &PubUserInfo,
+        room_id: &str,
+        f: impl FnOnce(&mut RoomInfo) + Send + 'static,
     ) -> Result<RoomInfo, RepositoryError> {
-        let room = init_room(room_name, &user_info.user_id, &user_info.user_name);
+        self.db
+            .supervisor
+            .call(room_id, move |room| {
+                f(&mut room.room_info);
+                let room_info = room.room_info.clone();
+                room.publish(&RoomEvent::RoomUpdated(Box::new((&room_info).into())))
+                    .map(|_| room_info)
+            })
+            .await?
+            .map_err(|_| RepositoryError::DbError)
+    }
+}
 
-        {
-            let mut guard = get_write_lock(&self).map_err(|e| e)?;
-            guard.insert(room.room_info.room_id.clone(), room.clone());
-        }
-        Ok(room.room_info.to_owned())
+impl RoomRepository for RoomRepositoryImpl {
+    fn open_new_room<'a>(
+        &'a self,
+        payload: &'a CreateRoom,
+        user_info: &'a PubUserInfo,
+        join_password_hash: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            for _ in 0..MAX_JOIN_CODE_ATTEMPTS {
+                let room = init_room(payload, user_info, join_password_hash);
+                let room_info = room.room_info.clone();
+                if self.db.supervisor.open(room).await? {
+                    return Ok(room_info);
+                }
+            }
+            Err(RepositoryError::DbError)
+        })
     }
 
-    fn listen_room(&self, room_id: &str) -> Result<Room, RepositoryError> {
-        let room = get_read_lock(&self).and_then(|guard| {
-            guard
-                .get(room_id)
-                .map(|r| r.to_owned())
-                .ok_or_else(|| RepositoryError::NotFound)
-        })?;
-        Ok(room)
+    fn get_room_info<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            let room_info = self.db.supervisor.info(room_id).await?;
+            if room_info.deleted_time.is_some() {
+                return Err(RepositoryError::NotFound);
+            }
+            Ok(room_info)
+        })
     }
 
-    fn get_owner_rooms(&self, owner_id: &str) -> Result<Vec<RoomInfo>, RepositoryError> {
-        let lock = get_read_lock(&self).map_err(|e| e)?;
+    fn get_room_by_join_code<'a>(
+        &'a self,
+        join_code: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            let join_code = normalize_join_code(join_code).ok_or(RepositoryError::NotFound)?;
+            let rooms = self.list_rooms().await?;
+            rooms
+                .into_iter()
+                .find(|room_info| room_info.join_code == join_code)
+                .ok_or(RepositoryError::NotFound)
+        })
+    }
 
-        let owner_rooms = lock
-            .iter()
-            .filter(|(_, room)| room.room_info.created_by_id == owner_id)
-            .map(|(_, b)| b.room_info.to_owned())
-            .collect();
-        Ok(owner_rooms)
+    fn get_owner_rooms<'a>(
+        &'a self,
+        owner_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<RoomInfo>, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            let rooms = self.list_rooms().await?;
+            let owner_rooms = rooms
+                .into_iter()
+                .filter(|room_info| room_info.created_by_id == owner_id)
+                .collect();
+            Ok(owner_rooms)
+        })
     }
 
-    fn get_all_room(&self) -> Result<Vec<RoomInfo>, RepositoryError> {
-        let lock = get_read_lock(&self).map_err(|e| e)?;
-        let rooms: Vec<RoomInfo> = lock
-            .iter()
-            .map(|(_, room)| room.room_info.to_owned())
-            .collect();
-        Ok(rooms)
+    fn get_all_room<'a>(
+        &'a self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<RoomInfo>, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.list_rooms())
+    }
+
+    fn update_room<'a>(
+        &'a self,
+        room_id: &'a str,
+        payload: UpdateRoom,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            self.db
+                .supervisor
+                .call(room_id, move |room| {
+                    let room_info = &mut room.room_info;
+                    if let Some(room_name) = payload.room_name {
+                        room_info.room_name = room_name;
+                    }
+                    if let Some(description) = payload.description {
+                        room_info.description = non_empty(Some(&description));
+                    }
+                    if let Some(topic) = payload.topic {
+                        room_info.topic = non_empty(Some(&topic));
+                    }
+                    if let Some(capacity) = payload.capacity {
+                        room_info.capacity = Some(capacity).filter(|capacity| *capacity > 0);
+                    }
+                    if let Some(overflow) = payload.overflow {
+                        room_info.overflow = overflow;
+                    }
+                    if let Some(idle_ttl_secs) = payload.idle_ttl_secs {
+                        room_info.idle_ttl_secs = Some(idle_ttl_secs).filter(|secs| *secs > 0);
+                    }
+                    if let Some(keep_forever) = payload.keep_forever {
+                        room_info.keep_forever = keep_forever;
+                    }
+                    if let Some(announcement_only) = payload.announcement_only {
+                        room_info.announcement_only = announcement_only;
+                    }
+                    if let Some(retention) = payload.retention {
+                        room_info.retention = retention;
+                    }
+                    if let Some(tags) = payload.tags {
+                        room_info.tags = normalize_tags(&tags);
+                    }
+                    if let Some(category) = payload.category {
+                        room_info.category = normalize_category(&category);
+                    }
+                    let previous_slow_mode_secs = room_info.slow_mode_secs;
+                    if let Some(slow_mode_secs) = payload.slow_mode_secs {
+                        room_info.slow_mode_secs = Some(slow_mode_secs).filter(|secs| *secs > 0);
+                    }
+                    // オーナーが設定を変更したルームは削除を取り消す
+                    room_info.expires_time = None;
+                    room_info.updated_time = Utc::now();
+                    // 定員を増やした場合は空いた席を観覧者に回す
+                    room.promote_spectators();
+
+                    // 再接続したクライアントにも再送されるように履歴に残す
+                    let room_info = room.room_info.clone();
+                    room.publish(&RoomEvent::RoomUpdated(Box::new((&room_info).into())))?;
+                    if room_info.slow_mode_secs != previous_slow_mode_secs {
+                        room.publish(&RoomEvent::SlowMode {
+                            seconds: room_info.slow_mode_secs,
+                        })?;
+                    }
+                    Ok::<_, serde_json::Error>(room_info)
+                })
+                .await?
+                .map_err(|_| RepositoryError::DbError)
+        })
     }
 
-    fn delete_room(&self, room_id: &str) -> Result<(), RepositoryError> {
-        let _ = get_write_lock(&self).and_then(|mut guard| {
-            guard
-                .remove(room_id)
-                .ok_or_else(|| RepositoryError::NotFound)
-        });
-        Ok(())
+    fn transfer_room<'a>(
+        &'a self,
+        room_id: &'a str,
+        new_owner: &'a PubUserInfo,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        let new_owner = new_owner.clone();
+        Box::pin(self.modify_room(room_id, |room_info| {
+            room_info.created_by_id = new_owner.user_id;
+            room_info.created_by_name = new_owner.user_name;
+            room_info.orphaned_time = None;
+        }))
+    }
+
+    fn orphan_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.modify_room(room_id, |room_info| {
+            let now = Utc::now();
+            room_info.archived_time.get_or_insert(now);
+            room_info.orphaned_time.get_or_insert(now);
+        }))
+    }
+
+    fn archive_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.modify_room(room_id, |room_info| {
+            room_info.archived_time.get_or_insert_with(Utc::now);
+        }))
+    }
+
+    fn unarchive_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.modify_room(room_id, |room_info| {
+            room_info.archived_time = None;
+        }))
+    }
+
+    fn set_expires_time<'a>(
+        &'a self,
+        room_id: &'a str,
+        expires_time: DateTime<Utc>,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.db.supervisor.call(room_id, move |room| {
+            room.room_info.expires_time = Some(expires_time);
+            room.room_info.clone()
+        }))
+    }
+
+    fn soft_delete_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.db.supervisor.call(room_id, |room| {
+            room.room_info.deleted_time.get_or_insert_with(Utc::now);
+            room.disconnect_all(&RoomEvent::RoomDeleted);
+            room.room_info.clone()
+        }))
+    }
+
+    fn restore_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<RoomInfo, RepositoryError>> + Send + 'a>> {
+        Box::pin(self.db.supervisor.call(room_id, |room| {
+            room.room_info.deleted_time = None;
+            // 復元したルームがすぐにアイドル期限で削除されないようにする
+            room.room_info.last_seen_time = Utc::now();
+            room.room_info.clone()
+        }))
+    }
+
+    fn get_deleted_rooms<'a>(
+        &'a self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<RoomInfo>, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            let rooms = self.db.supervisor.list().await?;
+            Ok(rooms
+                .into_iter()
+                .filter(|room_info| room_info.deleted_time.is_some())
+                .collect())
+        })
+    }
+
+    fn delete_room<'a>(
+        &'a self,
+        room_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            let _ = self.db.supervisor.close(room_id).await;
+            Ok(())
+        })
+    }
+
+    fn publish<'a>(
+        &'a self,
+        room_id: &'a str,
+        event: RoomEvent,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, RepositoryError>> + Send + 'a>> {
+        Box::pin(async move {
+            self.db
+                .supervisor
+                .call(room_id, move |room| room.publish(&event))
+                .await?
+                .map_err(|_| RepositoryError::DbError)
+        })
+    }
+
+    fn join<'a>(
+        &'a self,
+        room_id: &'a str,
+        connection_id: &'a str,
+        user_info: &'a PubUserInfo,
+        since: Option<u64>,
+        signal: UnboundedSender<RoomEvent>,
+    ) -> Pin<Box<dyn Future<Output = Result<Resume, RepositoryError>> + Send + 'a>> {
+        let connection_id = connection_id.to_owned();
+        let user_info = user_info.to_owned();
+        Box::pin(self.db.supervisor.call(room_id, move |room| {
+            let Some(admission) = room.admit(&user_info.user_id) else {
+                return Resume::Full;
+            };
+            let resume = room.resume(since, admission);
+            // 再送できない場合はクライアントが切断するため登録しない
+            if let Resume::Replay(..) = resume {
+                let connection = Connection {
+                    user_info,
+                    signal,
+                    admission,
+                    connected_time: Utc::now(),
+                };
+                room.add_connection(connection_id, connection);
+            }
+            resume
+        }))
+    }
+
+    fn publish_chat<'a>(
+        &'a self,
+        room_id: &'a str,
+        chat: Chat,
+        policy: PostPolicy,
+        now: DateTime<Utc>,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<PostRejection>, RepositoryError>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            self.db
+                .supervisor
+                .call(room_id, move |room| room.publish_chat(chat, policy, now))
+                .await?
+                .map_err(|_| RepositoryError::DbError)
+        })
+    }
+
+    fn acquire_join_attempt<'a>(
+        &'a self,
+        room_id: &'a str,
+        user_id: &'a str,
+        now: DateTime<Utc>,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<Duration>, RepositoryError>> + Send + 'a>> {
+        let user_id = user_id.to_owned();
+        Box::pin(self.db.supervisor.call(room_id, move |room| {
+            room.acquire_join_attempt(&user_id, now)
+        }))
+    }
+
+    fn admission<'a>(
+        &'a self,
+        room_id: &'a str,
+        user_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<Admission>, RepositoryError>> + Send + 'a>> {
+        let user_id = user_id.to_owned();
+        Box::pin(
+            self.db
+                .supervisor
+                .call(room_id, move |room| room.admit(&user_id)),
+        )
+    }
+
+    fn leave<'a>(
+        &'a self,
+        room_id: &'a str,
+        connection_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RepositoryError>> + Send + 'a>> {
+        let connection_id = connection_id.to_owned();
+        Box::pin(self.db.supervisor.call(room_id, move |room| {
+            room.remove_connection(&connection_id);
+        }))
+    }
+
+    fn disconnect_user<'a>(
+        &'a self,
+        room_id: &'a str,
+        user_id: &'a str,
+        event: RoomEvent,
+    ) -> Pin<Box<dyn Future<Output = Result<usize, RepositoryError>> + Send + 'a>> {
+        let user_id = user_id.to_owned();
+        Box::pin(
+            self.db
+                .supervisor
+                .call(room_id, move |room| room.disconnect_user(&user_id, &event)),
+        )
     }
 }
 
 // ユニークIDを割り振る
 // チャンネルの作成を行う
-fn init_room(room_name: &str, created_by_id: &str, user_name: &str) -> Room {
-    let (sender, _) = broadcast::channel(128);
-
-    Room {
-        room_info: RoomInfo {
-            room_id: Uuid::new_v4().to_string(),
-            room_name: room_name.to_owned(),
-            created_by_id: created_by_id.to_owned(),
-            created_by_name: user_name.to_owned(),
-            created_time: Utc::now(),
-        },
-        sender,
+fn init_room(
+    payload: &CreateRoom,
+    user_info: &PubUserInfo,
+    join_password_hash: Option<&str>,
+) -> Room {
+    let now = Utc::now();
+    Room::new(RoomInfo {
+        room_id: Uuid::new_v4().to_string(),
+        room_name: payload.room_name.to_owned(),
+        description: non_empty(payload.description.as_deref()),
+        topic: non_empty(payload.topic.as_deref()),
+        created_by_id: user_info.user_id.to_owned(),
+        created_by_name: user_info.user_name.to_owned(),
+        created_time: now,
+        updated_time: now,
+        last_activity_time: now,
+        message_ttl_secs: payload.message_ttl_secs,
+        retention: payload.retention.unwrap_or_default(),
+        slow_mode_secs: None,
+        announcement_only: payload.announcement_only,
+        visibility: payload.visibility,
+        tags: normalize_tags(&payload.tags),
+        category: payload.category.as_deref().and_then(normalize_category),
+        join_code: gen_join_code(),
+        join_password_hash: join_password_hash.map(str::to_owned),
+        capacity: payload.capacity,
+        overflow: payload.overflow,
+        occupancy: 0,
+        spectators: 0,
+        idle_ttl_secs: payload.idle_ttl_secs,
+        keep_forever: payload.keep_forever,
+        last_seen_time: now,
+        expires_time: None,
+        archived_time: None,
+        orphaned_time: None,
+        deleted_time: None,
+    })
+}
+
+// テスト用のルーム情報。ルームのタスクは起動しない
+#[cfg(test)]
+pub(crate) fn test_room_info(room_id: &str) -> RoomInfo {
+    use crate::domain::entity::visibility::Visibility;
+
+    let payload = CreateRoom {
+        room_name: "room".to_string(),
+        visibility: Visibility::Private,
+        ..Default::default()
+    };
+    let owner = PubUserInfo {
+        user_id: "owner".to_string(),
+        user_name: "owner".to_string(),
+    };
+    let mut room_info = init_room(&payload, &owner, None).room_info;
+    room_info.room_id = room_id.to_string();
+    room_info
+}
+
+// テスト用のユーザー。ユーザーIDは名前に"_id"を付けたもの
+#[cfg(test)]
+pub(crate) fn test_user(name: &str) -> PubUserInfo {
+    PubUserInfo {
+        user_id: format!("{}_id", name),
+        user_name: name.to_string(),
+    }
+}
+
+// テスト用に、ownerが作成したルームにmembersを指定したロールで参加させる
+// 返したDBからリポジトリを作ると同じルームとメンバーを参照する
+#[cfg(test)]
+pub(crate) async fn set_up_test_room(
+    visibility: crate::domain::entity::visibility::Visibility,
+    members: &[(&str, crate::domain::entity::room_role::RoomRole)],
+) -> (RoomDb, crate::MembershipDb, RoomInfo) {
+    use crate::{
+        domain::repository::membership_repository::MembershipRepository,
+        infrastructure::repository::membership_repository_impl::MembershipRepositoryImpl,
+        MembershipDb,
+    };
+
+    let room_db = RoomDb::new();
+    let membership_db = MembershipDb::new();
+    let payload = CreateRoom {
+        room_name: "room".to_string(),
+        visibility,
+        ..Default::default()
+    };
+    let room_info = RoomRepositoryImpl::new(room_db.clone())
+        .open_new_room(&payload, &test_user("owner"), None)
+        .await
+        .unwrap();
+    let membership_repo = MembershipRepositoryImpl::new(membership_db.clone());
+    for (name, role) in members {
+        membership_repo
+            .add_member(&room_info.room_id, &test_user(name), *role)
+            .unwrap();
     }
+    (room_db, membership_db, room_info)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.filter(|value| !value.is_empty()).map(str::to_owned)
+}
+
+// 英字3文字と数字3桁の"ABC-123"形式
+fn gen_join_code() -> String {
+    let letters: String = (0..3)
+        .map(|_| JOIN_CODE_LETTERS[gen_below(JOIN_CODE_LETTERS.len() as u8) as usize] as char)
+        .collect();
+    let digits: String = (0..3).map(|_| char::from(b'0' + gen_below(10))).collect();
+    format!("{}-{}", letters, digits)
 }
 
-fn get_write_lock(
-    repo: &RoomRepositoryImpl,
-) -> Result<RwLockWriteGuard<HashMap<String, Room>>, RepositoryError> {
-    let lock = repo.db.pool.write().map_err(|_| RepositoryError::DbError)?;
-    Ok(lock)
+// 0以上n未満の値を一様に選ぶ
+// 剰余をそのまま取ると小さい値が出やすくなるため、nの倍数に収まらない値は引き直す
+fn gen_below(n: u8) -> u8 {
+    let limit = 256 - 256 % n as u16;
+    loop {
+        let mut byte = [0u8; 1];
+        OsRng.fill_bytes(&mut byte);
+        if (byte[0] as u16) < limit {
+            return byte[0] % n;
+        }
+    }
+}
+
+// 小文字やハイフン・空白の有無の違いを許容する
+fn normalize_join_code(join_code: &str) -> Option<String> {
+    let chars: Vec<char> = join_code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if chars.len() != 6 {
+        return None;
+    }
+    let (letters, digits) = chars.split_at(3);
+    if !letters.iter().all(char::is_ascii_uppercase) || !digits.iter().all(char::is_ascii_digit) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}",
+        letters.iter().collect::<String>(),
+        digits.iter().collect::<String>()
+    ))
 }
 
-fn get_read_lock(
-    repo: &RoomRepositoryImpl,
-) -> Result<RwLockReadGuard<HashMap<String, Room>>, RepositoryError> {
-    let lock = repo.db.pool.read().map_err(|_| RepositoryError::DbError)?;
-    Ok(lock)
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::Value;
+    use tokio::sync::mpsc;
+
+    use crate::domain::entity::{event_log::EVENT_LOG_CAPACITY, overflow_policy::OverflowPolicy};
+
+    use super::*;
+
+    fn user_info() -> PubUserInfo {
+        PubUserInfo {
+            user_id: "user_id".to_string(),
+            user_name: "user_name".to_string(),
+        }
+    }
+
+    // 時計を進めて、休止の通知を受けたスーパーバイザーの処理が終わるまで待つ
+    async fn idle(duration: Duration) {
+        tokio::time::advance(duration).await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn set_up_room(db: RoomDb) -> (RoomRepositoryImpl, String) {
+        let repo = RoomRepositoryImpl::new(db);
+        let payload = CreateRoom {
+            room_name: "room".to_string(),
+            ..Default::default()
+        };
+        let room_info = repo
+            .open_new_room(&payload, &user_info(), None)
+            .await
+            .unwrap();
+        (repo, room_info.room_id)
+    }
+
+    fn disconnect() -> UnboundedSender<RoomEvent> {
+        mpsc::unbounded_channel().0
+    }
+
+    fn expired(i: usize) -> RoomEvent {
+        RoomEvent::MessagesExpired {
+            message_ids: vec![format!("message{}", i)],
+        }
+    }
+
+    fn seq(event: &str) -> u64 {
+        serde_json::from_str::<Value>(event).unwrap()["seq"]
+            .as_u64()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_open_and_get_rooms() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+
+        let room_info = repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.room_name, "room");
+        assert_eq!(repo.get_all_room().await.unwrap().len(), 1);
+        assert_eq!(repo.get_owner_rooms("user_id").await.unwrap().len(), 1);
+        assert!(repo.get_owner_rooms("other").await.unwrap().is_empty());
+
+        repo.delete_room(&room_id).await.unwrap();
+        assert!(matches!(
+            repo.get_room_info(&room_id).await,
+            Err(RepositoryError::NotFound)
+        ));
+        assert!(matches!(
+            repo.publish(&room_id, expired(0)).await,
+            Err(RepositoryError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_room() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let Resume::Replay(_, mut events, _) = repo
+            .join(&room_id, "connection", &user_info(), None, sender)
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+        drop(receiver);
+
+        // テスト対象
+        let before = Utc::now();
+        let payload = UpdateRoom {
+            description: Some("description".to_string()),
+            topic: Some("topic".to_string()),
+            announcement_only: Some(true),
+            ..Default::default()
+        };
+        let room_info = repo.update_room(&room_id, payload).await.unwrap();
+        assert_eq!(room_info.room_name, "room");
+        assert_eq!(room_info.topic.as_deref(), Some("topic"));
+        assert!(room_info.announcement_only);
+        assert!(room_info.updated_time >= before);
+
+        let event: Value = serde_json::from_str(&events.recv().await.unwrap()).unwrap();
+        assert_eq!(event["type"], "roomUpdated");
+        assert_eq!(event["description"], "description");
+        assert_eq!(event["announcementOnly"], true);
+        // 参加コードは接続中の全員には流さない
+        assert!(event.get("joinCode").is_none());
+
+        // 空文字で削除できる
+        let payload = UpdateRoom {
+            room_name: Some("renamed".to_string()),
+            description: Some(String::new()),
+            ..Default::default()
+        };
+        repo.update_room(&room_id, payload).await.unwrap();
+        let room_info = repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.room_name, "renamed");
+        assert_eq!(room_info.description, None);
+        assert_eq!(room_info.topic.as_deref(), Some("topic"));
+    }
+
+    #[tokio::test]
+    async fn test_tags_and_category() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let payload = UpdateRoom {
+            tags: Some(vec![
+                " Rust ".to_string(),
+                "rust".to_string(),
+                "Games".to_string(),
+            ]),
+            category: Some(" Tech ".to_string()),
+            ..Default::default()
+        };
+
+        // テスト対象
+        let room_info = repo.update_room(&room_id, payload).await.unwrap();
+        assert_eq!(room_info.tags, vec!["rust", "games"]);
+        assert_eq!(room_info.category.as_deref(), Some("tech"));
+
+        // 空文字でカテゴリを削除し、空の配列でタグを全て外す
+        let payload = UpdateRoom {
+            tags: Some(Vec::new()),
+            category: Some(String::new()),
+            ..Default::default()
+        };
+        let room_info = repo.update_room(&room_id, payload).await.unwrap();
+        assert!(room_info.tags.is_empty());
+        assert_eq!(room_info.category, None);
+    }
+
+    #[tokio::test]
+    async fn test_publish_chat_slow_mode() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let now = Utc::now();
+        let chat = |user_id: &str| Chat::from_str(user_id, "user_name", "text");
+        // 無効の間は記録しない
+        let result = repo
+            .publish_chat(&room_id, chat("user_id"), PostPolicy::Member, now)
+            .await;
+        assert_eq!(result.unwrap(), None);
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let Resume::Replay(_, mut events, _) = repo
+            .join(&room_id, "connection", &user_info(), None, sender)
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+        drop(receiver);
+        let payload = UpdateRoom {
+            slow_mode_secs: Some(10),
+            ..Default::default()
+        };
+        repo.update_room(&room_id, payload).await.unwrap();
+        let _room_updated = events.recv().await.unwrap();
+        let event: Value = serde_json::from_str(&events.recv().await.unwrap()).unwrap();
+        assert_eq!(event["type"], "slowMode");
+        assert_eq!(event["seconds"], 10);
+
+        // テスト対象
+        let result = repo
+            .publish_chat(&room_id, chat("user_id"), PostPolicy::Member, now)
+            .await;
+        assert_eq!(result.unwrap(), None);
+        let later = now + chrono::Duration::seconds(4);
+        let result = repo
+            .publish_chat(&room_id, chat("user_id"), PostPolicy::Member, later)
+            .await;
+        assert_eq!(
+            result.unwrap(),
+            Some(PostRejection::SlowMode(chrono::Duration::seconds(6)))
+        );
+        // ユーザーごとに判定し、モデレーター以上は対象外
+        let result = repo
+            .publish_chat(&room_id, chat("other_id"), PostPolicy::Member, later)
+            .await;
+        assert_eq!(result.unwrap(), None);
+        let result = repo
+            .publish_chat(&room_id, chat("user_id"), PostPolicy::Moderator, later)
+            .await;
+        assert_eq!(result.unwrap(), None);
+        let later = now + chrono::Duration::seconds(10);
+        let result = repo
+            .publish_chat(&room_id, chat("user_id"), PostPolicy::Member, later)
+            .await;
+        assert_eq!(result.unwrap(), None);
+
+        // 流したチャットだけが届く
+        for _ in 0..4 {
+            let event: Value = serde_json::from_str(&events.recv().await.unwrap()).unwrap();
+            assert_eq!(event["type"], "chat");
+        }
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_publish_chat_room_state() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let now = Utc::now();
+        let chat = || Chat::from_str("user_id", "user_name", "text");
+        let payload = UpdateRoom {
+            announcement_only: Some(true),
+            ..Default::default()
+        };
+        repo.update_room(&room_id, payload).await.unwrap();
+
+        // テスト対象
+        let result = repo
+            .publish_chat(&room_id, chat(), PostPolicy::Member, now)
+            .await;
+        assert_eq!(result.unwrap(), Some(PostRejection::AnnouncementOnly));
+        let result = repo
+            .publish_chat(&room_id, chat(), PostPolicy::Bot, now)
+            .await;
+        assert_eq!(result.unwrap(), Some(PostRejection::AnnouncementOnly));
+        let result = repo
+            .publish_chat(&room_id, chat(), PostPolicy::Moderator, now)
+            .await;
+        assert_eq!(result.unwrap(), None);
+
+        // アーカイブされたルームにはモデレーター以上も投稿できない
+        repo.archive_room(&room_id).await.unwrap();
+        let result = repo
+            .publish_chat(&room_id, chat(), PostPolicy::Moderator, now)
+            .await;
+        assert_eq!(result.unwrap(), Some(PostRejection::Archived));
+    }
+
+    #[tokio::test]
+    async fn test_join_attempt_limit() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let now = Utc::now();
+
+        // テスト対象
+        for _ in 0..5 {
+            let wait = repo.acquire_join_attempt(&room_id, "user_id", now).await;
+            assert_eq!(wait.unwrap(), None);
+        }
+        let wait = repo
+            .acquire_join_attempt(&room_id, "user_id", now + chrono::Duration::minutes(5))
+            .await;
+        assert_eq!(wait.unwrap(), Some(chrono::Duration::minutes(10)));
+        // ユーザーごとに数える
+        let wait = repo.acquire_join_attempt(&room_id, "other_id", now).await;
+        assert_eq!(wait.unwrap(), None);
+        // ウィンドウが終わると再度試せる
+        let wait = repo
+            .acquire_join_attempt(&room_id, "user_id", now + chrono::Duration::minutes(15))
+            .await;
+        assert_eq!(wait.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_room() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let new_owner = PubUserInfo {
+            user_id: "new_owner".to_string(),
+            user_name: "new_owner_name".to_string(),
+        };
+
+        // テスト対象
+        let room_info = repo.transfer_room(&room_id, &new_owner).await.unwrap();
+        assert_eq!(room_info.created_by_id, "new_owner");
+        assert_eq!(room_info.created_by_name, "new_owner_name");
+        assert_eq!(repo.get_owner_rooms("new_owner").await.unwrap().len(), 1);
+        assert!(repo
+            .get_owner_rooms(&user_info().user_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let archived = repo.archive_room(&room_id).await.unwrap();
+        assert!(archived.is_archived());
+        // レスポンスにはアーカイブの有無と時刻の両方を含める
+        let json = serde_json::to_value(&archived).unwrap();
+        assert_eq!(json["archived"], true);
+        assert!(json["archivedTime"].is_string());
+        // 2回目はアーカイブした時刻を変えない
+        let again = repo.archive_room(&room_id).await.unwrap();
+        assert_eq!(again.archived_time, archived.archived_time);
+
+        let unarchived = repo.unarchive_room(&room_id).await.unwrap();
+        assert!(!unarchived.is_archived());
+        let json = serde_json::to_value(&unarchived).unwrap();
+        assert_eq!(json["archived"], false);
+        assert!(json["archivedTime"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        repo.join(&room_id, "connection", &user_info(), None, sender)
+            .await
+            .unwrap();
+
+        // テスト対象
+        let room_info = repo.soft_delete_room(&room_id).await.unwrap();
+        assert!(room_info.deleted_time.is_some());
+        assert!(matches!(
+            receiver.recv().await,
+            Some(RoomEvent::RoomDeleted)
+        ));
+        assert!(matches!(
+            repo.get_room_info(&room_id).await,
+            Err(RepositoryError::NotFound)
+        ));
+        assert!(repo.get_all_room().await.unwrap().is_empty());
+        assert!(repo
+            .get_owner_rooms(&user_info().user_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(repo.get_deleted_rooms().await.unwrap().len(), 1);
+
+        let room_info = repo.restore_room(&room_id).await.unwrap();
+        assert_eq!(room_info.deleted_time, None);
+        assert_eq!(repo.get_room_info(&room_id).await.unwrap(), room_info);
+        assert!(repo.get_deleted_rooms().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_room_by_join_code() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let join_code = repo.get_room_info(&room_id).await.unwrap().join_code;
+        assert!(normalize_join_code(&join_code).is_some_and(|code| code == join_code));
+
+        // テスト対象
+        let loose = join_code.replace('-', " ").to_lowercase();
+        let room_info = repo.get_room_by_join_code(&loose).await.unwrap();
+        assert_eq!(room_info.room_id, room_id);
+        assert!(matches!(
+            repo.get_room_by_join_code("ABC-12").await,
+            Err(RepositoryError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_publish_assigns_sequence() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let Resume::Replay(replay, mut receiver, _) = repo
+            .join(&room_id, "connection", &user_info(), None, disconnect())
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+        assert!(replay.is_empty());
+
+        assert_eq!(repo.publish(&room_id, expired(0)).await.unwrap(), 1);
+        assert_eq!(repo.publish(&room_id, expired(1)).await.unwrap(), 2);
+
+        let event: Value = serde_json::from_str(&receiver.recv().await.unwrap()).unwrap();
+        assert_eq!(event["seq"], 1);
+        assert_eq!(event["type"], "messagesExpired");
+        assert_eq!(event["messageIds"][0], "message0");
+    }
+
+    #[tokio::test]
+    async fn test_publish_shares_serialized_event() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let mut receivers = Vec::new();
+        for i in 0..2 {
+            let Resume::Replay(_, receiver, _) = repo
+                .join(
+                    &room_id,
+                    &format!("connection{}", i),
+                    &user_info(),
+                    None,
+                    disconnect(),
+                )
+                .await
+                .unwrap()
+            else {
+                panic!("unexpected gap");
+            };
+            receivers.push(receiver);
+        }
+        repo.publish(&room_id, expired(0)).await.unwrap();
+
+        // 全ての購読者と再送用の履歴が同じバッファを参照している
+        let first = receivers[0].recv().await.unwrap();
+        let second = receivers[1].recv().await.unwrap();
+        assert_eq!(first.as_str().as_ptr(), second.as_str().as_ptr());
+        let Resume::Replay(replay, _, _) = repo
+            .join(&room_id, "connection2", &user_info(), Some(0), disconnect())
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+        assert_eq!(replay[0].as_str().as_ptr(), first.as_str().as_ptr());
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_after_since() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        for i in 0..5 {
+            repo.publish(&room_id, expired(i)).await.unwrap();
+        }
+
+        let Resume::Replay(replay, mut receiver, _) = repo
+            .join(&room_id, "connection", &user_info(), Some(3), disconnect())
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+        let seqs: Vec<u64> = replay.iter().map(|e| seq(e)).collect();
+        assert_eq!(seqs, vec![4, 5]);
+
+        // 再送後のイベントはReceiverから受け取れる
+        repo.publish(&room_id, expired(5)).await.unwrap();
+        assert_eq!(seq(&receiver.recv().await.unwrap()), 6);
+    }
+
+    #[tokio::test]
+    async fn test_resume_gap_too_large() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        for i in 0..EVENT_LOG_CAPACITY + 10 {
+            repo.publish(&room_id, expired(i)).await.unwrap();
+        }
+        let latest = (EVENT_LOG_CAPACITY + 10) as u64;
+
+        assert!(matches!(
+            repo.join(&room_id, "connection", &user_info(), Some(5), disconnect()).await,
+            Ok(Resume::GapTooLarge { latest_seq }) if latest_seq == latest
+        ));
+        // 未来のseqも再送できない
+        assert!(matches!(
+            repo.join(
+                &room_id,
+                "connection",
+                &user_info(),
+                Some(latest + 1),
+                disconnect()
+            )
+            .await,
+            Ok(Resume::GapTooLarge { .. })
+        ));
+        // 履歴に残っている最古のイベントの直前からは再送できる
+        let Ok(Resume::Replay(replay, _, _)) = repo
+            .join(&room_id, "connection", &user_info(), Some(10), disconnect())
+            .await
+        else {
+            panic!("unexpected gap");
+        };
+        assert_eq!(replay.len(), EVENT_LOG_CAPACITY);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_publish_keeps_order() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let Resume::Replay(_, mut receiver, _) = repo
+            .join(&room_id, "connection", &user_info(), None, disconnect())
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+
+        let tasks: Vec<_> = (0..50)
+            .map(|i| {
+                let repo = RoomRepositoryImpl::new(repo.db.clone());
+                let room_id = room_id.clone();
+                tokio::spawn(async move { repo.publish(&room_id, expired(i)).await.unwrap() })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // 採番された順にブロードキャストされる
+        for expected in 1..=50 {
+            assert_eq!(seq(&receiver.recv().await.unwrap()), expected);
+        }
+    }
+
+    // 時計を止めて進めるため、実時間の待ちやタイミングに依存しない
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_room_passivates_and_wakes() {
+        let db = RoomDb::with_idle_timeout(Duration::from_millis(20));
+        let (repo, room_id) = set_up_room(db).await;
+        repo.publish(&room_id, expired(0)).await.unwrap();
+
+        // 休止した後も状態は引き継がれる
+        idle(Duration::from_millis(100)).await;
+        assert_eq!(repo.publish(&room_id, expired(1)).await.unwrap(), 2);
+        idle(Duration::from_millis(100)).await;
+        assert_eq!(repo.get_all_room().await.unwrap().len(), 1);
+        let Ok(Resume::Replay(replay, _, _)) = repo
+            .join(&room_id, "connection", &user_info(), Some(0), disconnect())
+            .await
+        else {
+            panic!("unexpected gap");
+        };
+        assert_eq!(replay.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_room_closes_subscribers() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let Resume::Replay(_, mut receiver, _) = repo
+            .join(&room_id, "connection", &user_info(), None, disconnect())
+            .await
+            .unwrap()
+        else {
+            panic!("unexpected gap");
+        };
+
+        repo.delete_room(&room_id).await.unwrap();
+        assert!(receiver.recv().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_user() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        repo.join(&room_id, "connection", &user_info(), None, sender)
+            .await
+            .unwrap();
+        repo.join(&room_id, "other", &user_info(), None, disconnect())
+            .await
+            .unwrap();
+
+        // テスト対象
+        let event = RoomEvent::Kicked { reason: None };
+        let count = repo
+            .disconnect_user(&room_id, "user_id", event)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(matches!(
+            receiver.recv().await,
+            Some(RoomEvent::Kicked { .. })
+        ));
+
+        let count = repo
+            .disconnect_user(&room_id, "user_id", RoomEvent::Kicked { reason: None })
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_capacity() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let payload = UpdateRoom {
+            capacity: Some(1),
+            ..Default::default()
+        };
+        repo.update_room(&room_id, payload).await.unwrap();
+        let other = PubUserInfo {
+            user_id: "other_id".to_string(),
+            user_name: "other_name".to_string(),
+        };
+
+        repo.join(&room_id, "connection", &user_info(), None, disconnect())
+            .await
+            .unwrap();
+        // 参加中のユーザーの別の接続は定員に数えない
+        let resume = repo
+            .join(&room_id, "connection2", &user_info(), None, disconnect())
+            .await;
+        assert!(matches!(
+            resume,
+            Ok(Resume::Replay(_, _, Admission::Participant))
+        ));
+        assert_eq!(repo.get_room_info(&room_id).await.unwrap().occupancy, 1);
+
+        // テスト対象
+        let admission = repo.admission(&room_id, "other_id").await.unwrap();
+        assert_eq!(admission, None);
+        let resume = repo
+            .join(&room_id, "connection3", &other, None, disconnect())
+            .await;
+        assert!(matches!(resume, Ok(Resume::Full)));
+
+        // 観覧を許可すると観覧者として接続できる
+        let payload = UpdateRoom {
+            overflow: Some(OverflowPolicy::Spectate),
+            ..Default::default()
+        };
+        repo.update_room(&room_id, payload).await.unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let resume = repo
+            .join(&room_id, "connection3", &other, None, sender)
+            .await;
+        assert!(matches!(
+            resume,
+            Ok(Resume::Replay(_, _, Admission::Spectator))
+        ));
+        let room_info = repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!((room_info.occupancy, room_info.spectators), (1, 1));
+
+        // 参加中のユーザーの接続が全て抜けるまでは観覧者のまま
+        repo.leave(&room_id, "connection").await.unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        // 定員に空きができると観覧者が参加者になる
+        repo.leave(&room_id, "connection2").await.unwrap();
+        assert!(matches!(
+            receiver.recv().await,
+            Some(RoomEvent::Participating)
+        ));
+        let room_info = repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!((room_info.occupancy, room_info.spectators), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_promote_spectators() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let capacity = |capacity| UpdateRoom {
+            capacity: Some(capacity),
+            overflow: Some(OverflowPolicy::Spectate),
+            ..Default::default()
+        };
+        repo.update_room(&room_id, capacity(1)).await.unwrap();
+        repo.join(&room_id, "connection", &user_info(), None, disconnect())
+            .await
+            .unwrap();
+        // 接続IDの順ではなく接続した順に参加者になる
+        let mut receivers = Vec::new();
+        for (connection_id, user_id) in [("spectator_b", "first_id"), ("spectator_a", "second_id")]
+        {
+            let user_info = PubUserInfo {
+                user_id: user_id.to_string(),
+                user_name: user_id.to_string(),
+            };
+            let (sender, receiver) = mpsc::unbounded_channel();
+            repo.join(&room_id, connection_id, &user_info, None, sender)
+                .await
+                .unwrap();
+            receivers.push(receiver);
+        }
+
+        // テスト対象
+        let room_info = repo.update_room(&room_id, capacity(2)).await.unwrap();
+        assert_eq!((room_info.occupancy, room_info.spectators), (2, 1));
+        assert!(matches!(
+            receivers[0].try_recv(),
+            Ok(RoomEvent::Participating)
+        ));
+        assert!(receivers[1].try_recv().is_err());
+
+        // キックで空いた席も観覧者に回す
+        repo.disconnect_user(&room_id, "user_id", RoomEvent::Kicked { reason: None })
+            .await
+            .unwrap();
+        assert!(matches!(
+            receivers[1].try_recv(),
+            Ok(RoomEvent::Participating)
+        ));
+        let room_info = repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!((room_info.occupancy, room_info.spectators), (2, 0));
+    }
+
+    #[tokio::test]
+    async fn test_set_expires_time() {
+        let (repo, room_id) = set_up_room(RoomDb::new()).await;
+        let expires_time = Utc::now() + chrono::Duration::hours(1);
+
+        // テスト対象
+        let room_info = repo.set_expires_time(&room_id, expires_time).await.unwrap();
+        assert_eq!(room_info.expires_time, Some(expires_time));
+
+        // 設定を変更すると期限の通知は取り消される
+        let payload = UpdateRoom {
+            keep_forever: Some(true),
+            ..Default::default()
+        };
+        let room_info = repo.update_room(&room_id, payload).await.unwrap();
+        assert!(room_info.keep_forever);
+        assert_eq!(room_info.expires_time, None);
+
+        // 接続すると期限の通知は取り消される
+        repo.set_expires_time(&room_id, expires_time).await.unwrap();
+        repo.join(&room_id, "connection", &user_info(), None, disconnect())
+            .await
+            .unwrap();
+        let room_info = repo.get_room_info(&room_id).await.unwrap();
+        assert_eq!(room_info.expires_time, None);
+    }
 }
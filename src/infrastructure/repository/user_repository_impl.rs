@@ -118,6 +118,24 @@ impl<'r> UserRepository for UserRepositoryImpl<'r> {
             }
         })
     }
+
+    fn is_admin<'a>(
+        &'a self,
+        user_id: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let (is_admin,): (bool,) = sqlx::query_as(
+                r#"
+                SELECT is_admin FROM user_data
+                WHERE user_id = $1
+                "#,
+            )
+            .bind(user_id)
+            .fetch_one(self.pool)
+            .await?;
+            Ok(is_admin)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -243,4 +261,27 @@ mod test {
         // 削除
         repo.delete(&user_info_result.user_id).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_is_admin() {
+        let pool = set_up_db().await;
+        let new_user = gen_random_user();
+        let repo = UserRepositoryImpl::new(&pool);
+        // 最初に登録
+        repo.insert(&new_user).await.unwrap();
+
+        // テスト対象
+        // 登録直後は管理者ではない
+        assert!(!repo.is_admin(&new_user.user_id).await.unwrap());
+
+        sqlx::query("UPDATE user_data SET is_admin = TRUE WHERE user_id = $1")
+            .bind(&new_user.user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(repo.is_admin(&new_user.user_id).await.unwrap());
+
+        // 削除
+        repo.delete(&new_user.user_id).await.unwrap();
+    }
 }
@@ -0,0 +1,388 @@
+use std::{
+    collections::HashMap,
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::Duration,
+};
+
+use tokio::sync::{
+    mpsc::{self, error::SendError, UnboundedSender, WeakUnboundedSender},
+    oneshot,
+};
+use tracing::error;
+
+use crate::domain::{
+    entity::{room::Room, room_info::RoomInfo},
+    repository::error::RepositoryError,
+};
+
+// ルームのタスクのメールボックスの上限
+const ROOM_MAILBOX_SIZE: usize = 256;
+// 休止したタスクを呼び出し直す回数の上限
+const MAX_CALL_ATTEMPTS: usize = 3;
+
+// 呼び出し元への返信は、変更したルーム情報をスーパーバイザーに通知してから送る
+type RoomTask = Box<dyn FnOnce(&mut Room) -> Reply + Send>;
+type Reply = Box<dyn FnOnce() + Send>;
+
+enum RoomCommand {
+    Run(RoomTask),
+    // 状態をスーパーバイザーに返して停止する
+    Passivate,
+    // ルームの削除。購読者の接続も切断される
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct RoomHandle {
+    commands: mpsc::Sender<RoomCommand>,
+}
+
+enum SupervisorCommand {
+    // 参加コードが既存のルームと重複する場合はfalseを返す
+    Open {
+        room: Box<Room>,
+        reply: oneshot::Sender<bool>,
+    },
+    Info {
+        room_id: String,
+        reply: oneshot::Sender<Option<RoomInfo>>,
+    },
+    List {
+        reply: oneshot::Sender<Vec<RoomInfo>>,
+    },
+    Handle {
+        room_id: String,
+        reply: oneshot::Sender<Option<RoomHandle>>,
+    },
+    Close {
+        room_id: String,
+        reply: oneshot::Sender<Option<RoomInfo>>,
+    },
+    // 以下はルームのタスクから送られる
+    InfoChanged {
+        room_info: Box<RoomInfo>,
+    },
+    Idle {
+        room_id: String,
+    },
+    Passivated {
+        room: Box<Room>,
+    },
+}
+
+enum SlotState {
+    Active(RoomHandle),
+    // 休止の完了を待っている呼び出し
+    Passivating(Vec<oneshot::Sender<Option<RoomHandle>>>),
+    Dormant(Box<Room>),
+}
+
+struct Slot {
+    // 一覧の取得で休止中のルームを起こさないように保持する
+    room_info: RoomInfo,
+    state: SlotState,
+}
+
+// ルームごとのタスクの生成・休止・削除を管理するタスクへのハンドル
+// 接続中のメンバーがおらず一定時間操作のないルームは休止し、次の呼び出しで再開する
+#[derive(Debug, Clone)]
+pub struct RoomSupervisor {
+    commands: UnboundedSender<SupervisorCommand>,
+}
+
+impl RoomSupervisor {
+    // tokioのランタイム上で呼ぶこと
+    pub fn spawn(idle_timeout: Duration) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let supervisor = Supervisor {
+            commands: commands.downgrade(),
+            rooms: HashMap::new(),
+            idle_timeout,
+        };
+        tokio::spawn(supervisor.run(receiver));
+        Self { commands }
+    }
+
+    pub async fn open(&self, room: Room) -> Result<bool, RepositoryError> {
+        self.request(|reply| SupervisorCommand::Open {
+            room: Box::new(room),
+            reply,
+        })
+        .await
+    }
+
+    pub async fn info(&self, room_id: &str) -> Result<RoomInfo, RepositoryError> {
+        self.request(|reply| SupervisorCommand::Info {
+            room_id: room_id.to_owned(),
+            reply,
+        })
+        .await?
+        .ok_or(RepositoryError::NotFound)
+    }
+
+    pub async fn list(&self) -> Result<Vec<RoomInfo>, RepositoryError> {
+        self.request(|reply| SupervisorCommand::List { reply })
+            .await
+    }
+
+    pub async fn close(&self, room_id: &str) -> Result<RoomInfo, RepositoryError> {
+        self.request(|reply| SupervisorCommand::Close {
+            room_id: room_id.to_owned(),
+            reply,
+        })
+        .await?
+        .ok_or(RepositoryError::NotFound)
+    }
+
+    // ルームのタスク上でfを実行して結果を返す
+    pub async fn call<F, T>(&self, room_id: &str, f: F) -> Result<T, RepositoryError>
+    where
+        F: FnOnce(&mut Room) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let mut command = RoomCommand::Run(Box::new(move |room: &mut Room| {
+            let value = f(room);
+            Box::new(move || {
+                let _ = reply.send(value);
+            }) as Reply
+        }));
+
+        for _ in 0..MAX_CALL_ATTEMPTS {
+            let handle = self
+                .request(|reply| SupervisorCommand::Handle {
+                    room_id: room_id.to_owned(),
+                    reply,
+                })
+                .await?
+                .ok_or(RepositoryError::NotFound)?;
+            // 休止中のタスクに送った場合は取り直したハンドルで再送する
+            match handle.commands.send(command).await {
+                Ok(()) => return result.await.map_err(|_| RepositoryError::DbError),
+                Err(SendError(returned)) => command = returned,
+            }
+        }
+        Err(RepositoryError::DbError)
+    }
+
+    async fn request<T>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<T>) -> SupervisorCommand,
+    ) -> Result<T, RepositoryError> {
+        let (reply, result) = oneshot::channel();
+        self.commands
+            .send(command(reply))
+            .map_err(|_| RepositoryError::DbError)?;
+        result.await.map_err(|_| RepositoryError::DbError)
+    }
+}
+
+struct Supervisor {
+    // ルームのタスクに渡す
+    // 全てのRoomSupervisorが破棄されたらスーパーバイザーも停止する
+    commands: WeakUnboundedSender<SupervisorCommand>,
+    rooms: HashMap<String, Slot>,
+    idle_timeout: Duration,
+}
+
+impl Supervisor {
+    async fn run(mut self, mut receiver: mpsc::UnboundedReceiver<SupervisorCommand>) {
+        while let Some(command) = receiver.recv().await {
+            self.handle(command);
+        }
+    }
+
+    fn handle(&mut self, command: SupervisorCommand) {
+        match command {
+            SupervisorCommand::Open { room, reply } => {
+                let room_info = room.room_info.clone();
+                let duplicated = self
+                    .rooms
+                    .values()
+                    .any(|slot| slot.room_info.join_code == room_info.join_code);
+                if duplicated {
+                    let _ = reply.send(false);
+                    return;
+                }
+                let handle = self.spawn_room(room);
+                self.rooms.insert(
+                    room_info.room_id.clone(),
+                    Slot {
+                        room_info,
+                        state: SlotState::Active(handle),
+                    },
+                );
+                let _ = reply.send(true);
+            }
+            SupervisorCommand::Info { room_id, reply } => {
+                let room_info = self.rooms.get(&room_id).map(|slot| slot.room_info.clone());
+                let _ = reply.send(room_info);
+            }
+            SupervisorCommand::List { reply } => {
+                let room_infos = self
+                    .rooms
+                    .values()
+                    .map(|slot| slot.room_info.clone())
+                    .collect();
+                let _ = reply.send(room_infos);
+            }
+            SupervisorCommand::Handle { room_id, reply } => self.handle_room(&room_id, reply),
+            SupervisorCommand::Close { room_id, reply } => {
+                let slot = self.rooms.remove(&room_id);
+                if let Some(Slot {
+                    state: SlotState::Active(handle),
+                    ..
+                }) = &slot
+                {
+                    // 満杯の場合もハンドルが全て破棄された時点でタスクは停止する
+                    let _ = handle.commands.try_send(RoomCommand::Close);
+                }
+                let _ = reply.send(slot.map(|slot| slot.room_info));
+            }
+            SupervisorCommand::InfoChanged { room_info } => {
+                if let Some(slot) = self.rooms.get_mut(&room_info.room_id) {
+                    slot.room_info = *room_info;
+                }
+            }
+            SupervisorCommand::Idle { room_id } => {
+                let Some(slot) = self.rooms.get_mut(&room_id) else {
+                    return;
+                };
+                if let SlotState::Active(handle) = &slot.state {
+                    if handle.commands.try_send(RoomCommand::Passivate).is_ok() {
+                        slot.state = SlotState::Passivating(Vec::new());
+                    }
+                }
+            }
+            SupervisorCommand::Passivated { room } => {
+                let room_id = room.room_info.room_id.clone();
+                // 休止中に削除されたルームはそのまま破棄する
+                let Some(slot) = self.rooms.get_mut(&room_id) else {
+                    return;
+                };
+                slot.room_info = room.room_info.clone();
+                let previous = std::mem::replace(&mut slot.state, SlotState::Dormant(room));
+                // 休止中に呼び出しがあった場合はすぐに再開する
+                if let SlotState::Passivating(waiting) = previous {
+                    if !waiting.is_empty() {
+                        let handle = self.wake(&room_id);
+                        for reply in waiting {
+                            let _ = reply.send(handle.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_room(&mut self, room_id: &str, reply: oneshot::Sender<Option<RoomHandle>>) {
+        let Some(slot) = self.rooms.get_mut(room_id) else {
+            let _ = reply.send(None);
+            return;
+        };
+        match &mut slot.state {
+            SlotState::Active(handle) => {
+                let _ = reply.send(Some(handle.clone()));
+            }
+            SlotState::Passivating(waiting) => waiting.push(reply),
+            SlotState::Dormant(_) => {
+                let handle = self.wake(room_id);
+                let _ = reply.send(handle);
+            }
+        }
+    }
+
+    // 休止中のルームのタスクを再開する
+    fn wake(&mut self, room_id: &str) -> Option<RoomHandle> {
+        let slot = self.rooms.remove(room_id)?;
+        let SlotState::Dormant(room) = slot.state else {
+            self.rooms.insert(room_id.to_owned(), slot);
+            return None;
+        };
+        let handle = self.spawn_room(room);
+        self.rooms.insert(
+            room_id.to_owned(),
+            Slot {
+                room_info: slot.room_info,
+                state: SlotState::Active(handle.clone()),
+            },
+        );
+        Some(handle)
+    }
+
+    fn spawn_room(&self, room: Box<Room>) -> RoomHandle {
+        let (commands, receiver) = mpsc::channel(ROOM_MAILBOX_SIZE);
+        tokio::spawn(run_room(
+            room,
+            receiver,
+            self.commands.clone(),
+            self.idle_timeout,
+        ));
+        RoomHandle { commands }
+    }
+}
+
+async fn run_room(
+    mut room: Box<Room>,
+    mut receiver: mpsc::Receiver<RoomCommand>,
+    supervisor: WeakUnboundedSender<SupervisorCommand>,
+    idle_timeout: Duration,
+) {
+    let notify = |command: SupervisorCommand| {
+        if let Some(supervisor) = supervisor.upgrade() {
+            let _ = supervisor.send(command);
+        }
+    };
+    let mut idle_reported = false;
+
+    loop {
+        let idle = room.members.is_empty() && !idle_reported;
+        tokio::select! {
+            command = receiver.recv() => match command {
+                Some(RoomCommand::Run(task)) => {
+                    idle_reported = false;
+                    let room_info = room.room_info.clone();
+                    let reply = run_task(&mut room, task);
+                    if room.room_info != room_info {
+                        notify(SupervisorCommand::InfoChanged {
+                            room_info: Box::new(room.room_info.clone()),
+                        });
+                    }
+                    reply.into_iter().for_each(|reply| reply());
+                }
+                Some(RoomCommand::Passivate) => {
+                    // 受付済みの呼び出しを処理してから状態を返す
+                    receiver.close();
+                    let mut replies = Vec::new();
+                    while let Some(command) = receiver.recv().await {
+                        if let RoomCommand::Run(task) = command {
+                            replies.extend(run_task(&mut room, task));
+                        }
+                    }
+                    notify(SupervisorCommand::Passivated { room });
+                    replies.into_iter().for_each(|reply| reply());
+                    return;
+                }
+                // ルームの削除、または全てのハンドルが破棄された
+                Some(RoomCommand::Close) | None => return,
+            },
+            _ = tokio::time::sleep(idle_timeout), if idle => {
+                idle_reported = true;
+                notify(SupervisorCommand::Idle {
+                    room_id: room.room_info.room_id.clone(),
+                });
+            }
+        }
+    }
+}
+
+// 1つの呼び出しがpanicしても他のルームやこのルームの以降の呼び出しには影響させない
+fn run_task(room: &mut Room, task: RoomTask) -> Option<Reply> {
+    match catch_unwind(AssertUnwindSafe(|| task(room))) {
+        Ok(reply) => Some(reply),
+        Err(_) => {
+            error!("room task panicked: {}", room.room_info.room_id);
+            None
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use std::{
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::LazyLock,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use http::{header::CONTENT_TYPE, Method, Request};
+use http_body_util::Full;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::{
+    client::legacy::{
+        connect::{
+            dns::{GaiResolver, Name},
+            HttpConnector,
+        },
+        Client,
+    },
+    rt::TokioExecutor,
+};
+use serde::Serialize;
+use sha2::Sha256;
+use tower_service::Service;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        entity::{
+            hook_delivery::HookDelivery, hook_event::HookEvent,
+            hook_target_policy::HookTargetPolicy, outgoing_hook::OutgoingHook, room_info::RoomInfo,
+        },
+        repository::outgoing_hook_repository::OutgoingHookRepository,
+        service::util::event_notifier::EventNotifier,
+    },
+    infrastructure::repository::outgoing_hook_repository_impl::OutgoingHookRepositoryImpl,
+    OutgoingHookDb,
+};
+
+pub static SIGNATURE_HEADER: &str = "x-hook-signature";
+// 署名した時刻(UNIX秒)。署名の対象に含まれる
+pub static TIMESTAMP_HEADER: &str = "x-hook-timestamp";
+pub static EVENT_HEADER: &str = "x-hook-event";
+pub static DELIVERY_HEADER: &str = "x-hook-delivery";
+
+// 初回を含めた最大試行回数と、リトライ間隔の初期値(試行ごとに倍になる)
+const MAX_ATTEMPTS: u32 = 5;
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+type HookClient = Client<HttpsConnector<HttpConnector<TargetResolver>>, Full<Bytes>>;
+
+static CLIENT: LazyLock<HookClient> = LazyLock::new(|| build_client(HookTargetPolicy::default()));
+static INSECURE_CLIENT: LazyLock<HookClient> = LazyLock::new(|| {
+    build_client(HookTargetPolicy {
+        allow_insecure: true,
+    })
+});
+
+fn build_client(policy: HookTargetPolicy) -> HookClient {
+    let mut http = HttpConnector::new_with_resolver(TargetResolver {
+        policy,
+        inner: GaiResolver::new(),
+    });
+    http.enforce_http(false);
+    let builder = HttpsConnectorBuilder::new().with_webpki_roots();
+    let builder = if policy.allow_insecure {
+        builder.https_or_http()
+    } else {
+        builder.https_only()
+    };
+    let connector = builder.enable_http1().wrap_connector(http);
+    Client::builder(TokioExecutor::new()).build(connector)
+}
+
+// 名前解決の結果から送信先として許可しないアドレスを除き、内部ネットワークへの送信を防ぐ
+#[derive(Clone)]
+struct TargetResolver {
+    policy: HookTargetPolicy,
+    inner: GaiResolver,
+}
+
+impl Service<Name> for TargetResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let policy = self.policy;
+        let resolving = self.inner.call(name);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = resolving
+                .await?
+                .filter(|addr| policy.allows_addr(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "callback host has no public address",
+                ));
+            }
+            Ok(addrs.into_iter())
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeliveryBody<'a> {
+    delivery_id: &'a str,
+    hook_id: &'a str,
+    room_id: &'a str,
+    time: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a HookEvent,
+}
+
+// 1つのフックへの1回の配送
+struct Job {
+    hook: OutgoingHook,
+    delivery: HookDelivery,
+    body: Bytes,
+}
+
+pub struct EventNotifierImpl {
+    db: OutgoingHookDb,
+}
+
+impl EventNotifierImpl {
+    pub fn new(db: OutgoingHookDb) -> Self {
+        Self { db }
+    }
+
+    fn prepare(&self, room_id: &str, event: HookEvent) -> Vec<Job> {
+        let repo = OutgoingHookRepositoryImpl::new(self.db.clone());
+        let hooks = match repo.get_subscribed_hooks(room_id, event.kind()) {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                warn!("failed to get outgoing hooks: {:?}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut jobs = Vec::new();
+        for hook in hooks {
+            let delivery_id = Uuid::new_v4().to_string();
+            let body = DeliveryBody {
+                delivery_id: &delivery_id,
+                hook_id: &hook.hook_id,
+                room_id,
+                time: Utc::now(),
+                event: &event,
+            };
+            let Ok(body) = serde_json::to_vec(&body) else {
+                warn!("failed to serialize outgoing hook payload");
+                continue;
+            };
+            let delivery = HookDelivery {
+                delivery_id,
+                hook_id: hook.hook_id.clone(),
+                event: event.kind(),
+                attempts: 0,
+                success: false,
+                status_code: None,
+                last_error: None,
+                created_time: Utc::now(),
+                updated_time: Utc::now(),
+            };
+            jobs.push(Job {
+                hook,
+                delivery,
+                body: Bytes::from(body),
+            });
+        }
+        jobs
+    }
+}
+
+impl EventNotifier for EventNotifierImpl {
+    fn notify(&self, room_id: &str, event: HookEvent) {
+        for job in self.prepare(room_id, event) {
+            tokio::spawn(deliver(self.db.clone(), job, BASE_BACKOFF));
+        }
+    }
+
+    // 配送が終わるまでフックと配送ログを残し、その後でルームの送信Webhookを削除する
+    fn notify_room_deleted(&self, room_info: RoomInfo) {
+        let room_id = room_info.room_id.clone();
+        let jobs = self.prepare(&room_id, HookEvent::RoomDeleted((&room_info).into()));
+        tokio::spawn(deliver_and_remove_hooks(
+            self.db.clone(),
+            room_id,
+            jobs,
+            BASE_BACKOFF,
+        ));
+    }
+}
+
+async fn deliver_and_remove_hooks(
+    db: OutgoingHookDb,
+    room_id: String,
+    jobs: Vec<Job>,
+    backoff: Duration,
+) {
+    join_all(
+        jobs.into_iter()
+            .map(|job| deliver(db.clone(), job, backoff)),
+    )
+    .await;
+    if let Err(e) = OutgoingHookRepositoryImpl::new(db).delete_room_hooks(&room_id) {
+        warn!("failed to delete outgoing hooks: {:?}", e);
+    }
+}
+
+// 2xxが返るまで指数バックオフでリトライし、試行ごとに配送ログを更新する
+async fn deliver(db: OutgoingHookDb, job: Job, mut backoff: Duration) {
+    let Job {
+        hook,
+        mut delivery,
+        body,
+    } = job;
+    let client = if db.target_policy.allow_insecure {
+        &*INSECURE_CLIENT
+    } else {
+        &*CLIENT
+    };
+    let allowed = db.target_policy.allows_url(&hook.callback_url);
+    let repo = OutgoingHookRepositoryImpl::new(db);
+
+    while delivery.attempts < MAX_ATTEMPTS {
+        delivery.attempts += 1;
+
+        // リトライでも古い時刻の署名を使い回さない
+        let timestamp = Utc::now().timestamp();
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(&hook.callback_url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign(&hook.secret, timestamp, &body))
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(EVENT_HEADER, delivery.event.as_str())
+            .header(DELIVERY_HEADER, &delivery.delivery_id)
+            .body(Full::new(body.clone()))
+            .map_err(|e| e.to_string())
+            .and_then(|request| {
+                allowed
+                    .then_some(request)
+                    .ok_or_else(|| "callback url is not allowed".to_string())
+            });
+        let request = match request {
+            Ok(request) => request,
+            Err(e) => {
+                // URLが不正か許可されていない送信先の場合はリトライしても成功しない
+                delivery.last_error = Some(e);
+                delivery.updated_time = Utc::now();
+                let _ = repo.save_delivery(&delivery);
+                return;
+            }
+        };
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, client.request(request)).await {
+            Ok(Ok(res)) => {
+                delivery.status_code = Some(res.status().as_u16());
+                delivery.success = res.status().is_success();
+                delivery.last_error = None;
+            }
+            Ok(Err(e)) => delivery.last_error = Some(e.to_string()),
+            Err(_) => delivery.last_error = Some("request timed out".to_string()),
+        }
+        delivery.updated_time = Utc::now();
+
+        // 配送中にフックが削除されていてもリトライは続ける
+        let _ = repo.save_delivery(&delivery);
+        if delivery.success {
+            return;
+        }
+
+        if delivery.attempts < MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    warn!(
+        "outgoing hook delivery failed after {} attempts: {}",
+        delivery.attempts, delivery.delivery_id
+    );
+}
+
+// 受信側はシークレットで同じ計算を行い、ペイロードの改ざんを検知できる
+// 時刻も署名に含めるため、受信側は古い時刻のリクエストを拒否してリプレイを防げる
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{extract::State, routing::post, Router};
+    use http::{HeaderMap, StatusCode};
+
+    use crate::{
+        domain::entity::{hook_event::HookEventKind, pub_user_info::PubUserInfo},
+        infrastructure::repository::room_repository_impl::test_room_info,
+    };
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Received {
+        requests: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    }
+
+    // 最初のリクエストだけ失敗するスタブサーバー
+    async fn set_up_stub() -> (String, Received) {
+        async fn callback(
+            State(received): State<Received>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> StatusCode {
+            let mut requests = received.requests.lock().unwrap();
+            requests.push((headers, body));
+            if requests.len() == 1 {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::OK
+            }
+        }
+
+        let received = Received::default();
+        let app = Router::new()
+            .route("/callback", post(callback))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/callback", addr), received)
+    }
+
+    #[test]
+    fn test_sign() {
+        let signature = sign("secret", 1_700_000_000, b"payload");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature, sign("secret", 1_700_000_000, b"payload"));
+        assert_ne!(signature, sign("other_secret", 1_700_000_000, b"payload"));
+        // 時刻を変えると署名も変わる
+        assert_ne!(signature, sign("secret", 1_700_000_001, b"payload"));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_with_retry() {
+        let (callback_url, received) = set_up_stub().await;
+        let db = OutgoingHookDb::new().with_target_policy(HookTargetPolicy {
+            allow_insecure: true,
+        });
+        let repo = OutgoingHookRepositoryImpl::new(db.clone());
+        let hook = repo
+            .create_hook(
+                "room_id",
+                &callback_url,
+                "secret",
+                &[HookEventKind::MemberJoined],
+                "user_id",
+            )
+            .unwrap();
+
+        // テスト対象
+        let user_info = PubUserInfo {
+            user_id: "user_id".to_string(),
+            user_name: "user_name".to_string(),
+        };
+        let mut jobs = EventNotifierImpl::new(db.clone())
+            .prepare("room_id", HookEvent::MemberJoined(user_info));
+        assert_eq!(jobs.len(), 1);
+        // 1回目の失敗後、バックオフしてから2回目で成功する
+        deliver(db, jobs.remove(0), Duration::ZERO).await;
+
+        let requests = received.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        let (headers, body) = &requests[1];
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers[SIGNATURE_HEADER], sign("secret", timestamp, body));
+        assert_eq!(headers[EVENT_HEADER], "memberJoined");
+
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["event"], "memberJoined");
+        assert_eq!(payload["data"]["userId"], "user_id");
+
+        let deliveries = repo.get_deliveries(&hook.hook_id).unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert!(deliveries[0].success);
+        assert_eq!(deliveries[0].status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_rejects_private_target() {
+        let (callback_url, received) = set_up_stub().await;
+        // 開発用の設定でなければループバックアドレスには送信しない
+        let db = OutgoingHookDb::new();
+        let repo = OutgoingHookRepositoryImpl::new(db.clone());
+        let hook = repo
+            .create_hook(
+                "room_id",
+                &callback_url,
+                "secret",
+                &[HookEventKind::MemberJoined],
+                "user_id",
+            )
+            .unwrap();
+
+        // テスト対象
+        let user_info = PubUserInfo {
+            user_id: "user_id".to_string(),
+            user_name: "user_name".to_string(),
+        };
+        let mut jobs = EventNotifierImpl::new(db.clone())
+            .prepare("room_id", HookEvent::MemberJoined(user_info));
+        deliver(db, jobs.remove(0), Duration::ZERO).await;
+
+        assert!(received.requests.lock().unwrap().is_empty());
+        let deliveries = repo.get_deliveries(&hook.hook_id).unwrap();
+        assert_eq!(deliveries[0].attempts, 1);
+        assert!(!deliveries[0].success);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_and_remove_hooks() {
+        let (callback_url, received) = set_up_stub().await;
+        let db = OutgoingHookDb::new().with_target_policy(HookTargetPolicy {
+            allow_insecure: true,
+        });
+        let room_info = test_room_info("room_id");
+        let room_id = room_info.room_id.clone();
+        let repo = OutgoingHookRepositoryImpl::new(db.clone());
+        repo.create_hook(
+            &room_id,
+            &callback_url,
+            "secret",
+            &[HookEventKind::RoomDeleted],
+            "user_id",
+        )
+        .unwrap();
+
+        // テスト対象
+        let jobs = EventNotifierImpl::new(db.clone())
+            .prepare(&room_id, HookEvent::RoomDeleted((&room_info).into()));
+        deliver_and_remove_hooks(db, room_id.clone(), jobs, Duration::ZERO).await;
+
+        // フックを削除する前にリトライまで終わっている
+        let requests = received.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].0[EVENT_HEADER], "roomDeleted");
+        // 参加コードはルームの外に送らない
+        let payload: serde_json::Value = serde_json::from_slice(&requests[1].1).unwrap();
+        assert_eq!(payload["data"]["roomId"], "room_id");
+        assert!(payload["data"].get("joinCode").is_none());
+        assert!(repo.get_room_hooks(&room_id).unwrap().is_empty());
+    }
+}
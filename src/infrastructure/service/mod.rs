@@ -1,3 +1,8 @@
+pub mod event_notifier_impl;
 pub mod password_hash_service_impl;
+pub mod secret_gen_impl;
+pub mod slack_archive_reader;
 pub mod token_service_impl;
+pub mod transcript_encoder_impl;
 pub mod uuid_gen_impl;
+pub mod zip_reader;
@@ -23,6 +23,7 @@ static ARGON2: LazyLock<Argon2> = LazyLock::new(|| {
     argon
 });
 
+#[derive(Clone, Copy)]
 pub struct PasswordHashServiceImpl;
 
 impl PasswordHashService for PasswordHashServiceImpl {
@@ -45,7 +46,7 @@ impl PasswordHashService for PasswordHashServiceImpl {
         password_hash: &str,
     ) -> Result<bool, crate::domain::service::error::ServiceError> {
         let argon2 = &ARGON2;
-        let hash_password = PasswordHash::new(&password_hash).map_err(|_| ServiceError::ToHash)?;
+        let hash_password = PasswordHash::new(password_hash).map_err(|_| ServiceError::ToHash)?;
         match argon2.verify_password(password.as_bytes(), &hash_password) {
             Ok(_) => Ok(true),
             Err(e) => match e {
@@ -79,7 +80,7 @@ mod test {
 
         let hash_password = PasswordHashServiceImpl.to_hash_pwd(password).unwrap();
         let verify_result = PasswordHashServiceImpl
-            .verify_pwd(&wrong_password, &hash_password)
+            .verify_pwd(wrong_password, &hash_password)
             .unwrap();
         assert!(!verify_result);
     }
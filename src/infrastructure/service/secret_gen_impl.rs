@@ -0,0 +1,15 @@
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use rand_core::{OsRng, RngCore};
+
+use crate::domain::service::util::secret_gen::SecretGen;
+
+// URLに含めても安全な推測困難な文字列を生成する
+pub struct SecretGenImpl;
+
+impl SecretGen for SecretGenImpl {
+    fn gen_secret(&self) -> String {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        BASE64_URL_SAFE_NO_PAD.encode(secret)
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::domain::{
+    entity::{
+        import_report::SkippedItem,
+        slack_archive::{SlackArchive, SlackChannel, SlackMessage, SlackUser},
+    },
+    service::error::ServiceError,
+};
+
+use super::zip_reader::read_zip;
+
+// エクスポート直下にあるが取り込まないファイル
+const IGNORED_ROOT_FILES: [&str; 7] = [
+    "channels.json",
+    "groups.json",
+    "dms.json",
+    "mpims.json",
+    "integration_logs.json",
+    "canvases.json",
+    "org_users.json",
+];
+
+// 取り込み用ディレクトリからの相対パスを解決する
+// シンボリックリンクや".."でディレクトリの外を指す場合は拒否する
+pub fn resolve_import_path(import_dir: &Path, path: &str) -> Result<PathBuf, ServiceError> {
+    let import_dir = import_dir
+        .canonicalize()
+        .map_err(|_| ServiceError::Server)?;
+    let resolved = import_dir
+        .join(path)
+        .canonicalize()
+        .map_err(|_| ServiceError::NotFound)?;
+    if resolved.starts_with(&import_dir) {
+        Ok(resolved)
+    } else {
+        Err(ServiceError::Forbidden)
+    }
+}
+
+// ディレクトリかZIPファイルのパスからエクスポートを読み込む
+// ブロッキングIOを行うためspawn_blockingから呼ぶこと
+pub fn read_archive_path(path: &Path) -> Result<SlackArchive, ServiceError> {
+    let metadata = fs::metadata(path).map_err(|_| ServiceError::NotFound)?;
+    if metadata.is_dir() {
+        let mut files = Vec::new();
+        collect_files(path, PathBuf::new(), &mut files)?;
+        Ok(parse_archive(files))
+    } else {
+        let data = fs::read(path).map_err(|_| ServiceError::Server)?;
+        read_archive_zip(&data)
+    }
+}
+
+pub fn read_archive_zip(data: &[u8]) -> Result<SlackArchive, ServiceError> {
+    let files = read_zip(data).map_err(|_| ServiceError::Validation)?;
+    Ok(parse_archive(files))
+}
+
+fn collect_files(
+    root: &Path,
+    relative: PathBuf,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> Result<(), ServiceError> {
+    let entries = fs::read_dir(root.join(&relative)).map_err(|_| ServiceError::Server)?;
+    for entry in entries {
+        let entry = entry.map_err(|_| ServiceError::Server)?;
+        let file_type = entry.file_type().map_err(|_| ServiceError::Server)?;
+        let relative = relative.join(entry.file_name());
+        if file_type.is_dir() {
+            collect_files(root, relative, files)?;
+        } else if file_type.is_file() {
+            let content = fs::read(entry.path()).map_err(|_| ServiceError::Server)?;
+            files.push((relative.to_string_lossy().replace('\\', "/"), content));
+        }
+    }
+    Ok(())
+}
+
+// users.json, <channel>/<date>.jsonを読み込む
+// 読めないファイルやメッセージはskippedに記録して処理を続ける
+pub fn parse_archive(mut files: Vec<(String, Vec<u8>)>) -> SlackArchive {
+    let mut archive = SlackArchive::default();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // ZIPの場合はトップレベルのディレクトリに包まれていることがあるため
+    // users.jsonかchannels.jsonがある階層を基準にする
+    let root = files
+        .iter()
+        .filter_map(|(name, _)| {
+            name.strip_suffix("users.json")
+                .or_else(|| name.strip_suffix("channels.json"))
+        })
+        .filter(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+        .min_by_key(|prefix| prefix.len())
+        .unwrap_or("")
+        .to_string();
+
+    for (name, content) in files {
+        if name.ends_with('/') {
+            continue;
+        }
+        let Some(relative) = name.strip_prefix(&root) else {
+            archive
+                .skipped
+                .push(skipped(&name, "outside of the export root"));
+            continue;
+        };
+        let parts: Vec<&str> = relative.split('/').collect();
+        match parts.as_slice() {
+            ["users.json"] => match serde_json::from_slice::<Vec<SlackUser>>(&content) {
+                Ok(users) => archive.users = users,
+                Err(e) => archive.skipped.push(skipped(relative, &e.to_string())),
+            },
+            [file] if IGNORED_ROOT_FILES.contains(file) => {}
+            [channel, file] if file.ends_with(".json") => {
+                let messages = parse_messages(relative, &content, &mut archive.skipped);
+                match archive.channels.iter_mut().find(|c| c.name == *channel) {
+                    Some(found) => found.messages.extend(messages),
+                    None => archive.channels.push(SlackChannel {
+                        name: channel.to_string(),
+                        messages,
+                    }),
+                }
+            }
+            _ => archive.skipped.push(skipped(relative, "unsupported file")),
+        }
+    }
+    archive
+}
+
+fn parse_messages(
+    location: &str,
+    content: &[u8],
+    skipped_items: &mut Vec<SkippedItem>,
+) -> Vec<SlackMessage> {
+    let values = match serde_json::from_slice::<Vec<Value>>(content) {
+        Ok(values) => values,
+        Err(e) => {
+            skipped_items.push(skipped(location, &e.to_string()));
+            return Vec::new();
+        }
+    };
+    values
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, value)| match serde_json::from_value(value) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                skipped_items.push(skipped(&format!("{}[{}]", location, i), &e.to_string()));
+                None
+            }
+        })
+        .collect()
+}
+
+fn skipped(location: &str, reason: &str) -> SkippedItem {
+    SkippedItem {
+        location: location.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(name: &str, content: &str) -> (String, Vec<u8>) {
+        (name.to_string(), content.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_parse_archive() {
+        let files = vec![
+            file(
+                "export/users.json",
+                r#"[{"id": "U1", "name": "alice", "profile": {"display_name": "Alice"}}]"#,
+            ),
+            file(
+                "export/channels.json",
+                r#"[{"id": "C1", "name": "general"}]"#,
+            ),
+            file(
+                "export/general/2024-10-01.json",
+                r#"[{"user": "U1", "text": "hello", "ts": "1727740800.000100"}, {"text": "no ts"}]"#,
+            ),
+            file(
+                "export/general/2024-10-02.json",
+                r#"[{"user": "U1", "text": "again", "ts": "1727827200.000100"}]"#,
+            ),
+            file("export/random/2024-10-01.json", "not json"),
+            file("export/general/files/image.png", ""),
+        ];
+
+        let archive = parse_archive(files);
+        assert_eq!(archive.users.len(), 1);
+        assert_eq!(archive.users[0].display_name(), "Alice");
+        assert_eq!(archive.channels.len(), 2);
+        let general = archive
+            .channels
+            .iter()
+            .find(|c| c.name == "general")
+            .unwrap();
+        assert_eq!(general.messages.len(), 2);
+
+        let locations: Vec<&str> = archive
+            .skipped
+            .iter()
+            .map(|s| s.location.as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                "general/2024-10-01.json[1]",
+                "general/files/image.png",
+                "random/2024-10-01.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_import_path() {
+        let root = std::env::temp_dir().join(format!("slack_import_{}", uuid::Uuid::new_v4()));
+        let import_dir = root.join("import");
+        fs::create_dir_all(import_dir.join("export")).unwrap();
+        fs::write(root.join("secret.txt"), "secret").unwrap();
+
+        let resolved = resolve_import_path(&import_dir, "export").unwrap();
+        assert!(resolved.ends_with("import/export"));
+        assert!(matches!(
+            resolve_import_path(&import_dir, "../secret.txt"),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(matches!(
+            resolve_import_path(&import_dir, "/etc"),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(matches!(
+            resolve_import_path(&import_dir, "missing"),
+            Err(ServiceError::NotFound)
+        ));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_read_archive_zip_invalid() {
+        assert!(matches!(
+            read_archive_zip(b"not a zip"),
+            Err(ServiceError::Validation)
+        ));
+    }
+}
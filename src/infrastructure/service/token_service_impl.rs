@@ -35,7 +35,7 @@ fn read_key_from_file() -> String {
 fn generate_key() -> String {
     let mut key = [0u8; 32];
     OsRng.fill_bytes(&mut key);
-    BASE64_STANDARD.encode(&key)
+    BASE64_STANDARD.encode(key)
 }
 
 fn create_key_file(key: &str) -> Result<(), std::io::Error> {
@@ -155,7 +155,7 @@ mod test {
         let token = service.encode(&claims).expect("failed to create token");
 
         // 別のキーで検証
-        let invalid_key = BASE64_STANDARD.encode(&[1u8; 32]);
+        let invalid_key = BASE64_STANDARD.encode([1u8; 32]);
         let result = decode::<Claims>(
             &token,
             &DecodingKey::from_secret(invalid_key.as_bytes()),
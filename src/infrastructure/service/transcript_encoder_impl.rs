@@ -0,0 +1,162 @@
+use crate::domain::{
+    entity::{chat::Chat, export_query::ExportFormat, room_info::RoomInfo},
+    service::util::transcript_encoder::TranscriptEncoder,
+};
+
+pub struct TranscriptEncoderImpl {
+    format: ExportFormat,
+}
+
+impl TranscriptEncoderImpl {
+    pub fn new(format: ExportFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl TranscriptEncoder for TranscriptEncoderImpl {
+    fn content_type(&self) -> &'static str {
+        match self.format {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    fn file_extension(&self) -> &'static str {
+        match self.format {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    fn header(&self, room_info: &RoomInfo) -> String {
+        match self.format {
+            ExportFormat::Json => "[".to_string(),
+            ExportFormat::Csv => "messageId,time,userId,userName,text\r\n".to_string(),
+            ExportFormat::Html => format!(
+                concat!(
+                    "<!DOCTYPE html>\n<html lang=\"ja\">\n<head>\n",
+                    "<meta charset=\"utf-8\">\n<title>{name}</title>\n",
+                    "<style>",
+                    "body{{font-family:sans-serif;margin:2em}}",
+                    "table{{border-collapse:collapse;width:100%}}",
+                    "th,td{{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:top}}",
+                    "td.text{{white-space:pre-wrap}}",
+                    "</style>\n</head>\n<body>\n<h1>{name}</h1>\n",
+                    "<table>\n<tr><th>Time</th><th>User</th><th>Message</th></tr>\n"
+                ),
+                name = escape_html(&room_info.room_name),
+            ),
+        }
+    }
+
+    fn row(&self, chat: &Chat, index: usize) -> String {
+        match self.format {
+            ExportFormat::Json => {
+                let separator = if index == 0 { "" } else { "," };
+                // Chatのシリアライズは失敗しない
+                let json = serde_json::to_string(chat).unwrap_or_default();
+                format!("{}{}", separator, json)
+            }
+            ExportFormat::Csv => format!(
+                "{},{},{},{},{}\r\n",
+                escape_csv(&chat.message_id),
+                chat.time.to_rfc3339(),
+                escape_csv(&chat.user_id),
+                escape_csv(&chat.user_name),
+                escape_csv(&chat.text),
+            ),
+            ExportFormat::Html => format!(
+                "<tr><td>{}</td><td>{}</td><td class=\"text\">{}</td></tr>\n",
+                chat.time.to_rfc3339(),
+                escape_html(&chat.user_name),
+                escape_html(&chat.text),
+            ),
+        }
+    }
+
+    fn footer(&self) -> String {
+        match self.format {
+            ExportFormat::Json => "]".to_string(),
+            ExportFormat::Csv => String::new(),
+            ExportFormat::Html => "</table>\n</body>\n</html>\n".to_string(),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// RFC 4180に従ってクォートする
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::infrastructure::repository::room_repository_impl::test_room_info;
+
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        let escaped = escape_html("<script>alert(\"x\" & 'y')</script>");
+        assert_eq!(
+            escaped,
+            "&lt;script&gt;alert(&quot;x&quot; &amp; &#39;y&#39;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!(escape_csv("plain"), "plain");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_csv("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn test_json_rows() {
+        let encoder = TranscriptEncoderImpl::new(ExportFormat::Json);
+        let first = Chat::from_str("user_id", "user_name", "first");
+        let second = Chat::from_str("user_id", "user_name", "second");
+
+        let json = format!(
+            "{}{}{}{}",
+            encoder.header(&test_room_info("room_id")),
+            encoder.row(&first, 0),
+            encoder.row(&second, 1),
+            encoder.footer()
+        );
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1]["text"], "second");
+    }
+
+    #[test]
+    fn test_html_row_escaped() {
+        let encoder = TranscriptEncoderImpl::new(ExportFormat::Html);
+        let chat = Chat::from_str("user_id", "<b>name</b>", "<img src=x onerror=alert(1)>");
+
+        let row = encoder.row(&chat, 0);
+        assert!(!row.contains("<img"));
+        assert!(!row.contains("<b>"));
+    }
+}
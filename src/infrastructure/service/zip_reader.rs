@@ -0,0 +1,138 @@
+// Slackのエクスポートを読むためのZIPリーダー
+// 暗号化されたエントリには対応しない
+
+use std::io::{Cursor, Read};
+
+use thiserror::Error;
+use zip::{result::ZipError as ArchiveError, ZipArchive};
+
+// 展開後のサイズの上限(ZIP爆弾対策)
+// ヘッダーに書かれたサイズは信用せず、展開しながら数えて上限を超えた時点で止める
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+const MAX_TOTAL_SIZE: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ZipError {
+    #[error("invalid zip archive: {0}")]
+    Invalid(#[from] ArchiveError),
+    #[error("zip archive too large")]
+    TooLarge,
+}
+
+// アーカイブ内のファイルを(パス, 内容)の組で返す
+pub fn read_zip(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, ZipError> {
+    read_zip_limited(data, MAX_ENTRY_SIZE, MAX_TOTAL_SIZE)
+}
+
+fn read_zip_limited(
+    data: &[u8],
+    max_entry_size: u64,
+    max_total_size: u64,
+) -> Result<Vec<(String, Vec<u8>)>, ZipError> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let mut files = Vec::with_capacity(archive.len());
+    let mut total = 0;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+
+        // 上限を1バイトでも超えたら展開を止める。CRCは最後まで読んだ時に検証される
+        let limit = max_entry_size.min(max_total_size - total);
+        let mut content = Vec::new();
+        entry
+            .take(limit + 1)
+            .read_to_end(&mut content)
+            .map_err(ArchiveError::Io)?;
+        if content.len() as u64 > limit {
+            return Err(ZipError::TooLarge);
+        }
+        total += content.len() as u64;
+        files.push((name, content));
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    // python: zipfile.ZipFile(buf, "w", zipfile.ZIP_DEFLATED) で作成したアーカイブ
+    // "stored.txt"は無圧縮、"dir/deflated.json"は動的ハフマン符号、
+    // "fixed.txt"は固定ハフマン符号で圧縮されている
+    const FIXTURE: &[u8] = include_bytes!("testdata/fixture.zip");
+
+    // 同じ内容を繰り返したファイルを圧縮して、展開後だけ大きいアーカイブを作る
+    fn compressed_zip(entries: &[(&str, usize)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, size) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(&vec![b'a'; *size]).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let files = read_zip(FIXTURE).unwrap();
+        assert_eq!(files.len(), 3);
+
+        let (name, content) = &files[0];
+        assert_eq!(name, "stored.txt");
+        assert_eq!(content, b"hello zip");
+
+        let (name, content) = &files[1];
+        assert_eq!(name, "dir/deflated.json");
+        let expected: String = (0..200)
+            .map(|i| format!("{{\"text\":\"message {}\"}}\n", i))
+            .collect();
+        assert_eq!(String::from_utf8_lossy(content), expected);
+
+        let (name, content) = &files[2];
+        assert_eq!(name, "fixed.txt");
+        assert_eq!(content, b"short text, short text");
+    }
+
+    #[test]
+    fn test_not_zip() {
+        assert!(matches!(
+            read_zip(b"not a zip file at all, clearly"),
+            Err(ZipError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_zip() {
+        let mut corrupted = FIXTURE.to_vec();
+        // 圧縮データの途中を書き換える
+        let middle = corrupted.len() / 2;
+        corrupted[middle] ^= 0xff;
+        assert!(read_zip(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_size_limit() {
+        // 1つのエントリが上限を超える
+        let data = compressed_zip(&[("large.json", 10_000)]);
+        assert!(data.len() < 1_000);
+        assert!(matches!(
+            read_zip_limited(&data, 1_000, 1_000_000),
+            Err(ZipError::TooLarge)
+        ));
+
+        // それぞれは上限以内だが合計が上限を超える
+        let data = compressed_zip(&[("a.json", 600), ("b.json", 600)]);
+        assert!(matches!(
+            read_zip_limited(&data, 1_000, 1_000),
+            Err(ZipError::TooLarge)
+        ));
+        assert_eq!(read_zip_limited(&data, 1_000, 1_200).unwrap().len(), 2);
+    }
+}
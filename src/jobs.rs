@@ -0,0 +1,4 @@
+pub mod message_sweeper;
+pub mod retention_purger;
+pub mod room_reaper;
+pub mod sanction_sweeper;
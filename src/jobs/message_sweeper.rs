@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::{
+    domain::service::message_service::MessageServices,
+    infrastructure::repository::{
+        message_repository_impl::MessageRepositoryImpl, room_repository_impl::RoomRepositoryImpl,
+    },
+    MessageDb, RoomDb,
+};
+
+// 一定間隔で期限切れのメッセージを削除するバックグラウンドタスク
+pub fn spawn_message_sweeper(
+    room_db: RoomDb,
+    message_db: MessageDb,
+    period: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+
+            let services = MessageServices::new(
+                MessageRepositoryImpl::new(message_db.clone()),
+                RoomRepositoryImpl::new(room_db.clone()),
+            );
+            match services.sweep_expired_messages().await {
+                Ok(0) => {}
+                Ok(count) => debug!("swept {} expired messages", count),
+                Err(e) => warn!("message sweeper error: {:?}", e),
+            }
+        }
+    })
+}
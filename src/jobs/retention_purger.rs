@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::{
+    domain::{
+        entity::retention_policy::RetentionLimits, service::retention_service::RetentionServices,
+    },
+    infrastructure::repository::{
+        message_repository_impl::MessageRepositoryImpl, room_repository_impl::RoomRepositoryImpl,
+    },
+    MessageDb, RoomDb,
+};
+
+// 一定間隔でルームの保持期間を過ぎたメッセージを削除するバックグラウンドタスク
+pub fn spawn_retention_purger(
+    room_db: RoomDb,
+    message_db: MessageDb,
+    limits: RetentionLimits,
+    period: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+
+            let services = RetentionServices::new(
+                MessageRepositoryImpl::new(message_db.clone()),
+                RoomRepositoryImpl::new(room_db.clone()),
+            );
+            match services.purge_messages(limits, Utc::now()).await {
+                Ok(purges) => {
+                    for purge in purges {
+                        info!(
+                            "purged {} messages from room {} by retention policy {:?}",
+                            purge.count, purge.room_id, purge.policy
+                        );
+                    }
+                }
+                Err(e) => warn!("retention purger error: {:?}", e),
+            }
+        }
+    })
+}
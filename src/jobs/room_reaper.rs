@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::{
+    domain::{
+        entity::idle_expiry::IdleExpiry,
+        service::{
+            room_cleanup_service::RoomCleanupServices, room_expiry_service::RoomExpiryServices,
+        },
+    },
+    infrastructure::{
+        repository::{
+            favorite_repository_impl::FavoriteRepositoryImpl,
+            incoming_hook_repository_impl::IncomingHookRepositoryImpl,
+            membership_repository_impl::MembershipRepositoryImpl,
+            message_repository_impl::MessageRepositoryImpl,
+            room_repository_impl::RoomRepositoryImpl,
+        },
+        service::event_notifier_impl::EventNotifierImpl,
+    },
+    FavoriteDb, IncomingHookDb, MembershipDb, MessageDb, OutgoingHookDb, RoomDb,
+};
+
+// 一定間隔で接続も投稿もないまま期限を過ぎたルームと、削除の猶予期間が過ぎたルームを削除するバックグラウンドタスク
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_room_reaper(
+    room_db: RoomDb,
+    membership_db: MembershipDb,
+    message_db: MessageDb,
+    hook_db: IncomingHookDb,
+    outgoing_hook_db: OutgoingHookDb,
+    favorite_db: FavoriteDb,
+    expiry: IdleExpiry,
+    delete_grace: chrono::Duration,
+    period: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+
+            let services = RoomExpiryServices::new(
+                RoomRepositoryImpl::new(room_db.clone()),
+                MembershipRepositoryImpl::new(membership_db.clone()),
+                EventNotifierImpl::new(outgoing_hook_db.clone()),
+            );
+            let now = Utc::now();
+            let mut removed = Vec::new();
+            let sweeps = [
+                services.reap_idle_rooms(expiry, now).await,
+                services.purge_deleted_rooms(delete_grace, now).await,
+            ];
+            for sweep in sweeps {
+                match sweep {
+                    Ok(sweep) => {
+                        for (room_id, e) in sweep.failed {
+                            warn!("room reaper error in room {}: {:?}", room_id, e);
+                        }
+                        removed.extend(sweep.removed);
+                    }
+                    Err(e) => warn!("room reaper error: {:?}", e),
+                }
+            }
+            let cleanup_services = RoomCleanupServices::new(
+                MessageRepositoryImpl::new(message_db.clone()),
+                IncomingHookRepositoryImpl::new(hook_db.clone()),
+                FavoriteRepositoryImpl::new(favorite_db.clone()),
+                EventNotifierImpl::new(outgoing_hook_db.clone()),
+            );
+            for room_info in removed {
+                info!("removed room {}", room_info.room_id);
+                if let Err(e) = cleanup_services.delete_room_resources(room_info) {
+                    warn!("room reaper error: {:?}", e);
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::{
+    domain::service::moderation_service::ModerationServices,
+    infrastructure::repository::{
+        membership_repository_impl::MembershipRepositoryImpl,
+        room_repository_impl::RoomRepositoryImpl,
+    },
+    MembershipDb, RoomDb,
+};
+
+// 一定間隔で期限切れのBAN・ミュートを削除するバックグラウンドタスク
+pub fn spawn_sanction_sweeper(
+    room_db: RoomDb,
+    membership_db: MembershipDb,
+    period: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+
+            let services = ModerationServices::new(
+                MembershipRepositoryImpl::new(membership_db.clone()),
+                RoomRepositoryImpl::new(room_db.clone()),
+            );
+            match services.sweep_expired_sanctions() {
+                Ok(0) => {}
+                Ok(count) => debug!("swept {} expired sanctions", count),
+                Err(e) => warn!("sanction sweeper error: {:?}", e),
+            }
+        }
+    })
+}
@@ -1,40 +1,136 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use axum::extract::FromRef;
-use domain::entity::room::Room;
+use domain::entity::{
+    audit_entry::AuditEntry,
+    chat::Chat,
+    favorite::Favorite,
+    hook_delivery::HookDelivery,
+    hook_target_policy::HookTargetPolicy,
+    import_config::ImportConfig,
+    incoming_hook::IncomingHook,
+    invitation::Invitation,
+    invite_link::InviteLink,
+    membership::Membership,
+    orphan_policy::OrphanPolicy,
+    outgoing_hook::OutgoingHook,
+    ownership_transfer::OwnershipTransfer,
+    retention_policy::RetentionLimits,
+    retention_purge::RetentionPurge,
+    sanction::Sanction,
+    submission::{Submission, SubmissionKey},
+};
+use infrastructure::room_actor::RoomSupervisor;
 use sqlx::PgPool;
 
 pub mod domain;
 pub mod handlers;
 pub mod infrastructure;
+pub mod jobs;
 pub mod route;
 
 mod util;
 
+// 接続中のメンバーがいないルームのタスクを休止するまでの時間
+const ROOM_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
 #[derive(Debug, Clone)]
 pub struct AppState {
     room_db: RoomDb,
     user_db: UserDb,
+    message_db: MessageDb,
+    incoming_hook_db: IncomingHookDb,
+    outgoing_hook_db: OutgoingHookDb,
+    membership_db: MembershipDb,
+    favorite_db: FavoriteDb,
+    orphan_policy: OrphanPolicy,
+    retention_limits: RetentionLimits,
+    import_config: ImportConfig,
 }
 
 impl AppState {
-    pub fn new(room_db: RoomDb, user_db: UserDb) -> Self {
-        Self { room_db, user_db }
+    pub fn new(
+        room_db: RoomDb,
+        user_db: UserDb,
+        message_db: MessageDb,
+        incoming_hook_db: IncomingHookDb,
+        outgoing_hook_db: OutgoingHookDb,
+        membership_db: MembershipDb,
+        favorite_db: FavoriteDb,
+    ) -> Self {
+        Self {
+            room_db,
+            user_db,
+            message_db,
+            incoming_hook_db,
+            outgoing_hook_db,
+            membership_db,
+            favorite_db,
+            orphan_policy: OrphanPolicy::default(),
+            retention_limits: RetentionLimits::default(),
+            import_config: ImportConfig::default(),
+        }
+    }
+
+    pub fn with_orphan_policy(mut self, orphan_policy: OrphanPolicy) -> Self {
+        self.orphan_policy = orphan_policy;
+        self
+    }
+
+    pub fn with_retention_limits(mut self, retention_limits: RetentionLimits) -> Self {
+        self.retention_limits = retention_limits;
+        self
+    }
+
+    pub fn with_import_config(mut self, import_config: ImportConfig) -> Self {
+        self.import_config = import_config;
+        self
+    }
+}
+
+impl FromRef<AppState> for OrphanPolicy {
+    fn from_ref(input: &AppState) -> Self {
+        input.orphan_policy
+    }
+}
+
+impl FromRef<AppState> for RetentionLimits {
+    fn from_ref(input: &AppState) -> Self {
+        input.retention_limits
+    }
+}
+
+impl FromRef<AppState> for ImportConfig {
+    fn from_ref(input: &AppState) -> Self {
+        input.import_config.clone()
     }
 }
 
+// ルームはそれぞれのタスクが状態を持ち、スーパーバイザーを通して操作する
 #[derive(Debug, Clone)]
 pub struct RoomDb {
-    pub pool: Arc<RwLock<HashMap<String, Room>>>,
+    pub supervisor: RoomSupervisor,
+}
+
+impl Default for RoomDb {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RoomDb {
+    // tokioのランタイム上で呼ぶこと
     pub fn new() -> Self {
+        Self::with_idle_timeout(ROOM_IDLE_TIMEOUT)
+    }
+
+    pub fn with_idle_timeout(idle_timeout: Duration) -> Self {
         Self {
-            pool: Arc::default(),
+            supervisor: RoomSupervisor::spawn(idle_timeout),
         }
     }
 }
@@ -55,7 +151,7 @@ impl UserDb {
         Ok(Self {
             pool: PgPool::connect(database_url).await?,
         })
-    } 
+    }
 }
 
 impl FromRef<AppState> for UserDb {
@@ -63,3 +159,159 @@ impl FromRef<AppState> for UserDb {
         input.user_db.clone()
     }
 }
+
+// ルームIDごとのメッセージ履歴と、重複送信の判定に使う受付済みのメッセージ
+// 保持期間によって削除したメッセージの記録もルームIDごとに持つ
+#[derive(Debug, Clone)]
+pub struct MessageDb {
+    pub pool: Arc<RwLock<HashMap<String, Vec<Chat>>>>,
+    pub submissions: Arc<RwLock<HashMap<SubmissionKey, Submission>>>,
+    pub purges: Arc<RwLock<HashMap<String, Vec<RetentionPurge>>>>,
+}
+
+impl Default for MessageDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageDb {
+    pub fn new() -> Self {
+        Self {
+            pool: Arc::default(),
+            submissions: Arc::default(),
+            purges: Arc::default(),
+        }
+    }
+}
+
+impl FromRef<AppState> for MessageDb {
+    fn from_ref(input: &AppState) -> Self {
+        input.message_db.clone()
+    }
+}
+
+// トークンごとの受信Webhook
+#[derive(Debug, Clone)]
+pub struct IncomingHookDb {
+    pub pool: Arc<RwLock<HashMap<String, IncomingHook>>>,
+}
+
+impl Default for IncomingHookDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncomingHookDb {
+    pub fn new() -> Self {
+        Self {
+            pool: Arc::default(),
+        }
+    }
+}
+
+impl FromRef<AppState> for IncomingHookDb {
+    fn from_ref(input: &AppState) -> Self {
+        input.incoming_hook_db.clone()
+    }
+}
+
+// フックIDごとの送信Webhookと配送ログ、送信先の制限
+#[derive(Debug, Clone)]
+pub struct OutgoingHookDb {
+    pub pool: Arc<RwLock<HashMap<String, OutgoingHook>>>,
+    pub deliveries: Arc<RwLock<HashMap<String, VecDeque<HookDelivery>>>>,
+    pub target_policy: HookTargetPolicy,
+}
+
+impl Default for OutgoingHookDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutgoingHookDb {
+    pub fn new() -> Self {
+        Self {
+            pool: Arc::default(),
+            deliveries: Arc::default(),
+            target_policy: HookTargetPolicy::default(),
+        }
+    }
+
+    pub fn with_target_policy(self, target_policy: HookTargetPolicy) -> Self {
+        Self {
+            target_policy,
+            ..self
+        }
+    }
+}
+
+impl FromRef<AppState> for OutgoingHookDb {
+    fn from_ref(input: &AppState) -> Self {
+        input.outgoing_hook_db.clone()
+    }
+}
+
+// ルームIDごとのメンバーと制裁と未処理の譲渡と監査ログ、招待IDごとの未処理の招待、トークンごとの招待リンク
+#[derive(Debug, Clone)]
+pub struct MembershipDb {
+    pub pool: Arc<RwLock<HashMap<String, HashMap<String, Membership>>>>,
+    pub invitations: Arc<RwLock<HashMap<String, Invitation>>>,
+    pub invite_links: Arc<RwLock<HashMap<String, InviteLink>>>,
+    pub sanctions: Arc<RwLock<HashMap<String, Vec<Sanction>>>>,
+    pub transfers: Arc<RwLock<HashMap<String, OwnershipTransfer>>>,
+    pub audit_log: Arc<RwLock<HashMap<String, Vec<AuditEntry>>>>,
+}
+
+impl Default for MembershipDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MembershipDb {
+    pub fn new() -> Self {
+        Self {
+            pool: Arc::default(),
+            invitations: Arc::default(),
+            invite_links: Arc::default(),
+            sanctions: Arc::default(),
+            transfers: Arc::default(),
+            audit_log: Arc::default(),
+        }
+    }
+}
+
+impl FromRef<AppState> for MembershipDb {
+    fn from_ref(input: &AppState) -> Self {
+        input.membership_db.clone()
+    }
+}
+
+// ユーザーIDごとのお気に入りのルーム。ユーザーが指定した順に並ぶ
+#[derive(Debug, Clone)]
+pub struct FavoriteDb {
+    pub pool: Arc<RwLock<HashMap<String, Vec<Favorite>>>>,
+}
+
+impl Default for FavoriteDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FavoriteDb {
+    pub fn new() -> Self {
+        Self {
+            pool: Arc::default(),
+        }
+    }
+}
+
+impl FromRef<AppState> for FavoriteDb {
+    fn from_ref(input: &AppState) -> Self {
+        input.favorite_db.clone()
+    }
+}
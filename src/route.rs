@@ -1,26 +1,66 @@
 use axum::{
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, post, put},
     Router,
 };
 use http::{
-    header::{ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE},
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE,
+    },
     HeaderValue, Method,
 };
 use tower_http::cors::CorsLayer;
 
 use crate::{
     handlers::{
+        audit::get_audit_log_handler,
         auth::login,
         chat::chat_handler_with_upgrade,
+        export::export_room_handler,
+        favorites::{delete_favorite_handler, get_favorites_handler, put_favorite_handler},
+        hooks::{
+            create_hook_handler, get_room_hooks_handler, post_hook_message_handler,
+            revoke_hook_handler,
+        },
+        import::import_slack_handler,
+        invite_links::{
+            accept_invite_link_handler, create_invite_link_handler, get_room_invite_links_handler,
+            revoke_invite_link_handler,
+        },
+        membership::{
+            accept_invitation_handler, create_invitation_handler, decline_invitation_handler,
+            get_my_invitations_handler, get_room_invitations_handler, get_room_members_handler,
+            revoke_invitation_handler,
+        },
+        moderation::{
+            ban_handler, delete_message_handler, get_bans_handler, get_mutes_handler, kick_handler,
+            mute_handler, unban_handler, unmute_handler, update_role_handler,
+        },
+        outgoing_hooks::{
+            create_outgoing_hook_handler, get_hook_deliveries_handler, get_outgoing_hooks_handler,
+            revoke_outgoing_hook_handler,
+        },
+        ownership::{
+            accept_transfer_handler, cancel_transfer_handler, claim_orphaned_room_handler,
+            decline_transfer_handler, get_my_transfers_handler, get_room_transfer_handler,
+            request_transfer_handler,
+        },
         room::{
-            create_room_handler, delete_room_handler, get_all_room_info_handler,
-            get_owner_room_handler, get_specific_room_info,
+            archive_room_handler, create_room_handler, delete_room_handler,
+            get_all_room_info_handler, get_deleted_rooms_handler, get_expiring_rooms_handler,
+            get_owner_room_handler, get_retention_purges_handler, get_room_by_join_code_handler,
+            get_room_tags_handler, get_specific_room_info, join_room_handler, restore_room_handler,
+            unarchive_room_handler, update_room_handler, NEXT_CURSOR_HEADER,
         },
         users::{add_new_user, delete_user_handle, get_user_info_handle},
     },
     AppState,
 };
 
+// Slackエクスポートのアップロードの上限
+const MAX_IMPORT_BODY_SIZE: usize = 512 * 1024 * 1024;
+
 // ルーティング処理の実装
 pub fn app(app_state: AppState, origin: Vec<String>) -> Router {
     let origins: Vec<HeaderValue> = origin
@@ -35,18 +75,116 @@ pub fn app(app_state: AppState, origin: Vec<String>) -> Router {
                 .get(get_user_info_handle)
                 .delete(delete_user_handle),
         )
+        .route("/user/favorites", get(get_favorites_handler))
+        .route(
+            "/user/favorites/{room_id}",
+            put(put_favorite_handler).delete(delete_favorite_handler),
+        )
         .route("/login", post(login))
         .route(
             "/room",
             post(create_room_handler).get(get_all_room_info_handler),
         )
         .route("/room/self", get(get_owner_room_handler))
+        .route("/room/deleted", get(get_deleted_rooms_handler))
+        .route("/room/expiring", get(get_expiring_rooms_handler))
+        .route("/room/tags", get(get_room_tags_handler))
+        .route(
+            "/room/{id}",
+            get(get_specific_room_info)
+                .patch(update_room_handler)
+                .delete(delete_room_handler),
+        )
+        .route(
+            "/room/{id}/archive",
+            post(archive_room_handler).delete(unarchive_room_handler),
+        )
+        .route("/room/{id}/restore", post(restore_room_handler))
+        .route("/room/{id}/purges", get(get_retention_purges_handler))
+        .route("/room/{id}/audit", get(get_audit_log_handler))
+        .route(
+            "/room/{id}/messages/{message_id}",
+            delete(delete_message_handler),
+        )
+        .route("/room/{id}/export", get(export_room_handler))
+        .route("/room/{id}/join", post(join_room_handler))
+        .route("/room/{id}/members", get(get_room_members_handler))
+        .route(
+            "/room/{id}/members/{user_id}/role",
+            put(update_role_handler),
+        )
+        .route("/room/{id}/kick", post(kick_handler))
+        .route("/room/{id}/bans", post(ban_handler).get(get_bans_handler))
+        .route("/room/{id}/bans/{user_id}", delete(unban_handler))
+        .route(
+            "/room/{id}/mutes",
+            post(mute_handler).get(get_mutes_handler),
+        )
+        .route("/room/{id}/mutes/{user_id}", delete(unmute_handler))
+        .route(
+            "/room/{id}/invitations",
+            post(create_invitation_handler).get(get_room_invitations_handler),
+        )
+        .route(
+            "/room/{id}/invitations/{invitation_id}",
+            delete(revoke_invitation_handler),
+        )
+        .route(
+            "/room/{id}/invites",
+            post(create_invite_link_handler).get(get_room_invite_links_handler),
+        )
+        .route(
+            "/room/{id}/invites/{invite_id}",
+            delete(revoke_invite_link_handler),
+        )
+        .route("/invite/{token}/accept", post(accept_invite_link_handler))
+        .route(
+            "/room/{id}/transfer",
+            post(request_transfer_handler)
+                .get(get_room_transfer_handler)
+                .delete(cancel_transfer_handler),
+        )
+        .route("/transfers", get(get_my_transfers_handler))
+        .route("/transfers/{room_id}/accept", post(accept_transfer_handler))
+        .route(
+            "/transfers/{room_id}/decline",
+            post(decline_transfer_handler),
+        )
+        .route("/join/{code}", get(get_room_by_join_code_handler))
+        .route("/invitations", get(get_my_invitations_handler))
+        .route(
+            "/invitations/{invitation_id}/accept",
+            post(accept_invitation_handler),
+        )
+        .route(
+            "/invitations/{invitation_id}/decline",
+            post(decline_invitation_handler),
+        )
+        .route(
+            "/room/{id}/hooks",
+            post(create_hook_handler).get(get_room_hooks_handler),
+        )
+        .route("/room/{id}/hooks/{hook_id}", delete(revoke_hook_handler))
+        .route("/hooks/{token}", post(post_hook_message_handler))
+        .route(
+            "/room/{id}/outgoing-hooks",
+            post(create_outgoing_hook_handler).get(get_outgoing_hooks_handler),
+        )
+        .route(
+            "/room/{id}/outgoing-hooks/{hook_id}",
+            delete(revoke_outgoing_hook_handler),
+        )
+        .route(
+            "/room/{id}/outgoing-hooks/{hook_id}/deliveries",
+            get(get_hook_deliveries_handler),
+        )
+        .route("/admin/rooms/{id}/claim", post(claim_orphaned_room_handler))
         .route(
-            "/room/:id",
-            get(get_specific_room_info).delete(delete_room_handler),
+            "/admin/import/slack",
+            post(import_slack_handler).layer(DefaultBodyLimit::max(MAX_IMPORT_BODY_SIZE)),
         )
-        // ws://localhost:8080/chat/:id
-        .route("/chat/:id", get(chat_handler_with_upgrade))
+        // ws://localhost:8080/chat/{id}
+        .route("/chat/{id}", get(chat_handler_with_upgrade))
         .with_state(app_state)
         .layer(
             CorsLayer::new()
@@ -62,9 +200,11 @@ pub fn app(app_state: AppState, origin: Vec<String>) -> Router {
                     Method::POST,
                     Method::GET,
                     Method::PUT,
+                    Method::PATCH,
                     Method::DELETE,
                     Method::OPTIONS,
                 ])
+                .expose_headers([NEXT_CURSOR_HEADER])
                 .allow_credentials(true),
         )
 }
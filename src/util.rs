@@ -1,6 +1,5 @@
 use axum::{
-    async_trait,
-    extract::{rejection::JsonRejection, FromRequest, Request},
+    extract::{rejection::JsonRejection, FromRequest, OptionalFromRequest, Request},
     response::{IntoResponse, Response},
     Json,
 };
@@ -11,7 +10,6 @@ use validator::Validate;
 #[derive(Debug, Clone)]
 pub struct ValidatedJson<T>(pub T);
 
-#[async_trait]
 impl<S, T> FromRequest<S> for ValidatedJson<T>
 where
     T: Validate,
@@ -20,12 +18,31 @@ where
 {
     type Rejection = ServerError;
     async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
-        let Json(body) = Json::from_request(req, state).await?;
+        let Json(body) = <Json<T> as FromRequest<S>>::from_request(req, state).await?;
         body.validate()?;
         Ok(ValidatedJson(body))
     }
 }
 
+// Content-Typeのないリクエストはボディを省略したものとしてNoneになる
+impl<S, T> OptionalFromRequest<S> for ValidatedJson<T>
+where
+    T: Validate,
+    S: Send + Sync,
+    Json<T>: OptionalFromRequest<S, Rejection = JsonRejection>,
+{
+    type Rejection = ServerError;
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        let Some(Json(body)) =
+            <Json<T> as OptionalFromRequest<S>>::from_request(req, state).await?
+        else {
+            return Ok(None);
+        };
+        body.validate()?;
+        Ok(Some(ValidatedJson(body)))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ServerError {
     #[error(transparent)]